@@ -3,7 +3,10 @@
 //! Handlers must declare required capabilities in NXML. The runtime enforces
 //! these capabilities at every host function call.
 
+use crate::context::RuntimeValue;
+use crate::error::RuntimeError;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 
 /// Capability token format
@@ -18,7 +21,8 @@ pub enum CapabilityToken {
     StateReadAll,
     /// Write all state: `state:write:*`
     StateWriteAll,
-    /// Emit specific event: `events:emit:{name}`
+    /// Emit specific event, or a `.`-namespaced family of them: `events:emit:{name_glob}`
+    /// (see [`scope_matches`] for the `*`/`**` glob grammar)
     EventsEmit(String),
     /// Emit all events: `events:emit:*`
     EventsEmitAll,
@@ -30,6 +34,20 @@ pub enum CapabilityToken {
     Extension(String),
     /// Access all extensions: `ext:*`
     ExtensionAll,
+    /// Access a specific method on a specific extension, optionally
+    /// narrowed by argument predicates: `ext:{ext}:{method}`. The
+    /// `constraints` aren't part of the wire form (parsing `ext:{ext}:{method}`
+    /// always produces an empty list, mirroring how [`Caveat`]s augment a
+    /// [`Capability`] rather than living inside [`CapabilityToken`] itself) —
+    /// attach them after construction when building grants in code.
+    ExtensionMethod {
+        /// Extension name
+        ext: String,
+        /// Method name
+        method: String,
+        /// Predicates every call's arguments must satisfy, checked in order
+        constraints: Vec<ArgConstraint>,
+    },
 }
 
 impl CapabilityToken {
@@ -48,6 +66,11 @@ impl CapabilityToken {
             ["view", "update", id] => Some(Self::ViewUpdate((*id).to_string())),
             ["ext", "*"] => Some(Self::ExtensionAll),
             ["ext", name] => Some(Self::Extension((*name).to_string())),
+            ["ext", name, method] => Some(Self::ExtensionMethod {
+                ext: (*name).to_string(),
+                method: (*method).to_string(),
+                constraints: Vec::new(),
+            }),
             _ => None,
         }
     }
@@ -60,28 +83,39 @@ impl CapabilityToken {
         match (self, parts.as_slice()) {
             // State read
             (Self::StateReadAll, ["state", "read", _]) => true,
-            (Self::StateRead(key), ["state", "read", k]) => key == *k,
-            
+            (Self::StateRead(key), ["state", "read", k]) => scope_matches(key, k),
+
             // State write
             (Self::StateWriteAll, ["state", "write", _]) => true,
-            (Self::StateWrite(key), ["state", "write", k]) => key == *k,
-            
+            (Self::StateWrite(key), ["state", "write", k]) => scope_matches(key, k),
+
             // Events
             (Self::EventsEmitAll, ["events", "emit", _]) => true,
-            (Self::EventsEmit(name), ["events", "emit", n]) => name == *n,
-            
+            (Self::EventsEmit(name), ["events", "emit", n]) => scope_matches(name, n),
+
             // View
             (Self::ViewUpdateAll, ["view", "update", _]) => true,
-            (Self::ViewUpdate(id), ["view", "update", i]) => id == *i,
-            
+            (Self::ViewUpdate(id), ["view", "update", i]) => scope_matches(id, i),
+
             // Extensions
             (Self::ExtensionAll, ["ext", _]) => true,
-            (Self::Extension(name), ["ext", n]) => name == *n,
-            
+            (Self::Extension(name), ["ext", n]) => scope_matches(name, n),
+            (Self::ExtensionMethod { ext, method, .. }, ["ext", n, m]) => {
+                scope_matches(ext, n) && method == *m
+            }
+
             _ => false,
         }
     }
 
+    /// Check whether this capability fully covers (implies) `other` — every
+    /// access `other` would permit is also permitted by `self`. Used to
+    /// enforce the attenuation invariant when delegating capabilities to a
+    /// sub-handler: a delegated set may only ever narrow, never widen.
+    pub fn implies(&self, other: &CapabilityToken) -> bool {
+        self.matches(&other.to_string_repr())
+    }
+
     /// Convert to string representation
     pub fn to_string_repr(&self) -> String {
         match self {
@@ -95,6 +129,7 @@ impl CapabilityToken {
             Self::ViewUpdateAll => "view:update:*".to_string(),
             Self::Extension(name) => format!("ext:{}", name),
             Self::ExtensionAll => "ext:*".to_string(),
+            Self::ExtensionMethod { ext, method, .. } => format!("ext:{}:{}", ext, method),
         }
     }
 }
@@ -105,6 +140,31 @@ impl fmt::Display for CapabilityToken {
     }
 }
 
+/// Check whether a granted scope covers a required scope, splitting both on
+/// `.` and matching segment-by-segment: `*` matches exactly one segment and
+/// a trailing `**` matches the remainder at any depth (including zero
+/// segments). Mirrors the path-based capability routing used by Fuchsia
+/// component manifests, so namespaced keys like `user.profile.email` can be
+/// granted by a prefix like `user.*` (one level) or `user.**` (any depth).
+fn scope_matches(granted: &str, required: &str) -> bool {
+    if granted == required {
+        return true;
+    }
+
+    fn segments_match(granted: &[&str], required: &[&str]) -> bool {
+        match granted.first() {
+            None => required.is_empty(),
+            Some(&"**") => true,
+            Some(&"*") => !required.is_empty() && segments_match(&granted[1..], &required[1..]),
+            Some(seg) => required.first() == Some(seg) && segments_match(&granted[1..], &required[1..]),
+        }
+    }
+
+    let granted_segs: Vec<&str> = granted.split('.').collect();
+    let required_segs: Vec<&str> = required.split('.').collect();
+    segments_match(&granted_segs, &required_segs)
+}
+
 impl From<String> for CapabilityToken {
     fn from(s: String) -> Self {
         Self::parse(&s).unwrap_or(Self::Extension(s))
@@ -127,17 +187,28 @@ pub struct Capability {
 
     /// Scope (specific key/event/extension or '*' for all)
     pub scope: String,
+
+    /// Constraints narrowing when or how this capability applies
+    #[serde(default)]
+    pub caveats: Vec<Caveat>,
 }
 
 impl Capability {
-    /// Create a new capability
+    /// Create a new capability with no caveats
     pub fn new(cap_type: CapabilityType, scope: impl Into<String>) -> Self {
         Self {
             cap_type,
             scope: scope.into(),
+            caveats: Vec::new(),
         }
     }
 
+    /// Attach caveats to this capability
+    pub fn with_caveats(mut self, caveats: Vec<Caveat>) -> Self {
+        self.caveats = caveats;
+        self
+    }
+
     /// Convert to a capability token
     pub fn to_token(&self) -> CapabilityToken {
         match (&self.cap_type, self.scope.as_str()) {
@@ -153,6 +224,246 @@ impl Capability {
             (CapabilityType::Extension, name) => CapabilityToken::Extension(name.to_string()),
         }
     }
+
+    /// Convert to a capability grant, carrying this capability's caveats
+    /// along with its token
+    pub fn to_grant(&self) -> CapabilityGrant {
+        CapabilityGrant {
+            token: self.to_token(),
+            caveats: self.caveats.clone(),
+        }
+    }
+}
+
+/// A constraint narrowing when or how a capability may be exercised, similar
+/// to a UCAN caveat (e.g. granting `state:write:count` only up to a maximum
+/// value, or only within a time window).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Caveat {
+    /// A written numeric value must not exceed this maximum
+    ValueMax(f64),
+    /// A written value must match this regex pattern
+    ValuePattern(String),
+    /// Only valid within this Unix-timestamp window (seconds); either bound
+    /// may be omitted to leave that side unconstrained
+    TimeWindow {
+        /// Not valid before this timestamp (inclusive), if set
+        not_before: Option<i64>,
+        /// Not valid after this timestamp (inclusive), if set
+        not_after: Option<i64>,
+    },
+    /// At most `max_calls` uses per `per_secs`-second window
+    RateLimit {
+        /// Maximum calls allowed per window
+        max_calls: u32,
+        /// Window length in seconds
+        per_secs: u32,
+    },
+}
+
+impl Caveat {
+    /// Check whether this caveat is satisfied by a candidate write of
+    /// `value` at `now_unix_secs`, given `calls_in_window` prior calls
+    /// counted against this grant's rate limit window
+    pub fn is_satisfied(&self, value: &RuntimeValue, now_unix_secs: i64, calls_in_window: u32) -> bool {
+        match self {
+            Caveat::ValueMax(max) => value.as_number().is_some_and(|n| n <= *max),
+            Caveat::ValuePattern(pattern) => value
+                .as_str()
+                .and_then(|s| regex::Regex::new(pattern).ok().map(|re| re.is_match(s)))
+                .unwrap_or(false),
+            Caveat::TimeWindow {
+                not_before,
+                not_after,
+            } => {
+                not_before.is_none_or(|nb| now_unix_secs >= nb)
+                    && not_after.is_none_or(|na| now_unix_secs <= na)
+            }
+            Caveat::RateLimit { max_calls, .. } => calls_in_window < *max_calls,
+        }
+    }
+
+    /// Encode as the compact form used inside a `?caveat=...` suffix
+    fn encode(&self) -> String {
+        match self {
+            Caveat::ValueMax(max) => format!("value_max:{}", max),
+            Caveat::ValuePattern(pattern) => format!("value_pattern:{}", pattern),
+            Caveat::TimeWindow {
+                not_before,
+                not_after,
+            } => format!(
+                "time_window:{},{}",
+                not_before.map(|v| v.to_string()).unwrap_or_else(|| "*".to_string()),
+                not_after.map(|v| v.to_string()).unwrap_or_else(|| "*".to_string()),
+            ),
+            Caveat::RateLimit { max_calls, per_secs } => {
+                format!("rate_limit:{}/{}", max_calls, per_secs)
+            }
+        }
+    }
+
+    /// Decode the compact form used inside a `?caveat=...` suffix
+    fn decode(s: &str) -> Option<Self> {
+        let (kind, rest) = s.split_once(':')?;
+        match kind {
+            "value_max" => rest.parse().ok().map(Caveat::ValueMax),
+            "value_pattern" => Some(Caveat::ValuePattern(rest.to_string())),
+            "time_window" => {
+                let (nb, na) = rest.split_once(',')?;
+                Some(Caveat::TimeWindow {
+                    not_before: (nb != "*").then(|| nb.parse().ok()).flatten(),
+                    not_after: (na != "*").then(|| na.parse().ok()).flatten(),
+                })
+            }
+            "rate_limit" => {
+                let (mc, ps) = rest.split_once('/')?;
+                Some(Caveat::RateLimit {
+                    max_calls: mc.parse().ok()?,
+                    per_secs: ps.parse().ok()?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A predicate over an extension call's arguments, attached to a
+/// [`CapabilityToken::ExtensionMethod`] so a handler can be granted a method
+/// without being trusted with every argument it could possibly pass (e.g.
+/// `http.get` narrowed to a hostname allowlist, or `storage.read` narrowed
+/// to a key prefix).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgConstraint {
+    /// The first argument must be a string whose host component (the part
+    /// of a `scheme://host/path` URL, or a bare hostname) is in this list
+    HostAllowlist(Vec<String>),
+    /// The first argument must be a string starting with this prefix
+    KeyPrefix(String),
+    /// No argument's length (string chars, or array/object entry count) may
+    /// exceed this
+    MaxArgLen(usize),
+}
+
+impl ArgConstraint {
+    /// Check `args` against this constraint, returning a description of
+    /// what failed, or `None` if the call is allowed. An argument list that
+    /// doesn't carry the type this constraint expects (e.g. `KeyPrefix`
+    /// against a call with no arguments) is treated as a violation rather
+    /// than silently passing.
+    pub fn check(&self, args: &[RuntimeValue]) -> Option<String> {
+        match self {
+            ArgConstraint::HostAllowlist(hosts) => {
+                let Some(arg) = args.first().and_then(RuntimeValue::as_str) else {
+                    return Some("first argument must be a URL or hostname string".to_string());
+                };
+                let host = host_component(arg);
+                if hosts.iter().any(|h| h == host) {
+                    None
+                } else {
+                    Some(format!("host '{}' is not in the allowlist", host))
+                }
+            }
+            ArgConstraint::KeyPrefix(prefix) => {
+                let Some(arg) = args.first().and_then(RuntimeValue::as_str) else {
+                    return Some("first argument must be a string key".to_string());
+                };
+                if arg.starts_with(prefix.as_str()) {
+                    None
+                } else {
+                    Some(format!(
+                        "argument '{}' does not start with required prefix '{}'",
+                        arg, prefix
+                    ))
+                }
+            }
+            ArgConstraint::MaxArgLen(max) => args.iter().enumerate().find_map(|(i, arg)| {
+                let len = arg_len(arg);
+                (len > *max).then(|| format!("argument {} has length {}, exceeding max {}", i, len, max))
+            }),
+        }
+    }
+}
+
+/// Extract the host component from a bare hostname or a `scheme://host/path`
+/// URL: strip a leading `<scheme>://`, then take up to the next `/`, `:`, or
+/// `?`.
+fn host_component(arg: &str) -> &str {
+    let without_scheme = arg.split_once("://").map_or(arg, |(_, rest)| rest);
+    let end = without_scheme
+        .find(['/', ':', '?'])
+        .unwrap_or(without_scheme.len());
+    &without_scheme[..end]
+}
+
+/// Length of a value for [`ArgConstraint::MaxArgLen`]: character count for
+/// strings, entry count for arrays/objects, `0` for everything else.
+fn arg_len(value: &RuntimeValue) -> usize {
+    match value {
+        RuntimeValue::String(s) => s.chars().count(),
+        RuntimeValue::Array(a) => a.len(),
+        RuntimeValue::Object(o) => o.len(),
+        _ => 0,
+    }
+}
+
+/// A [`CapabilityToken`] together with any caveats narrowing when or how it
+/// may be exercised.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapabilityGrant {
+    /// The underlying capability being granted
+    pub token: CapabilityToken,
+    /// Constraints narrowing when/how `token` applies
+    pub caveats: Vec<Caveat>,
+}
+
+impl CapabilityGrant {
+    /// Create a grant with no caveats
+    pub fn new(token: CapabilityToken) -> Self {
+        Self {
+            token,
+            caveats: Vec::new(),
+        }
+    }
+
+    /// Attach a caveat to this grant
+    pub fn with_caveat(mut self, caveat: Caveat) -> Self {
+        self.caveats.push(caveat);
+        self
+    }
+
+    /// Parse a grant from its wire form: a capability token string, optionally
+    /// followed by one or more `?caveat=...`/`&caveat=...` suffixes. Backward
+    /// compatible with a bare token string carrying no caveats.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(2, '?');
+        let token = CapabilityToken::parse(parts.next()?)?;
+
+        let mut caveats = Vec::new();
+        if let Some(query) = parts.next() {
+            for pair in query.split('&') {
+                let (key, value) = pair.split_once('=')?;
+                if key == "caveat" {
+                    caveats.push(Caveat::decode(value)?);
+                }
+            }
+        }
+
+        Some(Self { token, caveats })
+    }
+
+    /// Convert to the `token?caveat=...&caveat=...` wire representation. A
+    /// grant with no caveats round-trips to the bare token string.
+    pub fn to_string_repr(&self) -> String {
+        let mut s = self.token.to_string_repr();
+        for (i, caveat) in self.caveats.iter().enumerate() {
+            s.push(if i == 0 { '?' } else { '&' });
+            s.push_str("caveat=");
+            s.push_str(&caveat.encode());
+        }
+        s
+    }
 }
 
 /// Capability types
@@ -178,152 +489,737 @@ pub enum CapabilityType {
 
 /// Capability checker for runtime enforcement
 pub struct CapabilityChecker {
-    capabilities: Vec<CapabilityToken>,
+    grants: Vec<CapabilityGrant>,
 }
 
 impl CapabilityChecker {
-    /// Create a new capability checker
+    /// Create a new capability checker from bare tokens, none of which carry
+    /// any caveats
     pub fn new(capabilities: Vec<CapabilityToken>) -> Self {
-        Self { capabilities }
+        Self {
+            grants: capabilities.into_iter().map(CapabilityGrant::new).collect(),
+        }
+    }
+
+    /// Create a new capability checker from grants, which may carry caveats
+    pub fn from_grants(grants: Vec<CapabilityGrant>) -> Self {
+        Self { grants }
     }
 
     /// Check if a state read is allowed
     pub fn can_read_state(&self, key: &str) -> bool {
         let required = format!("state:read:{}", key);
-        self.capabilities.iter().any(|c| c.matches(&required))
+        self.grants.iter().any(|g| g.token.matches(&required))
     }
 
-    /// Check if a state write is allowed
+    /// Check if a state write is allowed, ignoring any caveats on the
+    /// matching grant. Use [`Self::can_write_state_with_caveats`] to also
+    /// enforce them.
     pub fn can_write_state(&self, key: &str) -> bool {
         let required = format!("state:write:{}", key);
-        self.capabilities.iter().any(|c| c.matches(&required))
+        self.grants.iter().any(|g| g.token.matches(&required))
+    }
+
+    /// Check if a state write of `value` is allowed at `now_unix_secs`,
+    /// enforcing any caveats (value bounds, time window, rate limit) on the
+    /// matching grant. `calls_in_window` is the number of prior writes to
+    /// `key` already counted against a rate-limit caveat's window.
+    pub fn can_write_state_with_caveats(
+        &self,
+        key: &str,
+        value: &RuntimeValue,
+        now_unix_secs: i64,
+        calls_in_window: u32,
+    ) -> bool {
+        let required = format!("state:write:{}", key);
+        self.grants
+            .iter()
+            .filter(|g| g.token.matches(&required))
+            .any(|g| {
+                g.caveats
+                    .iter()
+                    .all(|c| c.is_satisfied(value, now_unix_secs, calls_in_window))
+            })
     }
 
     /// Check if an event emission is allowed
     pub fn can_emit_event(&self, event_name: &str) -> bool {
         let required = format!("events:emit:{}", event_name);
-        self.capabilities.iter().any(|c| c.matches(&required))
+        self.grants.iter().any(|g| g.token.matches(&required))
     }
 
     /// Check if a view update is allowed
     pub fn can_update_view(&self, component_id: &str) -> bool {
         let required = format!("view:update:{}", component_id);
-        self.capabilities.iter().any(|c| c.matches(&required))
+        self.grants.iter().any(|g| g.token.matches(&required))
     }
 
     /// Check if an extension access is allowed
     pub fn can_access_extension(&self, ext_name: &str) -> bool {
         let required = format!("ext:{}", ext_name);
-        self.capabilities.iter().any(|c| c.matches(&required))
+        self.grants.iter().any(|g| g.token.matches(&required))
     }
 
     /// Check any capability
     pub fn check(&self, required: &str) -> bool {
-        self.capabilities.iter().any(|c| c.matches(required))
+        self.grants.iter().any(|g| g.token.matches(required))
+    }
+
+    /// Construct a checker from a verified delegation chain, enforcing only
+    /// the leaf (most-attenuated) capability set
+    pub fn from_delegation_chain(chain: &DelegationChain) -> Result<Self, RuntimeError> {
+        Ok(Self::new(chain.verify()?))
     }
 }
 
-/// Infer capabilities from handler code (static analysis)
-pub fn infer_capabilities(handler_code: &str) -> Vec<CapabilityToken> {
-    let mut capabilities = Vec::new();
-    
-    // Simple regex-based detection
-    // In a real implementation, use a proper JS parser
-    
-    // Detect $state reads: $state.key or $state['key']
-    for cap in find_state_access(handler_code, false) {
-        if !capabilities.contains(&cap) {
-            capabilities.push(cap);
-        }
-    }
-    
-    // Detect $state writes: $state.key = ... or $state['key'] = ...
-    for cap in find_state_access(handler_code, true) {
-        if !capabilities.contains(&cap) {
-            capabilities.push(cap);
-        }
-    }
-    
-    // Detect $emit calls: $emit('event', ...)
-    for cap in find_emit_calls(handler_code) {
-        if !capabilities.contains(&cap) {
-            capabilities.push(cap);
-        }
-    }
-    
-    // Detect $ext access: $ext.name.method(...)
-    for cap in find_extension_access(handler_code) {
-        if !capabilities.contains(&cap) {
-            capabilities.push(cap);
-        }
-    }
-    
-    capabilities
-}
-
-/// Find state access patterns (simple regex-based)
-fn find_state_access(code: &str, writes_only: bool) -> Vec<CapabilityToken> {
-    let mut caps = Vec::new();
-    
-    // Pattern: $state.key
-    let re = if writes_only {
-        regex::Regex::new(r"\$state\.(\w+)\s*=").ok()
-    } else {
-        regex::Regex::new(r"\$state\.(\w+)").ok()
-    };
-    
-    if let Some(re) = re {
-        for cap in re.captures_iter(code) {
-            let key = cap.get(1).map(|m| m.as_str()).unwrap_or("");
-            if !key.is_empty() {
-                if writes_only {
-                    caps.push(CapabilityToken::StateWrite(key.to_string()));
+/// A chain of capability sets delegated from a root handler down to a leaf
+/// sub-handler (e.g. a dynamically loaded child component), each link
+/// granted by — and therefore no broader than — the link above it.
+///
+/// This mirrors the delegation/proof model used by UCAN: every capability
+/// held at level `n + 1` must be backed by an authorizing capability at
+/// level `n`, so [`Self::verify`] guarantees a child can never end up with
+/// more access than its parent held.
+#[derive(Debug, Clone, Default)]
+pub struct DelegationChain {
+    links: Vec<Vec<CapabilityToken>>,
+}
+
+impl DelegationChain {
+    /// Start a chain rooted at the capability set held by the delegating
+    /// handler
+    pub fn new(root: Vec<CapabilityToken>) -> Self {
+        Self { links: vec![root] }
+    }
+
+    /// Append a (possibly attenuated) capability set delegated to a child,
+    /// returning the extended chain
+    pub fn delegate(mut self, capabilities: Vec<CapabilityToken>) -> Self {
+        self.links.push(capabilities);
+        self
+    }
+
+    /// Walk the chain root to leaf and check the attenuation invariant:
+    /// every token at level `n + 1` must be implied by some token at level
+    /// `n`. Returns the leaf (most-attenuated) capability set on success.
+    pub fn verify(&self) -> Result<Vec<CapabilityToken>, RuntimeError> {
+        if self.links.is_empty() {
+            return Err(RuntimeError::Delegation("empty delegation chain".to_string()));
+        }
+
+        for window in self.links.windows(2) {
+            let (parent, child) = (&window[0], &window[1]);
+            for token in child {
+                if !parent.iter().any(|p| p.implies(token)) {
+                    return Err(RuntimeError::Delegation(format!(
+                        "delegated capability {} exceeds what its parent held",
+                        token
+                    )));
+                }
+            }
+        }
+
+        Ok(self.links.last().cloned().unwrap_or_default())
+    }
+}
+
+/// Where a capability a handler [`RoutedCapability`] offers down actually
+/// comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteSource {
+    /// The offering handler owns this capability itself.
+    SelfOwned,
+    /// The offering handler is forwarding a capability that was itself
+    /// routed down to it from its own parent. Resolution keeps walking
+    /// upward through the tree until it finds the `SelfOwned` offer (or
+    /// runs out of ancestors).
+    Parent,
+}
+
+/// Which child handler(s) a [`RoutedCapability`] is routed to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteTarget {
+    /// Routed to one specific named child handler.
+    Handler(String),
+    /// Routed to every child handler.
+    AllHandlers,
+}
+
+/// A capability offered from one handler down to a child (or all children),
+/// modeled after Fuchsia CML's `offer`/`expose` declarations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutedCapability {
+    /// Capability type being offered
+    pub kind: CapabilityType,
+    /// Scope (specific key/event/extension or '*' for all)
+    pub scope: String,
+    /// Where the offering handler itself got this capability from
+    pub from: RouteSource,
+    /// Which child handler(s) it is offered to
+    pub to: RouteTarget,
+}
+
+impl RoutedCapability {
+    /// Create a new routed capability
+    pub fn new(
+        kind: CapabilityType,
+        scope: impl Into<String>,
+        from: RouteSource,
+        to: RouteTarget,
+    ) -> Self {
+        Self {
+            kind,
+            scope: scope.into(),
+            from,
+            to,
+        }
+    }
+
+    /// The underlying capability token being routed
+    pub fn to_token(&self) -> CapabilityToken {
+        Capability::new(self.kind, self.scope.clone()).to_token()
+    }
+}
+
+/// One handler's position in a multi-handler capability composition tree:
+/// its parent (if any), the capabilities it requires (`use`), and the
+/// capabilities it offers down to its children (`offer`).
+#[derive(Debug, Clone)]
+pub struct HandlerRoute {
+    /// Unique name identifying this handler in the tree
+    pub name: String,
+    /// The parent handler's name, or `None` for the root of the composition
+    pub parent: Option<String>,
+    /// Capabilities this handler requires in order to run
+    pub uses: Vec<CapabilityToken>,
+    /// Capabilities this handler offers down to its children
+    pub offers: Vec<RoutedCapability>,
+}
+
+impl HandlerRoute {
+    /// Create a new, parentless handler route with no uses or offers
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            parent: None,
+            uses: Vec::new(),
+            offers: Vec::new(),
+        }
+    }
+
+    /// Set this handler's parent in the composition tree
+    pub fn with_parent(mut self, parent: impl Into<String>) -> Self {
+        self.parent = Some(parent.into());
+        self
+    }
+
+    /// Declare a capability this handler requires
+    pub fn with_use(mut self, token: CapabilityToken) -> Self {
+        self.uses.push(token);
+        self
+    }
+
+    /// Declare a capability this handler offers down to its children
+    pub fn with_offer(mut self, offer: RoutedCapability) -> Self {
+        self.offers.push(offer);
+        self
+    }
+}
+
+/// Resolves `use`/`offer` declarations across a tree of [`HandlerRoute`]s
+/// into a concrete, per-handler [`CapabilityChecker`].
+///
+/// This turns a flat, per-handler capability list into a verifiable
+/// composition graph: a handler only gets a capability if some ancestor
+/// actually offered it down to it (directly, or transitively via a
+/// [`RouteSource::Parent`] passthrough), rather than every handler
+/// independently declaring everything it might ever touch.
+pub struct CapabilityRouter {
+    handlers: HashMap<String, HandlerRoute>,
+}
+
+impl CapabilityRouter {
+    /// Build a router over the given handler routes
+    pub fn new(handlers: Vec<HandlerRoute>) -> Self {
+        Self {
+            handlers: handlers.into_iter().map(|h| (h.name.clone(), h)).collect(),
+        }
+    }
+
+    /// Resolve every handler's `use` requests against the routing graph,
+    /// producing a [`CapabilityChecker`] per handler name.
+    ///
+    /// Fails with [`RuntimeError::Routing`] on the first unrouted `use`
+    /// (a capability requested but never offered by any ancestor) or
+    /// dangling `offer` (offered to a handler name that doesn't exist in
+    /// the tree) it encounters.
+    pub fn resolve(&self) -> Result<HashMap<String, CapabilityChecker>, RuntimeError> {
+        self.check_dangling_offers()?;
+
+        let mut resolved = HashMap::new();
+        for handler in self.handlers.values() {
+            let mut granted = Vec::new();
+            for token in &handler.uses {
+                if self.is_routed(handler, token)? {
+                    granted.push(token.clone());
                 } else {
-                    caps.push(CapabilityToken::StateRead(key.to_string()));
+                    return Err(RuntimeError::Routing(format!(
+                        "handler '{}' uses {} but no ancestor offers it",
+                        handler.name, token
+                    )));
                 }
             }
+            resolved.insert(handler.name.clone(), CapabilityChecker::new(granted));
+        }
+        Ok(resolved)
+    }
+
+    /// Check every `offer` in the tree names a handler that actually exists
+    fn check_dangling_offers(&self) -> Result<(), RuntimeError> {
+        for handler in self.handlers.values() {
+            for offer in &handler.offers {
+                if let RouteTarget::Handler(target) = &offer.to {
+                    if !self.handlers.contains_key(target) {
+                        return Err(RuntimeError::Routing(format!(
+                            "handler '{}' offers {} to unknown handler '{}'",
+                            handler.name,
+                            offer.to_token(),
+                            target
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Walk up from `handler` looking for an ancestor that offers `token`
+    /// down to it, following `RouteSource::Parent` passthroughs as far up
+    /// the tree as necessary.
+    fn is_routed(&self, handler: &HandlerRoute, token: &CapabilityToken) -> Result<bool, RuntimeError> {
+        let mut current = handler;
+        loop {
+            let Some(parent_name) = &current.parent else {
+                return Ok(false);
+            };
+            let Some(parent) = self.handlers.get(parent_name) else {
+                return Err(RuntimeError::Routing(format!(
+                    "handler '{}' has unknown parent '{}'",
+                    current.name, parent_name
+                )));
+            };
+
+            let offer = parent
+                .offers
+                .iter()
+                .filter(|o| match &o.to {
+                    RouteTarget::AllHandlers => true,
+                    RouteTarget::Handler(name) => name == &current.name,
+                })
+                .find(|o| o.to_token().matches(&token.to_string_repr()));
+
+            match offer {
+                None => return Ok(false),
+                Some(o) if o.from == RouteSource::SelfOwned => return Ok(true),
+                Some(_) => current = parent,
+            }
+        }
+    }
+}
+
+/// Result of statically inferring capabilities from handler code
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InferredCapabilities {
+    /// Capabilities the handler appears to require
+    pub capabilities: Vec<CapabilityToken>,
+    /// Human-readable descriptions of accesses that could not be resolved to
+    /// a static scope (e.g. a computed `$state[expr]` or a templated event
+    /// name) and were conservatively widened to a `*` capability instead.
+    /// Callers should surface these so handler authors can declare the
+    /// capability explicitly rather than relying on inference.
+    pub widened: Vec<String>,
+}
+
+/// Infer capabilities from handler code via a token-level scan of the
+/// source, ignoring the `.widened` detail. See
+/// [`infer_capabilities_detailed`] for the full result, including accesses
+/// that could not be statically resolved.
+pub fn infer_capabilities(handler_code: &str) -> Vec<CapabilityToken> {
+    infer_capabilities_detailed(handler_code).capabilities
+}
+
+/// Infer capabilities from handler code by tokenizing it and walking
+/// member-access, assignment, destructuring, and call expressions rooted at
+/// `$state`/`$ext`/`$emit` — including through simple local aliases (`const
+/// s = $state; s.count = 1`). Tokenizing (rather than matching regexes
+/// against raw text) means identifiers inside string and comment tokens are
+/// never mistaken for real accesses.
+///
+/// A static string key (`$state.count`, `$state['count']`) resolves to a
+/// specific scope; a computed key (`$state[dynamicExpr]`) or a templated
+/// event name (`` $emit(`evt-${id}`) ``) cannot be resolved statically, so
+/// it is conservatively widened to the `*` wildcard and recorded in
+/// [`InferredCapabilities::widened`].
+pub fn infer_capabilities_detailed(handler_code: &str) -> InferredCapabilities {
+    let tokens = tokenize(handler_code);
+    let mut result = InferredCapabilities::default();
+    let mut aliases: HashMap<String, Root> = HashMap::new();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        if let Lexeme::Ident(ident) = &tokens[i] {
+            if matches!(ident.as_str(), "const" | "let" | "var") {
+                if let Some(consumed) = scan_declaration(&tokens[i + 1..], &mut aliases, &mut result) {
+                    i += 1 + consumed;
+                    continue;
+                }
+            } else if ident == "$emit" {
+                if let Some((consumed, event)) = scan_emit_call(&tokens[i + 1..]) {
+                    match event {
+                        Some(name) => push_capability(&mut result.capabilities, CapabilityToken::EventsEmit(name)),
+                        None => {
+                            push_capability(&mut result.capabilities, CapabilityToken::EventsEmitAll);
+                            result
+                                .widened
+                                .push("$emit(...) called with a non-literal event name".to_string());
+                        }
+                    }
+                    i += 1 + consumed;
+                    continue;
+                }
+            } else {
+                let root = match ident.as_str() {
+                    "$state" => Some(Root::State),
+                    "$ext" => Some(Root::Ext),
+                    other => aliases.get(other).copied(),
+                };
+
+                if let Some(root) = root {
+                    if let Some((consumed, access)) = scan_member_access(&tokens[i + 1..]) {
+                        record_member_access(root, access, &mut result);
+                        i += 1 + consumed;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        i += 1;
+    }
+
+    result
+}
+
+/// What a `$state`/`$ext` identifier resolves to, directly or through a
+/// simple local alias
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Root {
+    /// Rooted at `$state`
+    State,
+    /// Rooted at `$ext`
+    Ext,
+}
+
+/// A resolved member access: a static key (and whether it's a write) or a
+/// computed key that couldn't be resolved statically
+#[derive(Debug, Clone, PartialEq)]
+enum MemberAccess {
+    /// `.key` or `['key']`, and whether it's immediately assigned to
+    Static(String, bool),
+    /// `[expr]` where `expr` isn't a string literal
+    Dynamic(bool),
+}
+
+fn record_member_access(root: Root, access: MemberAccess, result: &mut InferredCapabilities) {
+    match (root, access) {
+        (Root::State, MemberAccess::Static(key, true)) => {
+            push_capability(&mut result.capabilities, CapabilityToken::StateWrite(key));
+        }
+        (Root::State, MemberAccess::Static(key, false)) => {
+            push_capability(&mut result.capabilities, CapabilityToken::StateRead(key));
+        }
+        (Root::State, MemberAccess::Dynamic(is_write)) => {
+            push_capability(
+                &mut result.capabilities,
+                if is_write {
+                    CapabilityToken::StateWriteAll
+                } else {
+                    CapabilityToken::StateReadAll
+                },
+            );
+            result
+                .widened
+                .push("$state accessed with a computed key".to_string());
         }
+        (Root::Ext, MemberAccess::Static(key, _)) => {
+            push_capability(&mut result.capabilities, CapabilityToken::Extension(key));
+        }
+        (Root::Ext, MemberAccess::Dynamic(_)) => {
+            push_capability(&mut result.capabilities, CapabilityToken::ExtensionAll);
+            result
+                .widened
+                .push("$ext accessed with a computed key".to_string());
+        }
+    }
+}
+
+fn push_capability(capabilities: &mut Vec<CapabilityToken>, cap: CapabilityToken) {
+    if !capabilities.contains(&cap) {
+        capabilities.push(cap);
     }
-    
-    caps
 }
 
-/// Find $emit calls
-fn find_emit_calls(code: &str) -> Vec<CapabilityToken> {
-    let mut caps = Vec::new();
-    
-    // Pattern: $emit('event_name', ...)
-    let re = regex::Regex::new(r#"\$emit\s*\(\s*['"](\w+)['"]"#).ok();
-    
-    if let Some(re) = re {
-        for cap in re.captures_iter(code) {
-            let event = cap.get(1).map(|m| m.as_str()).unwrap_or("");
-            if !event.is_empty() {
-                caps.push(CapabilityToken::EventsEmit(event.to_string()));
+/// Try to scan a `const|let|var <binding> = $state|$ext` or
+/// `const|let|var { a, b } = $state` declaration starting right after the
+/// `const`/`let`/`var` keyword. The former records a local alias to resolve
+/// later accesses through; the latter records an immediate state read per
+/// destructured binding. Returns the number of tokens consumed.
+fn scan_declaration(
+    tokens: &[Lexeme],
+    aliases: &mut HashMap<String, Root>,
+    result: &mut InferredCapabilities,
+) -> Option<usize> {
+    match tokens.first()? {
+        Lexeme::Ident(binding) => {
+            if tokens.get(1) != Some(&Lexeme::Symbol('=')) {
+                return None;
+            }
+            let root = match tokens.get(2)? {
+                Lexeme::Ident(name) if name == "$state" => Root::State,
+                Lexeme::Ident(name) if name == "$ext" => Root::Ext,
+                _ => return None,
+            };
+            // Only register an alias for a bare reference (`const s = $state;`).
+            // If `$state`/`$ext` is immediately followed by member access (e.g.
+            // `const name = $state.user.name;`), leave it unconsumed so the main
+            // scan loop records the access itself instead of us swallowing it.
+            if matches!(
+                tokens.get(3),
+                Some(Lexeme::Symbol('.')) | Some(Lexeme::Symbol('['))
+            ) {
+                return None;
             }
+            aliases.insert(binding.clone(), root);
+            Some(3)
         }
+        Lexeme::Symbol('{') => {
+            let mut consumed = 1;
+            let mut bindings = Vec::new();
+            while let Some(tok) = tokens.get(consumed) {
+                match tok {
+                    Lexeme::Ident(name) => {
+                        bindings.push(name.clone());
+                        consumed += 1;
+                        // skip a `: renamed` alias target, keeping the source key
+                        if tokens.get(consumed) == Some(&Lexeme::Symbol(':')) {
+                            consumed += 2;
+                        }
+                    }
+                    Lexeme::Symbol(',') => consumed += 1,
+                    Lexeme::Symbol('}') => {
+                        consumed += 1;
+                        break;
+                    }
+                    _ => return None,
+                }
+            }
+
+            if tokens.get(consumed) != Some(&Lexeme::Symbol('=')) {
+                return None;
+            }
+            let is_state = matches!(tokens.get(consumed + 1), Some(Lexeme::Ident(n)) if n == "$state");
+            if !is_state {
+                return None;
+            }
+
+            for key in bindings {
+                push_capability(&mut result.capabilities, CapabilityToken::StateRead(key));
+            }
+            Some(consumed + 2)
+        }
+        _ => None,
+    }
+}
+
+/// Try to scan `$emit(` followed by a string or template-literal event name.
+/// Returns the number of tokens consumed and the statically-resolved event
+/// name, or `None` for the name if it couldn't be resolved.
+fn scan_emit_call(tokens: &[Lexeme]) -> Option<(usize, Option<String>)> {
+    if tokens.first()? != &Lexeme::Symbol('(') {
+        return None;
+    }
+    match tokens.get(1)? {
+        Lexeme::Str(s) => Some((2, Some(s.clone()))),
+        Lexeme::Template(chunks) => match chunks.as_slice() {
+            [] => Some((2, Some(String::new()))),
+            [TemplateChunk::Literal(s)] => Some((2, Some(s.clone()))),
+            _ => Some((2, None)),
+        },
+        _ => None,
+    }
+}
+
+/// Try to scan a single member access (`.key`, `['key']`, or `[expr]`)
+/// starting right after the base identifier, returning the tokens consumed
+/// and whether it's immediately followed by an assignment.
+fn scan_member_access(tokens: &[Lexeme]) -> Option<(usize, MemberAccess)> {
+    match tokens.first()? {
+        Lexeme::Symbol('.') => {
+            let key = match tokens.get(1)? {
+                Lexeme::Ident(name) => name.clone(),
+                _ => return None,
+            };
+            let is_write = tokens.get(2) == Some(&Lexeme::Symbol('='));
+            Some((2, MemberAccess::Static(key, is_write)))
+        }
+        Lexeme::Symbol('[') => match tokens.get(1)? {
+            Lexeme::Str(key) if tokens.get(2) == Some(&Lexeme::Symbol(']')) => {
+                let is_write = tokens.get(3) == Some(&Lexeme::Symbol('='));
+                Some((3, MemberAccess::Static(key.clone(), is_write)))
+            }
+            _ => {
+                let mut depth = 1;
+                let mut consumed = 1;
+                while depth > 0 {
+                    match tokens.get(consumed)? {
+                        Lexeme::Symbol('[') => depth += 1,
+                        Lexeme::Symbol(']') => depth -= 1,
+                        _ => {}
+                    }
+                    consumed += 1;
+                }
+                let is_write = tokens.get(consumed) == Some(&Lexeme::Symbol('='));
+                Some((consumed, MemberAccess::Dynamic(is_write)))
+            }
+        },
+        _ => None,
     }
-    
-    caps
 }
 
-/// Find extension access patterns
-fn find_extension_access(code: &str) -> Vec<CapabilityToken> {
-    let mut caps = Vec::new();
-    
-    // Pattern: $ext.name
-    let re = regex::Regex::new(r"\$ext\.(\w+)").ok();
-    
-    if let Some(re) = re {
-        for cap in re.captures_iter(code) {
-            let ext = cap.get(1).map(|m| m.as_str()).unwrap_or("");
-            if !ext.is_empty() {
-                caps.push(CapabilityToken::Extension(ext.to_string()));
+/// A chunk of a template literal: either literal text or a `${...}`
+/// interpolation (kept as raw, unparsed source)
+#[derive(Debug, Clone, PartialEq)]
+enum TemplateChunk {
+    /// Literal text between interpolations
+    Literal(String),
+    /// The raw source of a `${...}` interpolation
+    Expr(String),
+}
+
+/// A single lexical token, produced by [`tokenize`]. This is a lightweight
+/// scan tailored to the `$state`/`$ext`/`$emit` access patterns this module
+/// cares about, not a full ECMAScript tokenizer — but unlike the regexes it
+/// replaces, it correctly skips over string, template, and comment content
+/// so identifiers that merely *look* like an access inside a string literal
+/// are never mistaken for a real one.
+#[derive(Debug, Clone, PartialEq)]
+enum Lexeme {
+    /// An identifier or keyword
+    Ident(String),
+    /// The contents of a single- or double-quoted string literal
+    Str(String),
+    /// The chunks of a template literal
+    Template(Vec<TemplateChunk>),
+    /// A single-character punctuation token this module cares about
+    Symbol(char),
+}
+
+fn tokenize(code: &str) -> Vec<Lexeme> {
+    let chars: Vec<char> = code.chars().collect();
+    let mut out = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i = (i + 2).min(chars.len());
+            }
+            quote @ ('\'' | '"') => {
+                i += 1;
+                let mut s = String::new();
+                while i < chars.len() && chars[i] != quote {
+                    if chars[i] == '\\' && i + 1 < chars.len() {
+                        s.push(chars[i + 1]);
+                        i += 2;
+                    } else {
+                        s.push(chars[i]);
+                        i += 1;
+                    }
+                }
+                i += 1;
+                out.push(Lexeme::Str(s));
             }
+            '`' => {
+                i += 1;
+                let mut chunks = Vec::new();
+                let mut literal = String::new();
+                while i < chars.len() && chars[i] != '`' {
+                    if chars[i] == '\\' && i + 1 < chars.len() {
+                        literal.push(chars[i + 1]);
+                        i += 2;
+                    } else if chars[i] == '$' && chars.get(i + 1) == Some(&'{') {
+                        if !literal.is_empty() {
+                            chunks.push(TemplateChunk::Literal(std::mem::take(&mut literal)));
+                        }
+                        i += 2;
+                        let mut depth = 1;
+                        let mut expr = String::new();
+                        while i < chars.len() && depth > 0 {
+                            match chars[i] {
+                                '{' => depth += 1,
+                                '}' => {
+                                    depth -= 1;
+                                    if depth == 0 {
+                                        break;
+                                    }
+                                }
+                                _ => {}
+                            }
+                            expr.push(chars[i]);
+                            i += 1;
+                        }
+                        i += 1;
+                        chunks.push(TemplateChunk::Expr(expr));
+                    } else {
+                        literal.push(chars[i]);
+                        i += 1;
+                    }
+                }
+                if !literal.is_empty() {
+                    chunks.push(TemplateChunk::Literal(literal));
+                }
+                i += 1;
+                out.push(Lexeme::Template(chunks));
+            }
+            '.' | '[' | ']' | '(' | ')' | '{' | '}' | '=' | ';' | ',' | ':' => {
+                out.push(Lexeme::Symbol(chars[i]));
+                i += 1;
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                let mut s = String::new();
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+                {
+                    s.push(chars[i]);
+                    i += 1;
+                }
+                out.push(Lexeme::Ident(s));
+            }
+            _ => i += 1,
         }
     }
-    
-    caps
+
+    out
 }
 
 #[cfg(test)]
@@ -357,6 +1253,111 @@ mod tests {
         assert!(!specific_read.matches("state:read:other"));
     }
 
+    #[test]
+    fn test_single_star_matches_exactly_one_segment() {
+        let scoped = CapabilityToken::StateRead("user.*".to_string());
+        assert!(scoped.matches("state:read:user.name"));
+        assert!(!scoped.matches("state:read:user.profile.email"));
+        assert!(!scoped.matches("state:read:admin"));
+    }
+
+    #[test]
+    fn test_double_star_matches_any_remaining_depth() {
+        let scoped = CapabilityToken::StateWrite("user.**".to_string());
+        assert!(scoped.matches("state:write:user.name"));
+        assert!(scoped.matches("state:write:user.profile.email"));
+        assert!(scoped.matches("state:write:user"));
+        assert!(!scoped.matches("state:write:admin"));
+    }
+
+    #[test]
+    fn test_view_and_extension_scopes_support_glob_matching() {
+        assert!(CapabilityToken::ViewUpdate("panel.*".to_string()).matches("view:update:panel.title"));
+        assert!(CapabilityToken::Extension("http.*".to_string()).matches("ext:http.get"));
+    }
+
+    #[test]
+    fn test_extension_method_token_parses_and_matches_its_own_method_only() {
+        assert_eq!(
+            CapabilityToken::parse("ext:http:get"),
+            Some(CapabilityToken::ExtensionMethod {
+                ext: "http".to_string(),
+                method: "get".to_string(),
+                constraints: Vec::new(),
+            })
+        );
+
+        let token = CapabilityToken::ExtensionMethod {
+            ext: "http".to_string(),
+            method: "get".to_string(),
+            constraints: Vec::new(),
+        };
+        assert!(token.matches("ext:http:get"));
+        assert!(!token.matches("ext:http:post"));
+        assert!(!token.matches("ext:storage:get"));
+    }
+
+    #[test]
+    fn test_extension_method_token_to_string_drops_constraints() {
+        let token = CapabilityToken::ExtensionMethod {
+            ext: "http".to_string(),
+            method: "get".to_string(),
+            constraints: vec![ArgConstraint::MaxArgLen(10)],
+        };
+        assert_eq!(token.to_string_repr(), "ext:http:get");
+    }
+
+    #[test]
+    fn test_arg_constraint_host_allowlist() {
+        let constraint = ArgConstraint::HostAllowlist(vec!["api.example.com".to_string()]);
+
+        assert!(constraint
+            .check(&[RuntimeValue::String("https://api.example.com/widgets".to_string())])
+            .is_none());
+        assert!(constraint
+            .check(&[RuntimeValue::String("api.example.com".to_string())])
+            .is_none());
+        assert!(constraint
+            .check(&[RuntimeValue::String("https://evil.example.net".to_string())])
+            .is_some());
+    }
+
+    #[test]
+    fn test_arg_constraint_key_prefix() {
+        let constraint = ArgConstraint::KeyPrefix("tenant-42.".to_string());
+
+        assert!(constraint
+            .check(&[RuntimeValue::String("tenant-42.profile".to_string())])
+            .is_none());
+        assert!(constraint
+            .check(&[RuntimeValue::String("tenant-7.profile".to_string())])
+            .is_some());
+    }
+
+    #[test]
+    fn test_arg_constraint_max_arg_len() {
+        let constraint = ArgConstraint::MaxArgLen(5);
+
+        assert!(constraint.check(&[RuntimeValue::String("short".to_string())]).is_none());
+        assert!(constraint
+            .check(&[RuntimeValue::String("way too long".to_string())])
+            .is_some());
+        assert!(constraint
+            .check(&[RuntimeValue::Array(vec![RuntimeValue::Null; 3])])
+            .is_none());
+    }
+
+    #[test]
+    fn test_events_emit_scope_supports_glob_matching() {
+        let scoped = CapabilityToken::EventsEmit("user.*".to_string());
+        assert!(scoped.matches("events:emit:user.created"));
+        assert!(!scoped.matches("events:emit:user.profile.updated"));
+        assert!(!scoped.matches("events:emit:order.created"));
+
+        let scoped_deep = CapabilityToken::EventsEmit("user.**".to_string());
+        assert!(scoped_deep.matches("events:emit:user.profile.updated"));
+    }
+
     #[test]
     fn test_capability_checker() {
         let checker = CapabilityChecker::new(vec![
@@ -380,4 +1381,304 @@ mod tests {
             "state:write:count"
         );
     }
+
+    #[test]
+    fn test_implies_all_covers_specific() {
+        assert!(CapabilityToken::StateReadAll.implies(&CapabilityToken::StateRead("count".to_string())));
+        assert!(!CapabilityToken::StateRead("count".to_string()).implies(&CapabilityToken::StateReadAll));
+    }
+
+    #[test]
+    fn test_implies_specific_only_covers_itself() {
+        let count = CapabilityToken::StateRead("count".to_string());
+        let other = CapabilityToken::StateRead("other".to_string());
+        assert!(count.implies(&count.clone()));
+        assert!(!count.implies(&other));
+    }
+
+    #[test]
+    fn test_delegation_chain_verifies_valid_attenuation() {
+        let chain = DelegationChain::new(vec![
+            CapabilityToken::StateReadAll,
+            CapabilityToken::EventsEmitAll,
+        ])
+        .delegate(vec![CapabilityToken::StateRead("count".to_string())])
+        .delegate(vec![CapabilityToken::StateRead("count".to_string())]);
+
+        let leaf = chain.verify().unwrap();
+        assert_eq!(leaf, vec![CapabilityToken::StateRead("count".to_string())]);
+    }
+
+    #[test]
+    fn test_delegation_chain_rejects_escalation() {
+        let chain = DelegationChain::new(vec![CapabilityToken::StateRead("count".to_string())])
+            .delegate(vec![CapabilityToken::StateReadAll]);
+
+        assert!(chain.verify().is_err());
+    }
+
+    #[test]
+    fn test_delegation_chain_rejects_unrelated_capability() {
+        let chain = DelegationChain::new(vec![CapabilityToken::StateRead("count".to_string())])
+            .delegate(vec![CapabilityToken::EventsEmit("toast".to_string())]);
+
+        assert!(chain.verify().is_err());
+    }
+
+    #[test]
+    fn test_checker_from_verified_delegation_chain_enforces_leaf_only() {
+        let chain = DelegationChain::new(vec![CapabilityToken::StateReadAll])
+            .delegate(vec![CapabilityToken::StateRead("count".to_string())]);
+
+        let checker = CapabilityChecker::from_delegation_chain(&chain).unwrap();
+        assert!(checker.can_read_state("count"));
+        assert!(!checker.can_read_state("other"));
+    }
+
+    #[test]
+    fn test_checker_from_invalid_chain_errors() {
+        let chain = DelegationChain::new(vec![CapabilityToken::StateRead("count".to_string())])
+            .delegate(vec![CapabilityToken::StateReadAll]);
+
+        assert!(CapabilityChecker::from_delegation_chain(&chain).is_err());
+    }
+
+    #[test]
+    fn test_grant_round_trips_through_string_with_caveats() {
+        let grant = CapabilityGrant::new(CapabilityToken::StateWrite("count".to_string()))
+            .with_caveat(Caveat::ValueMax(100.0))
+            .with_caveat(Caveat::RateLimit {
+                max_calls: 5,
+                per_secs: 60,
+            });
+
+        let s = grant.to_string_repr();
+        let parsed = CapabilityGrant::parse(&s).unwrap();
+        assert_eq!(parsed, grant);
+    }
+
+    #[test]
+    fn test_grant_parse_is_backward_compatible_with_bare_token() {
+        let parsed = CapabilityGrant::parse("state:write:count").unwrap();
+        assert_eq!(parsed.token, CapabilityToken::StateWrite("count".to_string()));
+        assert!(parsed.caveats.is_empty());
+    }
+
+    #[test]
+    fn test_value_max_caveat_rejects_values_over_limit() {
+        let caveat = Caveat::ValueMax(10.0);
+        assert!(caveat.is_satisfied(&RuntimeValue::Number(10.0), 0, 0));
+        assert!(!caveat.is_satisfied(&RuntimeValue::Number(10.1), 0, 0));
+    }
+
+    #[test]
+    fn test_value_pattern_caveat_matches_regex() {
+        let caveat = Caveat::ValuePattern("^[a-z]+$".to_string());
+        assert!(caveat.is_satisfied(&RuntimeValue::String("ok".to_string()), 0, 0));
+        assert!(!caveat.is_satisfied(&RuntimeValue::String("NOPE".to_string()), 0, 0));
+    }
+
+    #[test]
+    fn test_time_window_caveat_bounds_are_inclusive() {
+        let caveat = Caveat::TimeWindow {
+            not_before: Some(100),
+            not_after: Some(200),
+        };
+        assert!(caveat.is_satisfied(&RuntimeValue::Null, 100, 0));
+        assert!(caveat.is_satisfied(&RuntimeValue::Null, 200, 0));
+        assert!(!caveat.is_satisfied(&RuntimeValue::Null, 99, 0));
+        assert!(!caveat.is_satisfied(&RuntimeValue::Null, 201, 0));
+    }
+
+    #[test]
+    fn test_rate_limit_caveat_rejects_once_window_is_full() {
+        let caveat = Caveat::RateLimit {
+            max_calls: 3,
+            per_secs: 60,
+        };
+        assert!(caveat.is_satisfied(&RuntimeValue::Null, 0, 2));
+        assert!(!caveat.is_satisfied(&RuntimeValue::Null, 0, 3));
+    }
+
+    #[test]
+    fn test_checker_enforces_caveats_on_write() {
+        let grant = CapabilityGrant::new(CapabilityToken::StateWrite("count".to_string()))
+            .with_caveat(Caveat::ValueMax(10.0));
+        let checker = CapabilityChecker::from_grants(vec![grant]);
+
+        assert!(checker.can_write_state_with_caveats("count", &RuntimeValue::Number(5.0), 0, 0));
+        assert!(!checker.can_write_state_with_caveats("count", &RuntimeValue::Number(50.0), 0, 0));
+        // The caveat-unaware check still passes, since it ignores caveats
+        assert!(checker.can_write_state("count"));
+    }
+
+    #[test]
+    fn test_capability_to_grant_carries_caveats() {
+        let cap = Capability::new(CapabilityType::StateWrite, "count")
+            .with_caveats(vec![Caveat::ValueMax(10.0)]);
+
+        let grant = cap.to_grant();
+        assert_eq!(grant.token, CapabilityToken::StateWrite("count".to_string()));
+        assert_eq!(grant.caveats, vec![Caveat::ValueMax(10.0)]);
+    }
+
+    #[test]
+    fn test_infer_capabilities_static_state_and_ext_access() {
+        let code = r#"
+            function handle() {
+                const name = $state.user.name;
+                $ext.fetch.get("/api");
+            }
+        "#;
+        let caps = infer_capabilities(code);
+        assert!(caps.contains(&CapabilityToken::StateRead("user".to_string())));
+        assert!(caps.contains(&CapabilityToken::Extension("fetch".to_string())));
+    }
+
+    #[test]
+    fn test_infer_capabilities_computed_access_widens_to_wildcard() {
+        let inferred = infer_capabilities_detailed("const v = $state[varName];");
+        assert!(inferred.capabilities.contains(&CapabilityToken::StateReadAll));
+        assert!(!inferred.widened.is_empty());
+    }
+
+    #[test]
+    fn test_infer_capabilities_destructuring() {
+        let inferred = infer_capabilities_detailed("const { count } = $state;");
+        assert_eq!(
+            inferred.capabilities,
+            vec![CapabilityToken::StateRead("count".to_string())]
+        );
+        assert!(inferred.widened.is_empty());
+    }
+
+    #[test]
+    fn test_infer_capabilities_template_literal_event_name() {
+        let static_name = infer_capabilities_detailed(r#"$emit(`saved`, payload);"#);
+        assert_eq!(
+            static_name.capabilities,
+            vec![CapabilityToken::EventsEmit("saved".to_string())]
+        );
+        assert!(static_name.widened.is_empty());
+
+        let dynamic_name = infer_capabilities_detailed(r#"$emit(`saved-${id}`, payload);"#);
+        assert!(dynamic_name
+            .capabilities
+            .contains(&CapabilityToken::EventsEmitAll));
+        assert!(!dynamic_name.widened.is_empty());
+    }
+
+    #[test]
+    fn test_infer_capabilities_aliased_reference() {
+        let inferred = infer_capabilities_detailed("const s = $state; s.count = 1;");
+        assert_eq!(
+            inferred.capabilities,
+            vec![CapabilityToken::StateWrite("count".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_infer_capabilities_nested_extension_chain_uses_first_segment() {
+        let inferred = infer_capabilities_detailed("$ext.a.b.c();");
+        assert_eq!(
+            inferred.capabilities,
+            vec![CapabilityToken::Extension("a".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_infer_capabilities_ignores_identifiers_in_strings_and_comments() {
+        let code = r#"
+            // $state.secret should not count, it's a comment
+            const s = "$ext.hidden also should not count, it's a string";
+        "#;
+        let inferred = infer_capabilities_detailed(code);
+        assert!(inferred.capabilities.is_empty());
+    }
+
+    #[test]
+    fn test_router_resolves_offered_capability_to_child() {
+        let root = HandlerRoute::new("root").with_offer(RoutedCapability::new(
+            CapabilityType::StateWrite,
+            "count",
+            RouteSource::SelfOwned,
+            RouteTarget::Handler("child".to_string()),
+        ));
+        let child = HandlerRoute::new("child")
+            .with_parent("root")
+            .with_use(CapabilityToken::StateWrite("count".to_string()));
+
+        let resolved = CapabilityRouter::new(vec![root, child]).resolve().unwrap();
+        assert!(resolved["child"].can_write_state("count"));
+    }
+
+    #[test]
+    fn test_router_rejects_unrouted_use() {
+        let root = HandlerRoute::new("root");
+        let child = HandlerRoute::new("child")
+            .with_parent("root")
+            .with_use(CapabilityToken::StateWrite("count".to_string()));
+
+        let err = CapabilityRouter::new(vec![root, child]).resolve().unwrap_err();
+        assert!(matches!(err, RuntimeError::Routing(_)));
+    }
+
+    #[test]
+    fn test_router_rejects_dangling_offer() {
+        let root = HandlerRoute::new("root").with_offer(RoutedCapability::new(
+            CapabilityType::StateWrite,
+            "count",
+            RouteSource::SelfOwned,
+            RouteTarget::Handler("nonexistent".to_string()),
+        ));
+
+        let err = CapabilityRouter::new(vec![root]).resolve().unwrap_err();
+        assert!(matches!(err, RuntimeError::Routing(_)));
+    }
+
+    #[test]
+    fn test_router_follows_parent_passthrough_across_multiple_levels() {
+        let grandparent = HandlerRoute::new("grandparent").with_offer(RoutedCapability::new(
+            CapabilityType::Extension,
+            "fetch",
+            RouteSource::SelfOwned,
+            RouteTarget::Handler("parent".to_string()),
+        ));
+        let parent = HandlerRoute::new("parent")
+            .with_parent("grandparent")
+            .with_offer(RoutedCapability::new(
+                CapabilityType::Extension,
+                "fetch",
+                RouteSource::Parent,
+                RouteTarget::Handler("child".to_string()),
+            ));
+        let child = HandlerRoute::new("child")
+            .with_parent("parent")
+            .with_use(CapabilityToken::Extension("fetch".to_string()));
+
+        let resolved = CapabilityRouter::new(vec![grandparent, parent, child])
+            .resolve()
+            .unwrap();
+        assert!(resolved["child"].can_access_extension("fetch"));
+    }
+
+    #[test]
+    fn test_router_all_handlers_offer_reaches_every_child() {
+        let root = HandlerRoute::new("root").with_offer(RoutedCapability::new(
+            CapabilityType::EventsEmit,
+            "*",
+            RouteSource::SelfOwned,
+            RouteTarget::AllHandlers,
+        ));
+        let a = HandlerRoute::new("a")
+            .with_parent("root")
+            .with_use(CapabilityToken::EventsEmit("toast".to_string()));
+        let b = HandlerRoute::new("b")
+            .with_parent("root")
+            .with_use(CapabilityToken::EventsEmit("updated".to_string()));
+
+        let resolved = CapabilityRouter::new(vec![root, a, b]).resolve().unwrap();
+        assert!(resolved["a"].can_emit_event("toast"));
+        assert!(resolved["b"].can_emit_event("updated"));
+    }
 }
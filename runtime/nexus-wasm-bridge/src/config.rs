@@ -5,6 +5,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Default memory limit per instance (32 MB)
 pub const DEFAULT_MEMORY_LIMIT_BYTES: u64 = 32 * 1024 * 1024;
@@ -15,6 +16,11 @@ pub const DEFAULT_STACK_SIZE_BYTES: u64 = 1024 * 1024;
 /// Default maximum instances in pool
 pub const DEFAULT_MAX_INSTANCES: usize = 10;
 
+/// Largest `memory_limit_bytes` a 32-bit wasm linear memory can address
+/// (`i32` bounds checks top out at 4 GiB); exceeding this requires
+/// [`RuntimeConfig::enable_memory64`]
+pub const MEMORY32_LIMIT_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
 /// Default handler timeout in milliseconds
 pub const DEFAULT_TIMEOUT_MS: u32 = 5000;
 
@@ -30,6 +36,81 @@ pub const DEFAULT_MAX_STATE_MUTATIONS: u32 = 1000;
 /// Default maximum events per execution
 pub const DEFAULT_MAX_EVENTS: u32 = 100;
 
+/// Default maximum lifetime of a pooled instance in seconds before it is
+/// recycled instead of reused (0 = no lifetime limit)
+pub const DEFAULT_MAX_INSTANCE_LIFETIME_SECS: u64 = 0;
+
+/// Default maximum idle time of a pooled instance in seconds before it is
+/// recycled instead of reused (0 = no idle limit)
+pub const DEFAULT_MAX_IDLE_TIME_SECS: u64 = 0;
+
+/// Default maximum number of times a pooled instance may be reused before
+/// it is recycled (0 = no reuse limit)
+pub const DEFAULT_MAX_REUSES: u32 = 0;
+
+/// Default number of instance crashes tolerated before the pool trips its
+/// restart circuit breaker and enters a degraded state (0 = never trips)
+pub const DEFAULT_MAX_RESTARTS: u32 = 0;
+
+/// Default interval in seconds between background supervisor passes
+pub const DEFAULT_SUPERVISION_INTERVAL_SECS: u64 = 30;
+
+/// Default compute-unit (fuel) budget per execution (0 = no budget enforced)
+pub const DEFAULT_COMPUTE_UNIT_BUDGET: u64 = 0;
+
+/// Default instruction budget ("gas") per execution, charged at basic-block
+/// boundaries and on memory growth (0 = no limit enforced)
+pub const DEFAULT_GAS_LIMIT: u64 = 0;
+
+/// Default maximum number of compiled modules kept in the in-memory cache
+/// before the configured `cache_policy` evicts one (0 = no entry-count limit,
+/// bounded only by `max_cache_size_bytes`)
+pub const DEFAULT_MAX_CACHE_ENTRIES: usize = 256;
+
+/// Default size of the background worker pool used by
+/// `HandlerCompiler::compile_async`/`warm`
+pub const DEFAULT_MAX_CONCURRENT_COMPILATIONS: usize = 4;
+
+/// Default low-memory hook threshold in bytes (0 = hook never fires)
+pub const DEFAULT_LOW_MEMORY_THRESHOLD_BYTES: u64 = 0;
+
+/// WASM linear memory page size in bytes, used to validate
+/// `ModuleLimits::max_memory_pages` against `memory_limit_bytes`
+pub const WASM_PAGE_BYTES: u64 = 64 * 1024;
+
+/// Largest dirty-byte delta since instantiation that
+/// `RuntimeConfig::fast_instance_reuse` will still reset in place; beyond
+/// this the instance falls back to a full teardown on release
+pub const DEFAULT_FAST_REUSE_MAX_DIRTY_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Default `ModuleLimits::max_imported_functions`: the runtime's fixed host
+/// function surface (see `host_functions`), rounded up for headroom
+pub const DEFAULT_MAX_IMPORTED_FUNCTIONS: u32 = 32;
+
+/// Default `ModuleLimits::max_defined_functions`
+pub const DEFAULT_MAX_DEFINED_FUNCTIONS: u32 = 256;
+
+/// Default `ModuleLimits::max_tables`
+pub const DEFAULT_MAX_TABLES: u32 = 1;
+
+/// Default `ModuleLimits::max_memories`
+pub const DEFAULT_MAX_MEMORIES: u32 = 1;
+
+/// Default `ModuleLimits::max_memory_pages` (32MB, matching
+/// `DEFAULT_MEMORY_LIMIT_BYTES`)
+pub const DEFAULT_MAX_MEMORY_PAGES: u32 = (DEFAULT_MEMORY_LIMIT_BYTES / WASM_PAGE_BYTES) as u32;
+
+/// Notified at most once per memory-pressure episode when a running
+/// instance's remaining memory budget (`memory_limit_bytes - used`) drops
+/// below [`RuntimeConfig::low_memory_threshold_bytes`], so a handler can
+/// react (flush caches, emit a warning event) before being hard-killed on
+/// OOM. See [`crate::engine::instance::WasmInstance`] for where this fires.
+pub trait LowMemoryHook: std::fmt::Debug + Send + Sync {
+    /// Called once per episode with the instance id and its current
+    /// memory usage/limit in bytes
+    fn on_low_memory(&self, instance_id: &str, used_bytes: u64, limit_bytes: u64);
+}
+
 /// Configuration for the WASM runtime
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -38,6 +119,11 @@ pub struct RuntimeConfig {
     #[serde(default = "default_max_instances")]
     pub max_instances: usize,
 
+    /// Minimum number of idle instances the pool keeps pre-warmed at startup
+    /// and during supervision (default: `None`, pre-warms 1)
+    #[serde(default)]
+    pub min_instances: Option<u32>,
+
     /// Memory limit per instance in bytes (default: 32MB)
     #[serde(default = "default_memory_limit")]
     pub memory_limit_bytes: u64,
@@ -54,6 +140,44 @@ pub struct RuntimeConfig {
     #[serde(default = "default_true")]
     pub enable_bulk_memory: bool,
 
+    /// Enable the 64-bit linear memory ABI (`i64` memory indices and bounds
+    /// checks), letting `memory_limit_bytes` exceed the 4 GiB ceiling a
+    /// 32-bit module is limited to (default: false)
+    #[serde(default)]
+    pub enable_memory64: bool,
+
+    /// Instantiate the linear memory as WASM `shared` memory (default:
+    /// false). Required for `WasmInstance::snapshot`/`restore`, which
+    /// duplicate the memory via a shared-memory copy rather than a full
+    /// re-instantiation.
+    #[serde(default)]
+    pub enable_shared_memory: bool,
+
+    /// Extra 64KiB pages to grow an instance's linear memory by at
+    /// instantiation time, beyond what the module itself requests, so
+    /// handlers that allocate early don't pay repeated grow syscalls
+    /// (default: 0)
+    #[serde(default)]
+    pub extra_heap_pages: u64,
+
+    /// On `pool.release()`, reset only the dirtied memory since
+    /// instantiation back to a captured post-init snapshot and return the
+    /// instance to the available set, instead of tearing down and
+    /// re-instantiating the QuickJS context (default: false). Falls back to
+    /// a full reset if the dirty set grew past
+    /// [`DEFAULT_FAST_REUSE_MAX_DIRTY_BYTES`].
+    #[serde(default)]
+    pub fast_instance_reuse: bool,
+
+    /// Capture a handler's post-init linear memory the first time it's
+    /// compiled and reuse it (keyed by bytecode hash, cached alongside
+    /// `cache_dir`) so every later instance executing that handler clones
+    /// its starting memory from the shared image instead of repeating
+    /// initialization (default: false). Requires `enable_shared_memory`,
+    /// the same as `WasmInstance::snapshot`/`restore`.
+    #[serde(default)]
+    pub shared_init_image: bool,
+
     /// Path to QuickJS WASM module (optional, uses bundled if not provided)
     #[serde(default)]
     pub quickjs_module_path: Option<PathBuf>,
@@ -69,20 +193,191 @@ pub struct RuntimeConfig {
     /// Enable debug mode (default: false)
     #[serde(default)]
     pub debug: bool,
+
+    /// Maximum lifetime of a pooled instance in seconds before it is
+    /// recycled instead of reused on its next acquire (default: no limit)
+    #[serde(default = "default_max_instance_lifetime_secs")]
+    pub max_instance_lifetime_secs: u64,
+
+    /// Maximum time a pooled instance may sit idle in seconds before it is
+    /// recycled instead of reused (default: no limit)
+    #[serde(default = "default_max_idle_time_secs")]
+    pub max_idle_time_secs: u64,
+
+    /// Maximum number of times a pooled instance may be reused before it is
+    /// recycled (default: no limit)
+    #[serde(default = "default_max_reuses")]
+    pub max_reuses: u32,
+
+    /// Number of instance crashes tolerated before the pool trips its
+    /// restart circuit breaker and starts failing `acquire` (default: never)
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+
+    /// Interval in seconds between background supervisor passes that evict
+    /// stale instances and check pool health
+    #[serde(default = "default_supervision_interval_secs")]
+    pub supervision_interval_secs: u64,
+
+    /// Maximum number of compiled modules kept in the in-memory cache before
+    /// the eviction policy picks a victim (default: 256, 0 = unlimited)
+    #[serde(default = "default_max_cache_entries")]
+    pub max_cache_entries: usize,
+
+    /// Which eviction policy the compiled-handler cache uses once it
+    /// exceeds its size or entry-count budget (default: LRU)
+    #[serde(default = "default_cache_policy")]
+    pub cache_policy: CachePolicyKind,
+
+    /// Split on-disk cache entries into content-defined chunks and
+    /// deduplicate them in a shared `chunks/` store instead of writing each
+    /// entry's bytecode in full (default: false)
+    #[serde(default)]
+    pub enable_disk_cache_dedup: bool,
+
+    /// Maximum number of handler compilations that may run at once on the
+    /// background worker pool used by `compile_async`/`warm` (default: 4)
+    #[serde(default = "default_max_concurrent_compilations")]
+    pub max_concurrent_compilations: usize,
+
+    /// Remaining memory (`memory_limit_bytes - used`) below which a running
+    /// instance is considered under memory pressure and `low_memory_hook`
+    /// (if set) fires (default: 0, hook never fires)
+    #[serde(default = "default_low_memory_threshold_bytes")]
+    pub low_memory_threshold_bytes: u64,
+
+    /// Callback fired at most once per memory-pressure episode; see
+    /// [`LowMemoryHook`]. Not part of the wire format: a config loaded from
+    /// JSON always starts with no hook registered.
+    #[serde(skip)]
+    pub low_memory_hook: Option<Arc<dyn LowMemoryHook>>,
+
+    /// Whether the pool creates instances lazily as they're acquired
+    /// (`OnDemand`, default) or preallocates a full `max_instances` slab at
+    /// startup with `module_limits` baked in (`Pooling`), trading startup
+    /// cost and bounded RSS for `acquire` never paying allocation cost.
+    #[serde(default)]
+    pub pooling_strategy: PoolingStrategy,
+
+    /// Per-module resource ceilings enforced when `pooling_strategy` is
+    /// `Pooling`; ignored in `OnDemand` mode
+    #[serde(default)]
+    pub module_limits: ModuleLimits,
+}
+
+/// Which wasm linear memory ABI an instance was built with, derived from
+/// [`RuntimeConfig::enable_memory64`] and surfaced in
+/// [`crate::metrics::RuntimeStats::memory_model`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryModel {
+    /// `i32` memory indices and bounds checks, capped at
+    /// [`MEMORY32_LIMIT_BYTES`]
+    #[default]
+    Memory32,
+    /// `i64` memory indices and bounds checks, for `memory_limit_bytes`
+    /// beyond the 32-bit ceiling
+    Memory64,
+}
+
+/// Instance allocation strategy for [`crate::engine::pool::InstancePool`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolingStrategy {
+    /// Create instances lazily as `acquire` needs them, reusing idle ones
+    /// where possible (default)
+    #[default]
+    OnDemand,
+    /// Preallocate a slab of `max_instances` instances at startup, each
+    /// built to fit within `module_limits`, so `acquire` is an index bump
+    /// with no per-call allocation
+    Pooling,
+}
+
+/// Resource ceilings a single precompiled QuickJS module is allowed to
+/// need, enforced against a handler's compiled bytecode at
+/// `HandlerCompiler::compile` time when `RuntimeConfig::pooling_strategy`
+/// is [`PoolingStrategy::Pooling`] (mirroring the fixed-shape slots a
+/// pooling wasm allocator preallocates; see
+/// [`crate::engine::pool::InstancePool::new`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleLimits {
+    /// Maximum host functions a module may import
+    pub max_imported_functions: u32,
+    /// Maximum functions a module may define (handler body plus any
+    /// closures/helpers it declares)
+    pub max_defined_functions: u32,
+    /// Maximum tables a module may declare
+    pub max_tables: u32,
+    /// Maximum memories a module may declare
+    pub max_memories: u32,
+    /// Maximum linear memory pages (64KiB each) a module's memory may grow to
+    pub max_memory_pages: u32,
+    /// Maximum instances a single module may be instantiated into
+    /// concurrently; must be at least `RuntimeConfig::max_instances` since
+    /// the pool preallocates one slot per instance from this same module
+    pub max_instances: usize,
+}
+
+impl Default for ModuleLimits {
+    fn default() -> Self {
+        Self {
+            max_imported_functions: DEFAULT_MAX_IMPORTED_FUNCTIONS,
+            max_defined_functions: DEFAULT_MAX_DEFINED_FUNCTIONS,
+            max_tables: DEFAULT_MAX_TABLES,
+            max_memories: DEFAULT_MAX_MEMORIES,
+            max_memory_pages: DEFAULT_MAX_MEMORY_PAGES,
+            max_instances: DEFAULT_MAX_INSTANCES,
+        }
+    }
+}
+
+/// Which eviction policy the compiled-handler cache uses. See
+/// [`crate::engine::cache_policy`] for the policies themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CachePolicyKind {
+    /// Evict the least-recently-used entry
+    Lru,
+    /// Evict the least-frequently-used entry
+    Lfu,
+    /// Evict by a weighted score combining access frequency and entry size,
+    /// so large, rarely-accessed entries go before small, hot ones
+    WeightedLfu,
 }
 
 impl Default for RuntimeConfig {
     fn default() -> Self {
         Self {
             max_instances: DEFAULT_MAX_INSTANCES,
+            min_instances: None,
             memory_limit_bytes: DEFAULT_MEMORY_LIMIT_BYTES,
             stack_size_bytes: DEFAULT_STACK_SIZE_BYTES,
             enable_simd: true,
             enable_bulk_memory: true,
+            enable_memory64: false,
+            enable_shared_memory: false,
+            extra_heap_pages: 0,
+            fast_instance_reuse: false,
+            shared_init_image: false,
             quickjs_module_path: None,
             enable_aot: false,
             cache_dir: PathBuf::from(DEFAULT_CACHE_DIR),
             debug: false,
+            max_instance_lifetime_secs: DEFAULT_MAX_INSTANCE_LIFETIME_SECS,
+            max_idle_time_secs: DEFAULT_MAX_IDLE_TIME_SECS,
+            max_reuses: DEFAULT_MAX_REUSES,
+            max_restarts: DEFAULT_MAX_RESTARTS,
+            supervision_interval_secs: DEFAULT_SUPERVISION_INTERVAL_SECS,
+            max_cache_entries: DEFAULT_MAX_CACHE_ENTRIES,
+            cache_policy: CachePolicyKind::Lru,
+            enable_disk_cache_dedup: false,
+            max_concurrent_compilations: DEFAULT_MAX_CONCURRENT_COMPILATIONS,
+            low_memory_threshold_bytes: DEFAULT_LOW_MEMORY_THRESHOLD_BYTES,
+            low_memory_hook: None,
+            pooling_strategy: PoolingStrategy::default(),
+            module_limits: ModuleLimits::default(),
         }
     }
 }
@@ -99,6 +394,12 @@ impl RuntimeConfig {
         self
     }
 
+    /// Set the number of idle instances pre-warmed at startup
+    pub fn with_min_instances(mut self, min: u32) -> Self {
+        self.min_instances = Some(min);
+        self
+    }
+
     /// Set the memory limit per instance
     pub fn with_memory_limit(mut self, bytes: u64) -> Self {
         self.memory_limit_bytes = bytes;
@@ -117,6 +418,48 @@ impl RuntimeConfig {
         self
     }
 
+    /// Enable or disable instantiating the linear memory as `shared`,
+    /// required for instance snapshot/restore
+    pub fn with_shared_memory(mut self, enable: bool) -> Self {
+        self.enable_shared_memory = enable;
+        self
+    }
+
+    /// Enable or disable the 64-bit linear memory ABI
+    pub fn with_memory64(mut self, enable: bool) -> Self {
+        self.enable_memory64 = enable;
+        self
+    }
+
+    /// The active memory model implied by `enable_memory64`
+    pub fn memory_model(&self) -> MemoryModel {
+        if self.enable_memory64 {
+            MemoryModel::Memory64
+        } else {
+            MemoryModel::Memory32
+        }
+    }
+
+    /// Set the number of extra 64KiB pages to pre-grow an instance's linear
+    /// memory by at instantiation time
+    pub fn with_extra_heap_pages(mut self, pages: u64) -> Self {
+        self.extra_heap_pages = pages;
+        self
+    }
+
+    /// Enable or disable fast in-place instance reuse on release
+    pub fn with_fast_instance_reuse(mut self, enable: bool) -> Self {
+        self.fast_instance_reuse = enable;
+        self
+    }
+
+    /// Enable or disable cloning new instances' starting memory from a
+    /// shared per-handler init image instead of repeating initialization
+    pub fn with_shared_init_image(mut self, enable: bool) -> Self {
+        self.shared_init_image = enable;
+        self
+    }
+
     /// Set the QuickJS module path
     pub fn with_quickjs_path(mut self, path: PathBuf) -> Self {
         self.quickjs_module_path = Some(path);
@@ -141,6 +484,91 @@ impl RuntimeConfig {
         self
     }
 
+    /// Set the maximum lifetime of a pooled instance in seconds (0 = no limit)
+    pub fn with_max_instance_lifetime_secs(mut self, secs: u64) -> Self {
+        self.max_instance_lifetime_secs = secs;
+        self
+    }
+
+    /// Set the maximum idle time of a pooled instance in seconds (0 = no limit)
+    pub fn with_max_idle_time_secs(mut self, secs: u64) -> Self {
+        self.max_idle_time_secs = secs;
+        self
+    }
+
+    /// Set the maximum number of reuses of a pooled instance (0 = no limit)
+    pub fn with_max_reuses(mut self, reuses: u32) -> Self {
+        self.max_reuses = reuses;
+        self
+    }
+
+    /// Set the restart circuit-breaker threshold (0 = never trips)
+    pub fn with_max_restarts(mut self, max_restarts: u32) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /// Set the interval in seconds between background supervisor passes
+    pub fn with_supervision_interval_secs(mut self, secs: u64) -> Self {
+        self.supervision_interval_secs = secs;
+        self
+    }
+
+    /// Set the maximum number of compiled modules kept in the in-memory
+    /// cache (0 = unlimited)
+    pub fn with_max_cache_entries(mut self, max: usize) -> Self {
+        self.max_cache_entries = max;
+        self
+    }
+
+    /// Set the compiled-handler cache's eviction policy
+    pub fn with_cache_policy(mut self, policy: CachePolicyKind) -> Self {
+        self.cache_policy = policy;
+        self
+    }
+
+    /// Enable or disable content-defined chunk deduplication for the
+    /// on-disk cache
+    pub fn with_disk_cache_dedup(mut self, enable: bool) -> Self {
+        self.enable_disk_cache_dedup = enable;
+        self
+    }
+
+    /// Set the size of the background worker pool used by
+    /// `compile_async`/`warm`
+    pub fn with_max_concurrent_compilations(mut self, max: usize) -> Self {
+        self.max_concurrent_compilations = max;
+        self
+    }
+
+    /// Set the remaining-memory threshold (in bytes) below which
+    /// `low_memory_hook` fires (0 = hook never fires)
+    pub fn with_low_memory_threshold_bytes(mut self, bytes: u64) -> Self {
+        self.low_memory_threshold_bytes = bytes;
+        self
+    }
+
+    /// Register a callback fired at most once per memory-pressure episode;
+    /// see [`LowMemoryHook`]
+    pub fn with_low_memory_hook(mut self, hook: Arc<dyn LowMemoryHook>) -> Self {
+        self.low_memory_hook = Some(hook);
+        self
+    }
+
+    /// Set whether the pool creates instances on demand or preallocates a
+    /// full slab at startup; see [`PoolingStrategy`]
+    pub fn with_pooling_strategy(mut self, strategy: PoolingStrategy) -> Self {
+        self.pooling_strategy = strategy;
+        self
+    }
+
+    /// Set the per-module resource ceilings enforced in
+    /// [`PoolingStrategy::Pooling`] mode; see [`ModuleLimits`]
+    pub fn with_module_limits(mut self, limits: ModuleLimits) -> Self {
+        self.module_limits = limits;
+        self
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> Result<(), ConfigError> {
         if self.max_instances == 0 {
@@ -164,6 +592,34 @@ impl RuntimeConfig {
             });
         }
 
+        if self.memory_limit_bytes > MEMORY32_LIMIT_BYTES && !self.enable_memory64 {
+            return Err(ConfigError::InvalidValue {
+                field: "memory_limit_bytes".into(),
+                reason: "exceeds the 4GiB 32-bit memory ceiling; set enable_memory64 to allow this"
+                    .into(),
+            });
+        }
+
+        if self.pooling_strategy == PoolingStrategy::Pooling {
+            if self.module_limits.max_instances < self.max_instances {
+                return Err(ConfigError::InvalidValue {
+                    field: "module_limits.max_instances".into(),
+                    reason: "must be at least max_instances when pooling_strategy is Pooling"
+                        .into(),
+                });
+            }
+
+            let module_memory_bytes =
+                self.module_limits.max_memory_pages as u64 * WASM_PAGE_BYTES;
+            if module_memory_bytes < self.memory_limit_bytes {
+                return Err(ConfigError::InvalidValue {
+                    field: "module_limits.max_memory_pages".into(),
+                    reason: "must cover memory_limit_bytes when pooling_strategy is Pooling"
+                        .into(),
+                });
+            }
+        }
+
         Ok(())
     }
 }
@@ -195,6 +651,19 @@ pub struct ResourceLimits {
     /// Maximum event emission count per execution
     #[serde(default = "default_max_events")]
     pub max_events: u32,
+
+    /// Maximum compute units (fuel) an execution may consume (0 = no budget
+    /// enforced)
+    #[serde(default = "default_compute_unit_budget")]
+    pub compute_unit_budget: u64,
+
+    /// Maximum instruction budget ("gas") an execution may consume before
+    /// being trapped with `ExecutionStatus::GasExhausted` (0 = no limit
+    /// enforced). Charged at basic-block boundaries and proportionally to
+    /// pages requested on memory growth; unlike `compute_unit_budget` this
+    /// is enforced mid-execution rather than only observed after the fact.
+    #[serde(default = "default_gas_limit")]
+    pub gas_limit: u64,
 }
 
 impl Default for ResourceLimits {
@@ -206,6 +675,8 @@ impl Default for ResourceLimits {
             max_host_calls: DEFAULT_MAX_HOST_CALLS,
             max_state_mutations: DEFAULT_MAX_STATE_MUTATIONS,
             max_events: DEFAULT_MAX_EVENTS,
+            compute_unit_budget: DEFAULT_COMPUTE_UNIT_BUDGET,
+            gas_limit: DEFAULT_GAS_LIMIT,
         }
     }
 }
@@ -275,6 +746,50 @@ fn default_max_events() -> u32 {
     DEFAULT_MAX_EVENTS
 }
 
+fn default_compute_unit_budget() -> u64 {
+    DEFAULT_COMPUTE_UNIT_BUDGET
+}
+
+fn default_gas_limit() -> u64 {
+    DEFAULT_GAS_LIMIT
+}
+
+fn default_max_instance_lifetime_secs() -> u64 {
+    DEFAULT_MAX_INSTANCE_LIFETIME_SECS
+}
+
+fn default_max_idle_time_secs() -> u64 {
+    DEFAULT_MAX_IDLE_TIME_SECS
+}
+
+fn default_max_reuses() -> u32 {
+    DEFAULT_MAX_REUSES
+}
+
+fn default_max_restarts() -> u32 {
+    DEFAULT_MAX_RESTARTS
+}
+
+fn default_supervision_interval_secs() -> u64 {
+    DEFAULT_SUPERVISION_INTERVAL_SECS
+}
+
+fn default_max_cache_entries() -> usize {
+    DEFAULT_MAX_CACHE_ENTRIES
+}
+
+fn default_cache_policy() -> CachePolicyKind {
+    CachePolicyKind::Lru
+}
+
+fn default_max_concurrent_compilations() -> usize {
+    DEFAULT_MAX_CONCURRENT_COMPILATIONS
+}
+
+fn default_low_memory_threshold_bytes() -> u64 {
+    DEFAULT_LOW_MEMORY_THRESHOLD_BYTES
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -316,4 +831,181 @@ mod tests {
         let parsed: RuntimeConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.max_instances, config.max_instances);
     }
+
+    #[test]
+    fn test_resource_limits_default_budget_is_unlimited() {
+        let limits = ResourceLimits::default();
+        assert_eq!(limits.compute_unit_budget, DEFAULT_COMPUTE_UNIT_BUDGET);
+    }
+
+    #[test]
+    fn test_resource_limits_default_gas_limit_is_unlimited() {
+        let limits = ResourceLimits::default();
+        assert_eq!(limits.gas_limit, DEFAULT_GAS_LIMIT);
+    }
+
+    #[test]
+    fn test_shared_memory_disabled_by_default() {
+        let config = RuntimeConfig::default();
+        assert!(!config.enable_shared_memory);
+
+        let config = RuntimeConfig::new().with_shared_memory(true);
+        assert!(config.enable_shared_memory);
+    }
+
+    #[test]
+    fn test_low_memory_hook_disabled_by_default() {
+        let config = RuntimeConfig::default();
+        assert_eq!(config.low_memory_threshold_bytes, 0);
+        assert!(config.low_memory_hook.is_none());
+    }
+
+    #[derive(Debug)]
+    struct NoopLowMemoryHook;
+
+    impl LowMemoryHook for NoopLowMemoryHook {
+        fn on_low_memory(&self, _instance_id: &str, _used_bytes: u64, _limit_bytes: u64) {}
+    }
+
+    #[test]
+    fn test_with_low_memory_hook_registers_callback() {
+        let config = RuntimeConfig::new()
+            .with_low_memory_threshold_bytes(4 * 1024 * 1024)
+            .with_low_memory_hook(Arc::new(NoopLowMemoryHook));
+
+        assert_eq!(config.low_memory_threshold_bytes, 4 * 1024 * 1024);
+        assert!(config.low_memory_hook.is_some());
+    }
+
+    #[test]
+    fn test_low_memory_hook_is_not_part_of_the_wire_format() {
+        let config = RuntimeConfig::new().with_low_memory_hook(Arc::new(NoopLowMemoryHook));
+
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: RuntimeConfig = serde_json::from_str(&json).unwrap();
+        assert!(parsed.low_memory_hook.is_none());
+    }
+
+    #[test]
+    fn test_pooling_strategy_defaults_to_on_demand() {
+        let config = RuntimeConfig::default();
+        assert_eq!(config.pooling_strategy, PoolingStrategy::OnDemand);
+    }
+
+    #[test]
+    fn test_with_pooling_strategy() {
+        let config = RuntimeConfig::new().with_pooling_strategy(PoolingStrategy::Pooling);
+        assert_eq!(config.pooling_strategy, PoolingStrategy::Pooling);
+    }
+
+    #[test]
+    fn test_module_limits_default_covers_default_memory_limit() {
+        let limits = ModuleLimits::default();
+        assert!(limits.max_memory_pages as u64 * WASM_PAGE_BYTES >= DEFAULT_MEMORY_LIMIT_BYTES);
+        assert_eq!(limits.max_instances, DEFAULT_MAX_INSTANCES);
+    }
+
+    #[test]
+    fn test_validate_ignores_module_limits_in_on_demand_mode() {
+        let config = RuntimeConfig::new().with_module_limits(ModuleLimits {
+            max_imported_functions: 1,
+            max_defined_functions: 1,
+            max_tables: 1,
+            max_memories: 1,
+            max_memory_pages: 0,
+            max_instances: 0,
+        });
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_module_memory_below_instance_memory_limit() {
+        let config = RuntimeConfig::new()
+            .with_pooling_strategy(PoolingStrategy::Pooling)
+            .with_module_limits(ModuleLimits {
+                max_memory_pages: 1,
+                ..ModuleLimits::default()
+            });
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_module_instances_below_pool_max_instances() {
+        let config = RuntimeConfig::new()
+            .with_max_instances(10)
+            .with_pooling_strategy(PoolingStrategy::Pooling)
+            .with_module_limits(ModuleLimits {
+                max_instances: 5,
+                ..ModuleLimits::default()
+            });
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_memory_model_defaults_to_32_bit() {
+        let config = RuntimeConfig::default();
+        assert_eq!(config.memory_model(), MemoryModel::Memory32);
+        assert!(!config.enable_memory64);
+    }
+
+    #[test]
+    fn test_validate_rejects_over_4gib_memory_limit_without_memory64() {
+        let config = RuntimeConfig::new().with_memory_limit(MEMORY32_LIMIT_BYTES + 1);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_accepts_over_4gib_memory_limit_with_memory64() {
+        let config = RuntimeConfig::new()
+            .with_memory_limit(MEMORY32_LIMIT_BYTES + 1)
+            .with_memory64(true);
+
+        assert!(config.validate().is_ok());
+        assert_eq!(config.memory_model(), MemoryModel::Memory64);
+    }
+
+    #[test]
+    fn test_extra_heap_pages_and_fast_reuse_disabled_by_default() {
+        let config = RuntimeConfig::default();
+        assert_eq!(config.extra_heap_pages, 0);
+        assert!(!config.fast_instance_reuse);
+    }
+
+    #[test]
+    fn test_with_extra_heap_pages_and_fast_instance_reuse() {
+        let config = RuntimeConfig::new()
+            .with_extra_heap_pages(4)
+            .with_fast_instance_reuse(true);
+
+        assert_eq!(config.extra_heap_pages, 4);
+        assert!(config.fast_instance_reuse);
+    }
+
+    #[test]
+    fn test_shared_init_image_disabled_by_default() {
+        let config = RuntimeConfig::default();
+        assert!(!config.shared_init_image);
+    }
+
+    #[test]
+    fn test_with_shared_init_image() {
+        let config = RuntimeConfig::new().with_shared_init_image(true);
+        assert!(config.shared_init_image);
+    }
+
+    #[test]
+    fn test_validate_accepts_consistent_pooling_config() {
+        let config = RuntimeConfig::new()
+            .with_max_instances(10)
+            .with_pooling_strategy(PoolingStrategy::Pooling)
+            .with_module_limits(ModuleLimits {
+                max_instances: 10,
+                ..ModuleLimits::default()
+            });
+
+        assert!(config.validate().is_ok());
+    }
 }
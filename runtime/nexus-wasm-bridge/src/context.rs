@@ -4,11 +4,14 @@
 //! returned from execution, including state mutations, events, and
 //! suspension details for async operations.
 
-use crate::capability::CapabilityToken;
+use crate::capability::{ArgConstraint, CapabilityToken};
+use crate::config::ResourceLimits;
 use crate::error::WasmError;
+use crate::event_sink::EventSink;
 use crate::metrics::ExecutionMetrics;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Runtime value types (must be serializable)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -20,6 +23,14 @@ pub enum RuntimeValue {
     Bool(bool),
     /// Numeric value (f64 for JS compatibility)
     Number(f64),
+    /// Binary payload (e.g. a raw HTTP response body), serialized as a
+    /// `$bytes:`-prefixed base64 string via [`bytes_as_base64`] rather than
+    /// the lossy UTF-8 round-trip a `String` would force. Declared before
+    /// `String` so the untagged deserializer tries it first: its
+    /// `Deserialize` rejects any string lacking the `$bytes:` prefix,
+    /// falling through to `String` for everything else.
+    #[serde(with = "bytes_as_base64")]
+    Bytes(Vec<u8>),
     /// String value
     String(String),
     /// Array of values
@@ -28,6 +39,81 @@ pub enum RuntimeValue {
     Object(HashMap<String, RuntimeValue>),
 }
 
+/// Gives [`RuntimeValue::Bytes`] a JSON-string wire shape (a `$bytes:`-prefixed
+/// base64 payload) instead of a tagged object, so the untagged enum stays
+/// interoperable with the JS side: it just sees a string. Only this
+/// `deserialize` recognizes the prefix and decodes it back into real bytes;
+/// any other string is rejected so the untagged enum falls through to
+/// `RuntimeValue::String` instead.
+mod bytes_as_base64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    const PREFIX: &str = "$bytes:";
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    fn encode(bytes: &[u8]) -> String {
+        let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+        for chunk in bytes.chunks(3) {
+            let b0 = chunk[0];
+            let b1 = chunk.get(1).copied();
+            let b2 = chunk.get(2).copied();
+
+            out.push(ALPHABET[(b0 >> 2) as usize] as char);
+            out.push(ALPHABET[(((b0 & 0x03) << 4) | (b1.unwrap_or(0) >> 4)) as usize] as char);
+            out.push(match b1 {
+                Some(b1) => ALPHABET[(((b1 & 0x0f) << 2) | (b2.unwrap_or(0) >> 6)) as usize] as char,
+                None => '=',
+            });
+            out.push(match b2 {
+                Some(b2) => ALPHABET[(b2 & 0x3f) as usize] as char,
+                None => '=',
+            });
+        }
+        out
+    }
+
+    fn decode(encoded: &str) -> Result<Vec<u8>, String> {
+        fn value(c: u8) -> Result<u8, String> {
+            ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .map(|p| p as u8)
+                .ok_or_else(|| format!("invalid base64 byte '{}'", c as char))
+        }
+
+        let stripped = encoded.trim_end_matches('=');
+        let mut out = Vec::with_capacity(stripped.len() * 3 / 4);
+        let chars: Vec<u8> = stripped.bytes().collect();
+        for chunk in chars.chunks(4) {
+            let v: Vec<u8> = chunk
+                .iter()
+                .map(|&c| value(c))
+                .collect::<Result<_, _>>()?;
+            out.push((v[0] << 2) | (v.get(1).unwrap_or(&0) >> 4));
+            if v.len() > 2 {
+                out.push((v[1] << 4) | (v[2] >> 2));
+            }
+            if v.len() > 3 {
+                out.push((v[2] << 6) | v[3]);
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn serialize<S: Serializer>(bytes: &Vec<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}{}", PREFIX, encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let encoded = s
+            .strip_prefix(PREFIX)
+            .ok_or_else(|| serde::de::Error::custom("not a $bytes: value"))?;
+        decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
 impl RuntimeValue {
     /// Check if value is null
     pub fn is_null(&self) -> bool {
@@ -58,6 +144,14 @@ impl RuntimeValue {
         }
     }
 
+    /// Get as bytes
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            RuntimeValue::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
     /// Get as array
     pub fn as_array(&self) -> Option<&Vec<RuntimeValue>> {
         match self {
@@ -111,12 +205,156 @@ impl<T: Into<RuntimeValue>> From<Vec<T>> for RuntimeValue {
     }
 }
 
+impl From<Vec<u8>> for RuntimeValue {
+    fn from(bytes: Vec<u8>) -> Self {
+        RuntimeValue::Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for RuntimeValue {
+    fn from(bytes: &[u8]) -> Self {
+        RuntimeValue::Bytes(bytes.to_vec())
+    }
+}
+
 impl Default for RuntimeValue {
     fn default() -> Self {
         RuntimeValue::Null
     }
 }
 
+/// The `RuntimeValue` shape a declared parameter expects. `Any` opts a
+/// parameter out of kind checking (e.g. a JSON payload passed through
+/// verbatim) while still letting arity be validated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueKind {
+    /// Matches [`RuntimeValue::Null`]
+    Null,
+    /// Matches [`RuntimeValue::Bool`]
+    Bool,
+    /// Matches [`RuntimeValue::Number`]
+    Number,
+    /// Matches [`RuntimeValue::String`]
+    String,
+    /// Matches [`RuntimeValue::Bytes`]
+    Bytes,
+    /// Matches [`RuntimeValue::Array`]
+    Array,
+    /// Matches [`RuntimeValue::Object`]
+    Object,
+    /// Matches any value
+    Any,
+}
+
+impl ValueKind {
+    /// Check whether `value` satisfies this kind
+    pub fn matches(&self, value: &RuntimeValue) -> bool {
+        match self {
+            ValueKind::Any => true,
+            ValueKind::Null => value.is_null(),
+            ValueKind::Bool => matches!(value, RuntimeValue::Bool(_)),
+            ValueKind::Number => matches!(value, RuntimeValue::Number(_)),
+            ValueKind::String => matches!(value, RuntimeValue::String(_)),
+            ValueKind::Bytes => matches!(value, RuntimeValue::Bytes(_)),
+            ValueKind::Array => matches!(value, RuntimeValue::Array(_)),
+            ValueKind::Object => matches!(value, RuntimeValue::Object(_)),
+        }
+    }
+}
+
+/// One declared parameter in a [`MethodSignature`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParamSpec {
+    /// Parameter name, used only for error messages and introspection
+    pub name: String,
+    /// Expected value kind
+    pub kind: ValueKind,
+    /// Whether this parameter (and any after it) may be omitted
+    #[serde(default)]
+    pub optional: bool,
+}
+
+impl ParamSpec {
+    /// Declare a required parameter
+    pub fn new(name: impl Into<String>, kind: ValueKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            optional: false,
+        }
+    }
+
+    /// Mark this parameter optional
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+}
+
+/// The declared argument shape for an extension method: parameter arity,
+/// each parameter's expected [`ValueKind`], and which trailing parameters
+/// are optional. [`Self::validate`] checks a call's `args` against this
+/// before [`crate::host_functions::extension::ext_suspend`] allocates a
+/// suspension id, so a bad call fails fast with [`ErrorCode::InvalidArgument`]
+/// instead of surfacing (or panicking) once the extension finally runs it.
+///
+/// [`ErrorCode::InvalidArgument`]: crate::error::ErrorCode::InvalidArgument
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MethodSignature {
+    /// Declared parameters, required ones first
+    pub params: Vec<ParamSpec>,
+}
+
+impl MethodSignature {
+    /// Declare a signature from its parameters
+    pub fn new(params: Vec<ParamSpec>) -> Self {
+        Self { params }
+    }
+
+    /// Count of required (non-optional) leading parameters. Optional
+    /// parameters are expected to trail the required ones, mirroring JS
+    /// default-parameter conventions.
+    fn required_count(&self) -> usize {
+        self.params.iter().take_while(|p| !p.optional).count()
+    }
+
+    /// Validate `args` against this signature, returning a message naming
+    /// the first mismatch (arity or an individual parameter's kind).
+    pub fn validate(&self, args: &[RuntimeValue]) -> Result<(), String> {
+        let required = self.required_count();
+        let max = self.params.len();
+        if args.len() < required || args.len() > max {
+            return Err(if required == max {
+                format!(
+                    "expected {} argument{}, got {}",
+                    required,
+                    if required == 1 { "" } else { "s" },
+                    args.len()
+                )
+            } else {
+                format!(
+                    "expected {}-{} arguments, got {}",
+                    required,
+                    max,
+                    args.len()
+                )
+            });
+        }
+
+        for (param, arg) in self.params.iter().zip(args) {
+            if !param.kind.matches(arg) {
+                return Err(format!(
+                    "parameter '{}' expects a {:?} value",
+                    param.name, param.kind
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Execution context passed to WASM handler
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -139,8 +377,8 @@ pub struct WasmContext {
     /// Scope variables (from If/Iterate context)
     pub scope: HashMap<String, RuntimeValue>,
 
-    /// Extension registry (name -> available methods)
-    pub extension_registry: HashMap<String, Vec<String>>,
+    /// Extension registry (extension name -> method name -> signature)
+    pub extension_registry: HashMap<String, HashMap<String, MethodSignature>>,
 }
 
 impl WasmContext {
@@ -182,7 +420,10 @@ impl WasmContext {
     }
 
     /// Set extension registry
-    pub fn with_extensions(mut self, ext: HashMap<String, Vec<String>>) -> Self {
+    pub fn with_extensions(
+        mut self,
+        ext: HashMap<String, HashMap<String, MethodSignature>>,
+    ) -> Self {
         self.extension_registry = ext;
         self
     }
@@ -198,6 +439,11 @@ pub enum ExecutionStatus {
     Error,
     /// Handler suspended waiting for async operation
     Suspended,
+    /// Handler was trapped after exhausting its gas (instruction) budget
+    GasExhausted,
+    /// Handler was trapped after crossing a `ResourceLimits` host-call,
+    /// state-mutation, or event ceiling (see [`ResourceLimitKind`])
+    ResourceExhausted,
 }
 
 /// Result returned from WASM handler execution
@@ -220,9 +466,17 @@ pub struct WasmResult {
     /// View commands to execute IMMEDIATELY
     pub view_commands: Vec<ViewCommand>,
 
-    /// Suspension details (if status === 'suspended')
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub suspension: Option<SuspensionDetails>,
+    /// Pending suspensions (if status === 'suspended'); more than one entry
+    /// means the handler fanned out several concurrent extension calls
+    /// (e.g. `Promise.all`) and is waiting on `join_mode`'s condition before
+    /// resuming
+    pub suspensions: Vec<SuspensionDetails>,
+
+    /// Which condition `suspensions` must satisfy before the handler
+    /// resumes. Meaningless outside `status === 'suspended'`, where it
+    /// defaults to [`JoinMode::All`].
+    #[serde(default)]
+    pub join_mode: JoinMode,
 
     /// Error details (if status === 'error')
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -241,7 +495,8 @@ impl WasmResult {
             state_mutations: Vec::new(),
             events: Vec::new(),
             view_commands: Vec::new(),
-            suspension: None,
+            suspensions: Vec::new(),
+            join_mode: JoinMode::default(),
             error: None,
             metrics,
         }
@@ -255,26 +510,64 @@ impl WasmResult {
             state_mutations: Vec::new(),
             events: Vec::new(),
             view_commands: Vec::new(),
-            suspension: None,
+            suspensions: Vec::new(),
+            join_mode: JoinMode::default(),
             error: Some(error),
             metrics,
         }
     }
 
-    /// Create a suspended result
-    pub fn suspended(suspension: SuspensionDetails, metrics: ExecutionMetrics) -> Self {
+    /// Create a suspended result, pending on one or more concurrent
+    /// extension calls joined under `join_mode`
+    pub fn suspended(
+        suspensions: Vec<SuspensionDetails>,
+        join_mode: JoinMode,
+        metrics: ExecutionMetrics,
+    ) -> Self {
         Self {
             status: ExecutionStatus::Suspended,
             return_value: None,
             state_mutations: Vec::new(),
             events: Vec::new(),
             view_commands: Vec::new(),
-            suspension: Some(suspension),
+            suspensions,
+            join_mode,
             error: None,
             metrics,
         }
     }
 
+    /// Create a gas-exhausted result
+    pub fn gas_exhausted(error: WasmError, metrics: ExecutionMetrics) -> Self {
+        Self {
+            status: ExecutionStatus::GasExhausted,
+            return_value: None,
+            state_mutations: Vec::new(),
+            events: Vec::new(),
+            view_commands: Vec::new(),
+            suspensions: Vec::new(),
+            join_mode: JoinMode::default(),
+            error: Some(error),
+            metrics,
+        }
+    }
+
+    /// Create a result for a handler trapped by a crossed `ResourceLimits`
+    /// ceiling (host calls, state mutations, or events)
+    pub fn resource_exhausted(error: WasmError, metrics: ExecutionMetrics) -> Self {
+        Self {
+            status: ExecutionStatus::ResourceExhausted,
+            return_value: None,
+            state_mutations: Vec::new(),
+            events: Vec::new(),
+            view_commands: Vec::new(),
+            suspensions: Vec::new(),
+            join_mode: JoinMode::default(),
+            error: Some(error),
+            metrics,
+        }
+    }
+
     /// Add state mutations
     pub fn with_mutations(mut self, mutations: Vec<StateMutation>) -> Self {
         self.state_mutations = mutations;
@@ -306,6 +599,17 @@ pub struct StateMutation {
 
     /// Operation type
     pub operation: MutationOperation,
+
+    /// Host-captured time this mutation was recorded, assigned by
+    /// [`ExecutionContext::add_mutation`] (from [`crate::host_functions::logging::now`]);
+    /// `0.0` until then
+    pub timestamp_ms: f64,
+
+    /// Per-execution monotonic sequence number assigned by
+    /// [`ExecutionContext::add_mutation`], letting a replay interleave this
+    /// mutation with `EmittedEvent::seq` in the exact order both were
+    /// produced rather than relying on separate `Vec` indices
+    pub seq: u64,
 }
 
 impl StateMutation {
@@ -315,6 +619,8 @@ impl StateMutation {
             key: key.into(),
             value,
             operation: MutationOperation::Set,
+            timestamp_ms: 0.0,
+            seq: 0,
         }
     }
 
@@ -324,18 +630,47 @@ impl StateMutation {
             key: key.into(),
             value: RuntimeValue::Null,
             operation: MutationOperation::Delete,
+            timestamp_ms: 0.0,
+            seq: 0,
+        }
+    }
+
+    /// Create a compare-and-set mutation: write `value` only if the live
+    /// state at commit time still holds `expected` (`None` meaning the key
+    /// must not exist yet). Gives a handler that resumes from a suspension a
+    /// lost-update-free read-modify-write, where an unconditional `set`
+    /// would silently clobber a write made by another handler in between.
+    pub fn compare_and_set(
+        key: impl Into<String>,
+        expected: Option<RuntimeValue>,
+        value: RuntimeValue,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            value,
+            operation: MutationOperation::CompareAndSet { expected },
+            timestamp_ms: 0.0,
+            seq: 0,
         }
     }
 }
 
 /// Mutation operation type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum MutationOperation {
     /// Set a value
     Set,
     /// Delete a value
     Delete,
+    /// Write the accompanying `StateMutation::value` only if the live value
+    /// currently equals `expected`; otherwise the mutation applier rejects
+    /// the commit with [`crate::error::error_codes::CONFLICT`]
+    CompareAndSet {
+        /// Value the key is expected to hold right now (`None` meaning it
+        /// must not exist yet)
+        expected: Option<RuntimeValue>,
+    },
 }
 
 /// Event emission record
@@ -347,6 +682,17 @@ pub struct EmittedEvent {
 
     /// Event payload
     pub payload: RuntimeValue,
+
+    /// Host-captured time this event was recorded, assigned by
+    /// [`ExecutionContext::add_event`] (from [`crate::host_functions::logging::now`]);
+    /// `0.0` until then
+    pub timestamp_ms: f64,
+
+    /// Per-execution monotonic sequence number assigned by
+    /// [`ExecutionContext::add_event`], letting a replay correlate this event
+    /// with the `StateMutation::seq` it was produced alongside rather than
+    /// relying on separate `Vec` indices
+    pub seq: u64,
 }
 
 impl EmittedEvent {
@@ -355,6 +701,8 @@ impl EmittedEvent {
         Self {
             name: name.into(),
             payload,
+            timestamp_ms: 0.0,
+            seq: 0,
         }
     }
 }
@@ -409,7 +757,7 @@ impl ViewCommand {
 }
 
 /// View command types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum ViewCommandType {
     /// Set filter on a component
@@ -422,6 +770,27 @@ pub enum ViewCommandType {
     Custom,
 }
 
+/// Which join condition a concurrently-suspended batch of extension calls
+/// (registered together via [`crate::host_functions::extension::ext_suspend_many`])
+/// must satisfy before the handler resumes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinMode {
+    /// Resume only once every outstanding call in the batch has resolved
+    /// (`Promise.all`); a rejection from any of them still waits for the
+    /// rest so every error can be surfaced together
+    All,
+    /// Resume as soon as the first outstanding call resolves
+    /// (`Promise.race`/`Promise.any`); the remaining calls are abandoned
+    Any,
+}
+
+impl Default for JoinMode {
+    fn default() -> Self {
+        JoinMode::All
+    }
+}
+
 /// Suspension details for async operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -429,6 +798,12 @@ pub struct SuspensionDetails {
     /// Unique suspension ID for resuming
     pub suspension_id: String,
 
+    /// Monotonically increasing sequence number assigned when this call
+    /// suspended, mirroring the request/response correlation scheme used by
+    /// debug-adapter transports. Ordering survives even if `suspension_id`s
+    /// (UUIDs) sort unpredictably.
+    pub seq: u64,
+
     /// Extension name (e.g., 'http')
     pub extension_name: String,
 
@@ -437,21 +812,29 @@ pub struct SuspensionDetails {
 
     /// Method arguments
     pub args: Vec<RuntimeValue>,
+
+    /// Gas remaining at the moment of suspension, so `resume` can continue
+    /// charging against the leftover budget instead of a fresh allowance
+    pub gas_remaining: u64,
 }
 
 impl SuspensionDetails {
     /// Create new suspension details
     pub fn new(
         suspension_id: impl Into<String>,
+        seq: u64,
         extension_name: impl Into<String>,
         method: impl Into<String>,
         args: Vec<RuntimeValue>,
+        gas_remaining: u64,
     ) -> Self {
         Self {
             suspension_id: suspension_id.into(),
+            seq,
             extension_name: extension_name.into(),
             method: method.into(),
             args,
+            gas_remaining,
         }
     }
 }
@@ -492,6 +875,32 @@ impl AsyncResult {
     }
 }
 
+/// Which [`crate::config::ResourceLimits`] ceiling a call-counter tripped,
+/// reported on [`ExecutionContext::resource_exhausted`] and
+/// [`crate::metrics::ExecutionMetrics::resource_limit_exceeded`] so an
+/// aborted execution's cause can be told apart from a plain [`WasmError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceLimitKind {
+    /// `ResourceLimits::max_host_calls` was exceeded
+    HostCalls,
+    /// `ResourceLimits::max_state_mutations` was exceeded
+    StateMutations,
+    /// `ResourceLimits::max_events` was exceeded
+    Events,
+}
+
+impl ResourceLimitKind {
+    /// Label used as the metrics key and Prometheus label value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResourceLimitKind::HostCalls => "host_calls",
+            ResourceLimitKind::StateMutations => "state_mutations",
+            ResourceLimitKind::Events => "events",
+        }
+    }
+}
+
 /// Internal execution context used during handler execution
 #[derive(Debug)]
 pub struct ExecutionContext {
@@ -513,8 +922,8 @@ pub struct ExecutionContext {
     /// Capabilities
     pub capabilities: Vec<CapabilityToken>,
 
-    /// Extension registry
-    pub extension_registry: HashMap<String, Vec<String>>,
+    /// Extension registry (extension name -> method name -> signature)
+    pub extension_registry: HashMap<String, HashMap<String, MethodSignature>>,
 
     /// Collected state mutations
     pub state_mutations: Vec<StateMutation>,
@@ -525,14 +934,95 @@ pub struct ExecutionContext {
     /// Collected view commands
     pub view_commands: Vec<ViewCommand>,
 
+    /// Buffered commands for an in-progress view batch (see
+    /// [`Self::begin_view_batch`]), `None` when no batch is active
+    view_batch: Option<Vec<ViewCommand>>,
+
     /// Log messages
     pub log_messages: Vec<LogMessage>,
 
     /// Host function call count
     pub host_call_count: u32,
 
-    /// Current suspension state
-    pub suspension: Option<SuspensionState>,
+    /// Maximum host function calls allowed before [`Self::increment_host_calls`]
+    /// reports the ceiling crossed; defaults to
+    /// `ResourceLimits::default().max_host_calls` until [`Self::set_resource_limits`]
+    /// applies the execution's actual configured limits
+    max_host_calls: u32,
+
+    /// Maximum state mutations allowed before [`Self::add_mutation`] rejects
+    /// a new one; see [`Self::max_host_calls`] for the default/override story
+    max_state_mutations: u32,
+
+    /// Maximum events allowed before [`Self::add_event`] rejects a new one;
+    /// see [`Self::max_host_calls`] for the default/override story
+    max_events: u32,
+
+    /// Which [`ResourceLimits`] ceiling was crossed first, if any; the
+    /// instance that drives this context to completion surfaces this as a
+    /// typed [`WasmError::resource_exhausted`] and records it on
+    /// [`crate::metrics::ExecutionMetrics::resource_limit_exceeded`]
+    pub resource_exhausted: Option<ResourceLimitKind>,
+
+    /// Gas (instruction budget) remaining; `u64::MAX` until
+    /// [`Self::set_gas_remaining`] applies an actual limit
+    pub gas_remaining: u64,
+
+    /// Pending suspensions, keyed by suspension ID. More than one entry
+    /// means the handler has fanned out several concurrent extension calls
+    /// (e.g. `Promise.all`); whether the instance returns to `Idle` once
+    /// every entry is resolved or as soon as the first one is depends on
+    /// `join_mode`. See [`Self::resolve_suspension`] (plain removal) and
+    /// [`Self::resolve`] (join-aware, records the result for delivery).
+    pub suspensions: HashMap<String, SuspensionState>,
+
+    /// Join condition the current batch of `suspensions` must satisfy
+    /// before [`Self::resolve`] reports the handler ready to resume; set by
+    /// [`Self::set_join_mode`] when the batch is registered (defaults to
+    /// [`JoinMode::All`] for a lone [`crate::host_functions::extension::ext_suspend`] call)
+    pub join_mode: JoinMode,
+
+    /// Sequence counter handed out by [`Self::next_seq`], incremented once
+    /// per suspended call so concurrent suspensions can be ordered even
+    /// though `suspension_id`s (UUIDs) don't sort meaningfully
+    next_seq: u64,
+
+    /// Results delivered via [`Self::resolve`] for suspensions that have
+    /// settled but not yet been taken by [`Self::take_resolved`] once the
+    /// join condition is met. Keyed by `suspension_id` so the guest can
+    /// match each completion to its originating call.
+    resolved: HashMap<String, AsyncResult>,
+
+    /// Sequence counter handed out by [`Self::add_mutation`]/[`Self::add_event`],
+    /// shared across both so a replay can interleave `StateMutation`s and
+    /// `EmittedEvent`s in the exact total order they were produced, rather
+    /// than relying on separate `Vec` indices per record type
+    next_effect_seq: u64,
+
+    /// Details of the most recent host-call panic caught by
+    /// [`crate::host_functions::HostFunctions::call_guarded`], if any. Kept
+    /// around so the eventual `WasmError::internal_error` for this execution
+    /// can surface it via `with_context` instead of collapsing a crashing
+    /// host function down to a bare error code.
+    pub last_panic: Option<PanicDetails>,
+
+    /// The `WasmError` an extension reported back through
+    /// [`crate::host_functions::extension::ext_resume`] for the most
+    /// recently rejected suspension, if any. The host call itself can only
+    /// return an `i32` code, so this is where the rich error (message,
+    /// stack, etc.) is kept for the final result to surface.
+    pub last_extension_error: Option<WasmError>,
+
+    /// The `WasmError` for the most recent capability denial from
+    /// [`Self::add_view_command`] or [`Self::add_event`], if any. Those
+    /// methods can only return `bool` to their host-function callers, so
+    /// this is where the rich error (capability, operation) is kept for the
+    /// host function to surface instead of a bare error code.
+    pub last_capability_error: Option<WasmError>,
+
+    /// Sink notified synchronously as each event is emitted, if set (see
+    /// [`Self::set_event_sink`])
+    event_sink: Option<Arc<dyn EventSink>>,
 }
 
 impl ExecutionContext {
@@ -549,50 +1039,414 @@ impl ExecutionContext {
             state_mutations: Vec::new(),
             events: Vec::new(),
             view_commands: Vec::new(),
+            view_batch: None,
             log_messages: Vec::new(),
             host_call_count: 0,
-            suspension: None,
+            max_host_calls: crate::config::DEFAULT_MAX_HOST_CALLS,
+            max_state_mutations: crate::config::DEFAULT_MAX_STATE_MUTATIONS,
+            max_events: crate::config::DEFAULT_MAX_EVENTS,
+            resource_exhausted: None,
+            gas_remaining: u64::MAX,
+            suspensions: HashMap::new(),
+            join_mode: JoinMode::default(),
+            next_seq: 0,
+            resolved: HashMap::new(),
+            next_effect_seq: 0,
+            last_panic: None,
+            last_extension_error: None,
+            last_capability_error: None,
+            event_sink: None,
         }
     }
 
+    /// Set the sink notified synchronously as each event is emitted, in
+    /// addition to the normal buffering into `events`
+    pub fn set_event_sink(&mut self, sink: Arc<dyn EventSink>) {
+        self.event_sink = Some(sink);
+    }
+
     /// Check if a capability is granted
     pub fn has_capability(&self, cap: &str) -> bool {
         self.capabilities.iter().any(|c| c.matches(cap))
     }
 
-    /// Increment host call counter
+    /// Argument constraints from every granted
+    /// [`CapabilityToken::ExtensionMethod`] matching `ext_name`/`method`, in
+    /// grant order. A handler call must satisfy all of them, across all
+    /// matching grants. Returned owned (rather than borrowed) so callers can
+    /// check them against an `&mut self` without holding a borrow open.
+    pub fn extension_method_constraints(&self, ext_name: &str, method: &str) -> Vec<ArgConstraint> {
+        self.capabilities
+            .iter()
+            .filter_map(|c| match c {
+                CapabilityToken::ExtensionMethod {
+                    ext,
+                    method: granted_method,
+                    constraints,
+                } if ext == ext_name && granted_method == method => Some(constraints.iter().cloned()),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Look up a registered extension method's declared signature, if the
+    /// extension and method both exist
+    pub fn extension_signature(&self, ext_name: &str, method: &str) -> Option<&MethodSignature> {
+        self.extension_registry.get(ext_name)?.get(method)
+    }
+
+    /// Apply the `max_host_calls`/`max_state_mutations`/`max_events`
+    /// ceilings from a [`ResourceLimits`], replacing the defaults
+    /// [`Self::from_wasm_context`] started with. Called once when execution
+    /// begins; resuming a suspension must not call this again, so a ceiling
+    /// already crossed before suspension stays crossed.
+    pub fn set_resource_limits(&mut self, limits: &ResourceLimits) {
+        self.max_host_calls = limits.max_host_calls;
+        self.max_state_mutations = limits.max_state_mutations;
+        self.max_events = limits.max_events;
+    }
+
+    /// If a `ResourceLimits` ceiling has been crossed, the kind that tripped
+    /// first along with its configured limit and the current count: the
+    /// host-call count including the call that crossed the limit, or the
+    /// number of mutations/events actually recorded (the rejected one is
+    /// not counted, so this equals the limit)
+    pub fn resource_limit_violation(&self) -> Option<(ResourceLimitKind, u32, u32)> {
+        match self.resource_exhausted? {
+            ResourceLimitKind::HostCalls => {
+                Some((ResourceLimitKind::HostCalls, self.max_host_calls, self.host_call_count))
+            }
+            ResourceLimitKind::StateMutations => Some((
+                ResourceLimitKind::StateMutations,
+                self.max_state_mutations,
+                self.state_mutations.len() as u32,
+            )),
+            ResourceLimitKind::Events => {
+                Some((ResourceLimitKind::Events, self.max_events, self.events.len() as u32))
+            }
+        }
+    }
+
+    /// Increment host call counter, recording
+    /// [`ResourceLimitKind::HostCalls`] on [`Self::resource_exhausted`] once
+    /// `max_host_calls` is exceeded
     pub fn increment_host_calls(&mut self) -> u32 {
         self.host_call_count += 1;
+        if self.host_call_count > self.max_host_calls {
+            self.resource_exhausted.get_or_insert(ResourceLimitKind::HostCalls);
+        }
         self.host_call_count
     }
 
-    /// Add a state mutation
-    pub fn add_mutation(&mut self, mutation: StateMutation) {
+    /// Apply a gas (instruction) budget, replacing whatever was left from a
+    /// previous call. A `limit` of `0` means no limit is enforced. Called
+    /// once when execution begins; resuming a suspension must not call this
+    /// again, so the leftover `gas_remaining` carries over instead of being
+    /// reset to a fresh allowance.
+    pub fn set_gas_remaining(&mut self, limit: u64) {
+        self.gas_remaining = if limit == 0 { u64::MAX } else { limit };
+    }
+
+    /// Charge `cost` units of gas for the basic block about to execute,
+    /// saturating at zero. Returns `false` once the budget is exhausted,
+    /// which the caller should treat as a trap.
+    pub fn charge_gas(&mut self, cost: u64) -> bool {
+        if self.gas_remaining == 0 {
+            return false;
+        }
+        self.gas_remaining = self.gas_remaining.saturating_sub(cost);
+        self.gas_remaining > 0
+    }
+
+    /// Add a state mutation, stamping it with the current time and the next
+    /// shared effect sequence number (see [`Self::next_effect_seq`]) before
+    /// forwarding it synchronously to the event sink (if one is set) and
+    /// buffering it into `state_mutations`. Returns `false` without
+    /// recording it once `max_state_mutations` has already been reached,
+    /// setting [`ResourceLimitKind::StateMutations`] on
+    /// [`Self::resource_exhausted`].
+    pub fn add_mutation(&mut self, mut mutation: StateMutation) -> bool {
+        if self.state_mutations.len() as u32 >= self.max_state_mutations {
+            self.resource_exhausted.get_or_insert(ResourceLimitKind::StateMutations);
+            return false;
+        }
+        mutation.timestamp_ms = crate::host_functions::logging::now().unwrap_or(0.0);
+        mutation.seq = self.next_effect_seq;
+        self.next_effect_seq += 1;
+        if let Some(sink) = &self.event_sink {
+            sink.on_mutation(&self.panel_id, &self.handler_name, &mutation);
+        }
         self.state_mutations.push(mutation);
+        true
     }
 
-    /// Add an event
-    pub fn add_event(&mut self, event: EmittedEvent) {
+    /// Add an event, stamping it with the current time and the next shared
+    /// effect sequence number (see [`Self::next_effect_seq`]) before
+    /// forwarding it synchronously to the event sink (if one is set) and
+    /// buffering it into `events`. Returns `false` without recording it if
+    /// the caller lacks an `events:emit:{name_glob}`/`events:emit:*`
+    /// capability covering `event.name` (setting
+    /// [`Self::last_capability_error`]) or if
+    /// `max_events` has already been reached (setting
+    /// [`ResourceLimitKind::Events`] on [`Self::resource_exhausted`]).
+    pub fn add_event(&mut self, mut event: EmittedEvent) -> bool {
+        self.last_capability_error = None;
+        let required = format!("events:emit:{}", event.name);
+        if !self.has_capability(&required) {
+            self.last_capability_error = Some(WasmError::permission_denied(
+                required,
+                format!("emit event '{}'", event.name),
+            ));
+            return false;
+        }
+        if self.events.len() as u32 >= self.max_events {
+            self.resource_exhausted.get_or_insert(ResourceLimitKind::Events);
+            return false;
+        }
+        event.timestamp_ms = crate::host_functions::logging::now().unwrap_or(0.0);
+        event.seq = self.next_effect_seq;
+        self.next_effect_seq += 1;
+        if let Some(sink) = &self.event_sink {
+            sink.on_event(&self.panel_id, &self.handler_name, &event);
+        }
         self.events.push(event);
+        true
     }
 
     /// Add a view command
-    pub fn add_view_command(&mut self, command: ViewCommand) {
-        self.view_commands.push(command);
+    ///
+    /// Returns `false` without recording it if the caller lacks a
+    /// `view:update:{id}`/`view:update:*` capability covering
+    /// `command.component_id` (setting [`Self::last_capability_error`]).
+    ///
+    /// If a batch is currently open (see [`Self::begin_view_batch`]), the
+    /// command is buffered rather than recorded immediately, so a later
+    /// [`Self::abort_view_batch`] discards it along with the rest of the
+    /// batch; the event sink (if any) is only notified once the command is
+    /// actually recorded, i.e. immediately here or at
+    /// [`Self::commit_view_batch`].
+    pub fn add_view_command(&mut self, command: ViewCommand) -> bool {
+        self.last_capability_error = None;
+        let required = match &command.component_id {
+            Some(id) => format!("view:update:{}", id),
+            None => "view:update:*".to_string(),
+        };
+        if !self.has_capability(&required) {
+            self.last_capability_error = Some(WasmError::permission_denied(
+                required,
+                "drive view component",
+            ));
+            return false;
+        }
+
+        match self.view_batch.as_mut() {
+            Some(batch) => batch.push(command),
+            None => {
+                if let Some(sink) = &self.event_sink {
+                    sink.on_view_command(&self.panel_id, &self.handler_name, &command);
+                }
+                self.view_commands.push(command);
+            }
+        }
+        true
+    }
+
+    /// Start buffering view commands instead of recording them immediately.
+    /// Starting a new batch while one is already open discards the old one.
+    pub fn begin_view_batch(&mut self) {
+        self.view_batch = Some(Vec::new());
+    }
+
+    /// Discard the currently buffered batch, if any, without recording
+    /// anything. Used to make a batch atomic: one denied command aborts the
+    /// whole batch.
+    pub fn abort_view_batch(&mut self) {
+        self.view_batch = None;
+    }
+
+    /// Coalesce and commit the currently buffered batch into `view_commands`.
+    /// For idempotent command types (`SetFilter`, `ScrollTo`, `Focus`) only
+    /// the last command per `(component_id, command_type)` is kept, at the
+    /// position of that target's first occurrence in the batch; `Custom`
+    /// commands are appended untouched. A no-op if no batch is open.
+    pub fn commit_view_batch(&mut self) {
+        if let Some(batch) = self.view_batch.take() {
+            let coalesced = coalesce_view_commands(batch);
+            if let Some(sink) = &self.event_sink {
+                for command in &coalesced {
+                    sink.on_view_command(&self.panel_id, &self.handler_name, command);
+                }
+            }
+            self.view_commands.extend(coalesced);
+        }
     }
 
     /// Add a log message
     pub fn add_log(&mut self, level: LogLevel, message: String) {
-        self.log_messages.push(LogMessage { level, message });
+        self.add_log_with_fields(level, message, None);
+    }
+
+    /// Add a log message with structured key-value context attached (see
+    /// [`LogMessage::fields`]), as recorded by `log_structured`
+    pub fn add_log_with_fields(
+        &mut self,
+        level: LogLevel,
+        message: String,
+        fields: Option<HashMap<String, RuntimeValue>>,
+    ) {
+        self.log_messages.push(LogMessage {
+            level,
+            message,
+            fields,
+        });
+    }
+
+    /// Hand out the next sequence number for a newly suspended call (see
+    /// [`SuspensionDetails::seq`]), incrementing the counter.
+    pub fn next_seq(&mut self) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        seq
+    }
+
+    /// Set the join condition the current batch of concurrent suspensions
+    /// must satisfy before [`Self::resolve`] reports the handler ready to
+    /// resume. Called once per batch, before its calls are registered via
+    /// [`Self::add_suspension`].
+    pub fn set_join_mode(&mut self, mode: JoinMode) {
+        self.join_mode = mode;
+    }
+
+    /// Register a new pending suspension, keyed by its id. A handler that
+    /// fans out several concurrent extension calls (`Promise.all`) ends up
+    /// with more than one entry here at once.
+    pub fn add_suspension(&mut self, suspension: SuspensionState) {
+        self.suspensions.insert(suspension.id.clone(), suspension);
+    }
+
+    /// Resolve (remove) one pending suspension by id, returning its state if
+    /// it was actually pending. Does not by itself mean the handler can
+    /// resume — see [`Self::has_pending_suspensions`].
+    pub fn resolve_suspension(&mut self, suspension_id: &str) -> Option<SuspensionState> {
+        self.suspensions.remove(suspension_id)
+    }
+
+    /// Record the result of a settled outstanding call, keyed by
+    /// `suspension_id`, and report whether `join_mode`'s condition is now
+    /// met: every outstanding id resolved for [`JoinMode::All`], or just this
+    /// one for [`JoinMode::Any`]. Returns `None` if `suspension_id` wasn't
+    /// actually pending.
+    ///
+    /// The handler should only be redispatched once this returns
+    /// `Some(true)`; until then the result sits in `resolved`; at that point
+    /// [`Self::take_resolved`] hands back the stable `suspension_id ->
+    /// AsyncResult` map so the guest can match each completion to its
+    /// originating call.
+    pub fn resolve(&mut self, suspension_id: &str, result: AsyncResult) -> Option<bool> {
+        self.suspensions.remove(suspension_id)?;
+        self.resolved.insert(suspension_id.to_string(), result);
+        Some(match self.join_mode {
+            JoinMode::All => self.suspensions.is_empty(),
+            JoinMode::Any => true,
+        })
+    }
+
+    /// Drain the results recorded by [`Self::resolve`], for delivery to the
+    /// handler once its join condition is met. Any calls still outstanding
+    /// (only possible under [`JoinMode::Any`], whose sibling calls are
+    /// abandoned once the first settles) are dropped along with them.
+    pub fn take_resolved(&mut self) -> HashMap<String, AsyncResult> {
+        self.suspensions.clear();
+        std::mem::take(&mut self.resolved)
+    }
+
+    /// Whether any fanned-out extension calls are still awaiting resolution.
+    pub fn has_pending_suspensions(&self) -> bool {
+        !self.suspensions.is_empty()
+    }
+
+    /// Record a panic caught while dispatching a host call, overwriting any
+    /// previous record (only the most recent one is kept).
+    pub fn record_panic(&mut self, host_function: &str, message: impl Into<String>, location: impl Into<String>) {
+        self.last_panic = Some(PanicDetails {
+            host_function: host_function.to_string(),
+            message: message.into(),
+            location: location.into(),
+        });
+    }
+
+    /// Record the `WasmError` an extension rejected a suspension with,
+    /// overwriting any previous record (only the most recent one is kept).
+    pub fn record_extension_error(&mut self, error: WasmError) {
+        self.last_extension_error = Some(error);
+    }
+}
+
+/// Details of a panic caught while dispatching a host call, recorded on
+/// [`ExecutionContext::last_panic`] by
+/// [`crate::host_functions::HostFunctions::call_guarded`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanicDetails {
+    /// Name of the host call that panicked (the `name` passed to `call_guarded`)
+    pub host_function: String,
+    /// The panic payload, rendered the same way a panic hook would
+    pub message: String,
+    /// Source location of the `call_guarded` call site
+    pub location: String,
+}
+
+/// Coalesce a batch of view commands, keeping only the last command for
+/// each `(component_id, command_type)` target and placing it at the
+/// position of that target's first occurrence. `Custom` commands are never
+/// coalesced, since their semantics are opaque to the runtime.
+fn coalesce_view_commands(batch: Vec<ViewCommand>) -> Vec<ViewCommand> {
+    let mut slots: Vec<Option<ViewCommand>> = Vec::with_capacity(batch.len());
+    let mut slot_for_key: HashMap<(Option<String>, ViewCommandType), usize> = HashMap::new();
+
+    for command in batch {
+        if command.command_type == ViewCommandType::Custom {
+            slots.push(Some(command));
+            continue;
+        }
+
+        let key = (command.component_id.clone(), command.command_type);
+        match slot_for_key.get(&key) {
+            Some(&index) => slots[index] = Some(command),
+            None => {
+                slot_for_key.insert(key, slots.len());
+                slots.push(Some(command));
+            }
+        }
+    }
+
+    slots.into_iter().flatten().collect()
+}
+
+impl From<&SuspensionState> for SuspensionDetails {
+    fn from(state: &SuspensionState) -> Self {
+        SuspensionDetails::new(
+            state.id.clone(),
+            state.seq,
+            state.extension_name.clone(),
+            state.method.clone(),
+            state.args.clone(),
+            state.gas_remaining,
+        )
     }
 }
 
 /// Suspension state for async operations
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SuspensionState {
     /// Suspension ID
     pub id: String,
 
+    /// Sequence number assigned when this call suspended (see
+    /// [`SuspensionDetails::seq`])
+    pub seq: u64,
+
     /// Extension name
     pub extension_name: String,
 
@@ -601,6 +1455,10 @@ pub struct SuspensionState {
 
     /// Arguments
     pub args: Vec<RuntimeValue>,
+
+    /// Gas remaining at the moment of suspension (see
+    /// [`SuspensionDetails::gas_remaining`])
+    pub gas_remaining: u64,
 }
 
 /// Log message
@@ -611,6 +1469,10 @@ pub struct LogMessage {
 
     /// Message content
     pub message: String,
+
+    /// Structured key-value context attached via `log_structured`; `None`
+    /// for plain `log` calls
+    pub fields: Option<HashMap<String, RuntimeValue>>,
 }
 
 /// Log levels
@@ -641,6 +1503,7 @@ impl From<i32> for LogLevel {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use parking_lot::Mutex;
 
     #[test]
     fn test_runtime_value_conversions() {
@@ -652,6 +1515,26 @@ mod tests {
 
         let v: RuntimeValue = "hello".into();
         assert_eq!(v.as_str(), Some("hello"));
+
+        let v: RuntimeValue = vec![1u8, 2, 3].into();
+        assert_eq!(v.as_bytes(), Some(&[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn test_bytes_round_trips_through_json_as_base64_string() {
+        let original = RuntimeValue::Bytes(vec![0, 1, 2, 253, 254, 255]);
+
+        let json = serde_json::to_string(&original).unwrap();
+        assert!(json.starts_with("\"$bytes:"));
+
+        let decoded: RuntimeValue = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, original);
+    }
+
+    #[test]
+    fn test_plain_string_does_not_deserialize_as_bytes() {
+        let value: RuntimeValue = serde_json::from_str("\"hello\"").unwrap();
+        assert_eq!(value, RuntimeValue::String("hello".to_string()));
     }
 
     #[test]
@@ -691,4 +1574,424 @@ mod tests {
         assert!(!error.success);
         assert_eq!(error.error, Some("failed".into()));
     }
+
+    fn test_context() -> ExecutionContext {
+        ExecutionContext::from_wasm_context(
+            WasmContext::new("panel-1", "on_click").with_capabilities(vec![
+                CapabilityToken::ViewUpdateAll,
+                CapabilityToken::EventsEmitAll,
+            ]),
+        )
+    }
+
+    #[test]
+    fn test_view_batch_coalesces_by_target() {
+        let mut ctx = test_context();
+
+        ctx.begin_view_batch();
+        ctx.add_view_command(ViewCommand::set_filter("list", RuntimeValue::from("a")));
+        ctx.add_view_command(ViewCommand::scroll_to("list", RuntimeValue::from(10.0)));
+        ctx.add_view_command(ViewCommand::set_filter("list", RuntimeValue::from("b")));
+        ctx.commit_view_batch();
+
+        assert_eq!(ctx.view_commands.len(), 2);
+        assert_eq!(ctx.view_commands[0].command_type, ViewCommandType::SetFilter);
+        assert_eq!(
+            ctx.view_commands[0].args.get("value"),
+            Some(&RuntimeValue::from("b"))
+        );
+        assert_eq!(ctx.view_commands[1].command_type, ViewCommandType::ScrollTo);
+    }
+
+    #[test]
+    fn test_view_batch_never_coalesces_custom_commands() {
+        let mut ctx = test_context();
+
+        let custom = ViewCommand {
+            command_type: ViewCommandType::Custom,
+            component_id: Some("chart".to_string()),
+            args: HashMap::new(),
+        };
+
+        ctx.begin_view_batch();
+        ctx.add_view_command(custom.clone());
+        ctx.add_view_command(custom);
+        ctx.commit_view_batch();
+
+        assert_eq!(ctx.view_commands.len(), 2);
+    }
+
+    #[test]
+    fn test_view_batch_keeps_separate_components_independent() {
+        let mut ctx = test_context();
+
+        ctx.begin_view_batch();
+        ctx.add_view_command(ViewCommand::focus("input-a"));
+        ctx.add_view_command(ViewCommand::focus("input-b"));
+        ctx.commit_view_batch();
+
+        assert_eq!(ctx.view_commands.len(), 2);
+        assert_eq!(ctx.view_commands[0].component_id.as_deref(), Some("input-a"));
+        assert_eq!(ctx.view_commands[1].component_id.as_deref(), Some("input-b"));
+    }
+
+    #[test]
+    fn test_abort_view_batch_discards_buffered_commands() {
+        let mut ctx = test_context();
+
+        ctx.begin_view_batch();
+        ctx.add_view_command(ViewCommand::focus("input-a"));
+        ctx.abort_view_batch();
+
+        assert!(ctx.view_commands.is_empty());
+    }
+
+    #[test]
+    fn test_commit_without_batch_is_a_no_op() {
+        let mut ctx = test_context();
+
+        ctx.commit_view_batch();
+
+        assert!(ctx.view_commands.is_empty());
+    }
+
+    #[test]
+    fn test_add_view_command_outside_batch_is_recorded_immediately() {
+        let mut ctx = test_context();
+
+        ctx.add_view_command(ViewCommand::focus("input-a"));
+
+        assert_eq!(ctx.view_commands.len(), 1);
+    }
+
+    #[test]
+    fn test_add_event_forwards_to_sink_and_still_buffers() {
+        let sink = Arc::new(crate::event_sink::BoundedEventSink::new(10));
+        let mut ctx = test_context();
+        ctx.set_event_sink(sink.clone());
+
+        ctx.add_event(EmittedEvent::new("toast", RuntimeValue::Null));
+
+        assert_eq!(ctx.events.len(), 1);
+        assert_eq!(sink.len(), 1);
+    }
+
+    #[test]
+    fn test_add_event_without_sink_only_buffers() {
+        let mut ctx = test_context();
+
+        ctx.add_event(EmittedEvent::new("toast", RuntimeValue::Null));
+
+        assert_eq!(ctx.events.len(), 1);
+    }
+
+    /// Records every call it receives, so tests can assert a sink saw the
+    /// view commands/mutations it was notified of (and in what order),
+    /// unlike `BoundedEventSink` which only tracks events.
+    #[derive(Debug, Default)]
+    struct RecordingSink {
+        view_commands: Mutex<Vec<ViewCommand>>,
+        mutations: Mutex<Vec<StateMutation>>,
+    }
+
+    impl crate::event_sink::EventSink for RecordingSink {
+        fn on_event(&self, _panel_id: &str, _handler_id: &str, _event: &EmittedEvent) {}
+
+        fn on_view_command(&self, _panel_id: &str, _handler_id: &str, command: &ViewCommand) {
+            self.view_commands.lock().push(command.clone());
+        }
+
+        fn on_mutation(&self, _panel_id: &str, _handler_id: &str, mutation: &StateMutation) {
+            self.mutations.lock().push(mutation.clone());
+        }
+    }
+
+    #[test]
+    fn test_add_mutation_forwards_to_sink_and_still_buffers() {
+        let sink = Arc::new(RecordingSink::default());
+        let mut ctx = test_context();
+        ctx.set_event_sink(sink.clone());
+
+        ctx.add_mutation(StateMutation::set("count", RuntimeValue::Number(1.0)));
+
+        assert_eq!(ctx.state_mutations.len(), 1);
+        assert_eq!(sink.mutations.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_add_view_command_outside_batch_forwards_to_sink_immediately() {
+        let sink = Arc::new(RecordingSink::default());
+        let mut ctx = test_context();
+        ctx.set_event_sink(sink.clone());
+
+        ctx.add_view_command(ViewCommand::focus("input-a"));
+
+        assert_eq!(sink.view_commands.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_batched_view_commands_only_forwarded_on_commit() {
+        let sink = Arc::new(RecordingSink::default());
+        let mut ctx = test_context();
+        ctx.set_event_sink(sink.clone());
+
+        ctx.begin_view_batch();
+        ctx.add_view_command(ViewCommand::focus("input-a"));
+        assert!(sink.view_commands.lock().is_empty());
+
+        ctx.commit_view_batch();
+        assert_eq!(sink.view_commands.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_aborted_batch_never_forwarded_to_sink() {
+        let sink = Arc::new(RecordingSink::default());
+        let mut ctx = test_context();
+        ctx.set_event_sink(sink.clone());
+
+        ctx.begin_view_batch();
+        ctx.add_view_command(ViewCommand::focus("input-a"));
+        ctx.abort_view_batch();
+
+        assert!(sink.view_commands.lock().is_empty());
+    }
+
+    #[test]
+    fn test_add_mutation_rejects_once_ceiling_reached() {
+        let mut ctx = test_context();
+        ctx.set_resource_limits(&crate::config::ResourceLimits {
+            max_state_mutations: 1,
+            ..crate::config::ResourceLimits::default()
+        });
+
+        assert!(ctx.add_mutation(StateMutation::compare_and_set("a", None, RuntimeValue::Null)));
+        assert!(!ctx.add_mutation(StateMutation::compare_and_set("b", None, RuntimeValue::Null)));
+        assert_eq!(ctx.state_mutations.len(), 1);
+        assert_eq!(
+            ctx.resource_limit_violation(),
+            Some((ResourceLimitKind::StateMutations, 1, 1))
+        );
+    }
+
+    #[test]
+    fn test_add_event_rejects_once_ceiling_reached() {
+        let mut ctx = test_context();
+        ctx.set_resource_limits(&crate::config::ResourceLimits {
+            max_events: 1,
+            ..crate::config::ResourceLimits::default()
+        });
+
+        assert!(ctx.add_event(EmittedEvent::new("a", RuntimeValue::Null)));
+        assert!(!ctx.add_event(EmittedEvent::new("b", RuntimeValue::Null)));
+        assert_eq!(ctx.events.len(), 1);
+        assert_eq!(
+            ctx.resource_limit_violation(),
+            Some((ResourceLimitKind::Events, 1, 1))
+        );
+    }
+
+    #[test]
+    fn test_mutation_and_event_seq_interleave_in_one_total_order() {
+        let mut ctx = test_context();
+
+        ctx.add_mutation(StateMutation::set("a", RuntimeValue::Number(1.0)));
+        ctx.add_event(EmittedEvent::new("first", RuntimeValue::Null));
+        ctx.add_mutation(StateMutation::set("b", RuntimeValue::Number(2.0)));
+
+        assert_eq!(ctx.state_mutations[0].seq, 0);
+        assert_eq!(ctx.events[0].seq, 1);
+        assert_eq!(ctx.state_mutations[1].seq, 2);
+
+        assert!(ctx.state_mutations[0].timestamp_ms > 0.0);
+        assert!(ctx.events[0].timestamp_ms > 0.0);
+    }
+
+    #[test]
+    fn test_add_event_rejects_without_matching_capability() {
+        let mut ctx = ExecutionContext::from_wasm_context(
+            WasmContext::new("panel-1", "on_click")
+                .with_capabilities(vec![CapabilityToken::EventsEmit("toast".to_string())]),
+        );
+
+        assert!(!ctx.add_event(EmittedEvent::new("spoofed", RuntimeValue::Null)));
+        assert!(ctx.events.is_empty());
+        assert!(ctx.last_capability_error.is_some());
+    }
+
+    #[test]
+    fn test_add_event_accepts_namespaced_glob_capability() {
+        let mut ctx = ExecutionContext::from_wasm_context(
+            WasmContext::new("panel-1", "on_click")
+                .with_capabilities(vec![CapabilityToken::EventsEmit("app.*".to_string())]),
+        );
+
+        assert!(ctx.add_event(EmittedEvent::new("app.login", RuntimeValue::Null)));
+        assert!(!ctx.add_event(EmittedEvent::new("other.login", RuntimeValue::Null)));
+        assert_eq!(ctx.events.len(), 1);
+    }
+
+    #[test]
+    fn test_add_view_command_rejects_without_matching_capability() {
+        let mut ctx = ExecutionContext::from_wasm_context(
+            WasmContext::new("panel-1", "on_click")
+                .with_capabilities(vec![CapabilityToken::ViewUpdate("logs".to_string())]),
+        );
+
+        assert!(!ctx.add_view_command(ViewCommand::focus("other-component")));
+        assert!(ctx.view_commands.is_empty());
+        assert!(ctx.last_capability_error.is_some());
+    }
+
+    #[test]
+    fn test_increment_host_calls_reports_violation_once_over_ceiling() {
+        let mut ctx = test_context();
+        ctx.set_resource_limits(&crate::config::ResourceLimits {
+            max_host_calls: 1,
+            ..crate::config::ResourceLimits::default()
+        });
+
+        ctx.increment_host_calls();
+        assert!(ctx.resource_limit_violation().is_none());
+
+        ctx.increment_host_calls();
+        assert_eq!(
+            ctx.resource_limit_violation(),
+            Some((ResourceLimitKind::HostCalls, 1, 2))
+        );
+    }
+
+    #[test]
+    fn test_resource_limit_violation_reports_first_kind_tripped() {
+        let mut ctx = test_context();
+        ctx.set_resource_limits(&crate::config::ResourceLimits {
+            max_host_calls: 0,
+            max_events: 0,
+            ..crate::config::ResourceLimits::default()
+        });
+
+        ctx.increment_host_calls();
+        ctx.add_event(EmittedEvent::new("a", RuntimeValue::Null));
+
+        // Host calls tripped first; later ceilings crossed in the same
+        // execution don't override which kind gets reported.
+        assert_eq!(
+            ctx.resource_limit_violation().map(|(kind, _, _)| kind),
+            Some(ResourceLimitKind::HostCalls)
+        );
+    }
+
+    #[test]
+    fn test_gas_unlimited_by_default() {
+        let mut ctx = test_context();
+        assert_eq!(ctx.gas_remaining, u64::MAX);
+        assert!(ctx.charge_gas(1_000_000));
+    }
+
+    #[test]
+    fn test_set_gas_remaining_zero_means_unlimited() {
+        let mut ctx = test_context();
+        ctx.set_gas_remaining(0);
+        assert_eq!(ctx.gas_remaining, u64::MAX);
+    }
+
+    #[test]
+    fn test_charge_gas_traps_once_exhausted() {
+        let mut ctx = test_context();
+        ctx.set_gas_remaining(100);
+
+        assert!(ctx.charge_gas(60));
+        assert!(!ctx.charge_gas(60), "should trap once the budget runs out");
+        assert_eq!(ctx.gas_remaining, 0);
+    }
+
+    #[test]
+    fn test_charge_gas_saturates_instead_of_underflowing() {
+        let mut ctx = test_context();
+        ctx.set_gas_remaining(10);
+
+        assert!(!ctx.charge_gas(1000));
+        assert_eq!(ctx.gas_remaining, 0);
+    }
+
+    fn suspension(id: &str) -> SuspensionState {
+        SuspensionState {
+            id: id.to_string(),
+            seq: 0,
+            extension_name: "http".to_string(),
+            method: "get".to_string(),
+            args: vec![],
+            gas_remaining: 100,
+        }
+    }
+
+    #[test]
+    fn test_add_suspension_tracks_multiple_pending_ids() {
+        let mut ctx = test_context();
+        ctx.add_suspension(suspension("a"));
+        ctx.add_suspension(suspension("b"));
+
+        assert!(ctx.has_pending_suspensions());
+        assert_eq!(ctx.suspensions.len(), 2);
+    }
+
+    #[test]
+    fn test_resolve_suspension_removes_only_matching_id() {
+        let mut ctx = test_context();
+        ctx.add_suspension(suspension("a"));
+        ctx.add_suspension(suspension("b"));
+
+        let resolved = ctx.resolve_suspension("a").unwrap();
+        assert_eq!(resolved.id, "a");
+        assert!(ctx.has_pending_suspensions());
+        assert!(ctx.resolve_suspension("a").is_none());
+
+        ctx.resolve_suspension("b");
+        assert!(!ctx.has_pending_suspensions());
+    }
+
+    #[test]
+    fn test_next_seq_increments_monotonically() {
+        let mut ctx = test_context();
+        assert_eq!(ctx.next_seq(), 0);
+        assert_eq!(ctx.next_seq(), 1);
+        assert_eq!(ctx.next_seq(), 2);
+    }
+
+    #[test]
+    fn test_resolve_under_all_waits_for_every_id() {
+        let mut ctx = test_context();
+        ctx.set_join_mode(JoinMode::All);
+        ctx.add_suspension(suspension("a"));
+        ctx.add_suspension(suspension("b"));
+
+        assert_eq!(ctx.resolve("a", AsyncResult::success(RuntimeValue::Null)), Some(false));
+        assert_eq!(ctx.resolve("b", AsyncResult::success(RuntimeValue::Null)), Some(true));
+
+        let results = ctx.take_resolved();
+        assert_eq!(results.len(), 2);
+        assert!(results.contains_key("a"));
+        assert!(results.contains_key("b"));
+    }
+
+    #[test]
+    fn test_resolve_under_any_is_ready_on_first_settle() {
+        let mut ctx = test_context();
+        ctx.set_join_mode(JoinMode::Any);
+        ctx.add_suspension(suspension("a"));
+        ctx.add_suspension(suspension("b"));
+
+        assert_eq!(ctx.resolve("a", AsyncResult::success(RuntimeValue::Null)), Some(true));
+
+        // The still-outstanding sibling is abandoned once the join is ready.
+        let results = ctx.take_resolved();
+        assert_eq!(results.len(), 1);
+        assert!(results.contains_key("a"));
+        assert!(!ctx.has_pending_suspensions());
+    }
+
+    #[test]
+    fn test_resolve_unknown_id_returns_none() {
+        let mut ctx = test_context();
+        assert_eq!(ctx.resolve("missing", AsyncResult::success(RuntimeValue::Null)), None);
+    }
 }
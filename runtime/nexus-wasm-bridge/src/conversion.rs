@@ -0,0 +1,282 @@
+//! Typed coercion of [`RuntimeValue`] into a more specific type.
+//!
+//! `RuntimeValue` only distinguishes the handful of JS-shaped variants
+//! (`Number`, `String`, `Bool`, ...), so a host function pulling a value out
+//! of `state`/`args` has no principled way to turn, say, a stringified
+//! config value into the typed value it actually needs. A [`Conversion`],
+//! parsed from a name via [`FromStr`] and applied via [`Conversion::apply`],
+//! lets a host function declare the type it expects and reject malformed
+//! input with a structured [`WasmError`] instead of silently passing it
+//! through (or falling back to `Null`).
+
+use crate::context::RuntimeValue;
+use crate::error::WasmError;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use std::str::FromStr;
+
+/// A requested coercion of a [`RuntimeValue`], parsed by name via
+/// [`FromStr`] (`"int"`, `"float"`, `"bool"`, `"timestamp"`, or
+/// `"timestamp:<format>"`/`"timestamp_tz:<format>"` for a specific
+/// strftime-style format) and applied via [`Self::apply`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conversion {
+    /// No coercion — [`Self::apply`] returns the value unchanged
+    AsIs,
+    /// Coerce to an integral [`RuntimeValue::Number`], parsing a `String` or
+    /// narrowing a `Number`/`Bool`
+    Integer,
+    /// Coerce to a [`RuntimeValue::Number`], parsing a `String` or narrowing
+    /// a `Bool`
+    Float,
+    /// Coerce to a [`RuntimeValue::Bool`], parsing `"true"`/`"false"`/`"1"`/`"0"`
+    /// (case-insensitive) from a `String` or narrowing a `Number`
+    Boolean,
+    /// Parse a `String` as an RFC 3339 timestamp into an epoch-millis
+    /// [`RuntimeValue::Number`]
+    Timestamp,
+    /// Parse a `String` as a timestamp using a strftime-style `format` (e.g.
+    /// `"%Y-%m-%d"`) with no timezone of its own, interpreted as UTC, into
+    /// an epoch-millis [`RuntimeValue::Number`]
+    TimestampFmt(String),
+    /// Like [`Self::TimestampFmt`], but `format` includes its own timezone
+    /// offset/name (e.g. `"%Y-%m-%d %H:%M:%S %z"`), so the parsed instant
+    /// isn't assumed to be UTC
+    TimestampTzFmt(String),
+}
+
+impl FromStr for Conversion {
+    type Err = WasmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bytes" | "as_is" => return Ok(Conversion::AsIs),
+            "int" | "integer" => return Ok(Conversion::Integer),
+            "float" | "number" => return Ok(Conversion::Float),
+            "bool" | "boolean" => return Ok(Conversion::Boolean),
+            "timestamp" => return Ok(Conversion::Timestamp),
+            _ => {}
+        }
+
+        if let Some(format) = s.strip_prefix("timestamp_tz:") {
+            return Ok(Conversion::TimestampTzFmt(format.to_string()));
+        }
+        if let Some(format) = s.strip_prefix("timestamp:") {
+            return Ok(Conversion::TimestampFmt(format.to_string()));
+        }
+
+        Err(WasmError::invalid_argument(format!(
+            "Unknown conversion '{}'",
+            s
+        )))
+    }
+}
+
+impl Conversion {
+    /// Apply this conversion to `value`, returning a structured
+    /// [`WasmError`] (rather than `Null`) if `value` can't be coerced.
+    pub fn apply(&self, value: &RuntimeValue) -> Result<RuntimeValue, WasmError> {
+        match self {
+            Conversion::AsIs => Ok(value.clone()),
+            Conversion::Integer => Self::coerce_number(value).map(|n| RuntimeValue::Number(n.trunc())),
+            Conversion::Float => Self::coerce_number(value).map(RuntimeValue::Number),
+            Conversion::Boolean => Self::coerce_bool(value).map(RuntimeValue::Bool),
+            Conversion::Timestamp => Self::parse_timestamp(value, None, false),
+            Conversion::TimestampFmt(format) => Self::parse_timestamp(value, Some(format), false),
+            Conversion::TimestampTzFmt(format) => Self::parse_timestamp(value, Some(format), true),
+        }
+    }
+
+    fn coerce_number(value: &RuntimeValue) -> Result<f64, WasmError> {
+        match value {
+            RuntimeValue::Number(n) => Ok(*n),
+            RuntimeValue::Bool(b) => Ok(if *b { 1.0 } else { 0.0 }),
+            RuntimeValue::String(s) => s.trim().parse::<f64>().map_err(|_| {
+                WasmError::invalid_argument(format!("Cannot coerce '{}' to a number", s))
+            }),
+            other => Err(WasmError::invalid_argument(format!(
+                "Cannot coerce {} to a number",
+                value_kind_name(other)
+            ))),
+        }
+    }
+
+    fn coerce_bool(value: &RuntimeValue) -> Result<bool, WasmError> {
+        match value {
+            RuntimeValue::Bool(b) => Ok(*b),
+            RuntimeValue::Number(n) => Ok(*n != 0.0),
+            RuntimeValue::String(s) => match s.to_ascii_lowercase().as_str() {
+                "true" | "1" => Ok(true),
+                "false" | "0" => Ok(false),
+                _ => Err(WasmError::invalid_argument(format!(
+                    "Cannot coerce '{}' to a boolean",
+                    s
+                ))),
+            },
+            other => Err(WasmError::invalid_argument(format!(
+                "Cannot coerce {} to a boolean",
+                value_kind_name(other)
+            ))),
+        }
+    }
+
+    /// Parse a `String` value into epoch-millis: RFC 3339 when `format` is
+    /// `None`, otherwise the given strftime-style `format` — read as
+    /// already carrying an offset when `with_tz` is set, or assumed UTC
+    /// otherwise.
+    fn parse_timestamp(
+        value: &RuntimeValue,
+        format: Option<&str>,
+        with_tz: bool,
+    ) -> Result<RuntimeValue, WasmError> {
+        let RuntimeValue::String(s) = value else {
+            return Err(WasmError::invalid_argument(format!(
+                "Cannot coerce {} to a timestamp: expected a string",
+                value_kind_name(value)
+            )));
+        };
+
+        let millis = match format {
+            None => DateTime::parse_from_rfc3339(s)
+                .map_err(|e| {
+                    WasmError::invalid_argument(format!("Invalid RFC3339 timestamp '{}': {}", s, e))
+                })?
+                .timestamp_millis(),
+            Some(format) if with_tz => DateTime::parse_from_str(s, format)
+                .map_err(|e| {
+                    WasmError::invalid_argument(format!(
+                        "Invalid timestamp '{}' for format '{}': {}",
+                        s, format, e
+                    ))
+                })?
+                .timestamp_millis(),
+            Some(format) => {
+                // `NaiveDateTime` requires hour+minute, so a date-only
+                // format like `%Y-%m-%d` never parses through it directly;
+                // try `NaiveDate` first and default the missing time of day
+                // to UTC midnight, falling back to `NaiveDateTime` only for
+                // formats that do carry time components.
+                let naive = match NaiveDate::parse_from_str(s, format) {
+                    Ok(date) => date.and_hms_opt(0, 0, 0).expect("midnight is always valid"),
+                    Err(_) => NaiveDateTime::parse_from_str(s, format).map_err(|e| {
+                        WasmError::invalid_argument(format!(
+                            "Invalid timestamp '{}' for format '{}': {}",
+                            s, format, e
+                        ))
+                    })?,
+                };
+                naive.and_utc().timestamp_millis()
+            }
+        };
+
+        Ok(RuntimeValue::Number(millis as f64))
+    }
+}
+
+/// Short name for `value`'s variant, used only to build readable
+/// [`WasmError`] messages above
+fn value_kind_name(value: &RuntimeValue) -> &'static str {
+    match value {
+        RuntimeValue::Null => "null",
+        RuntimeValue::Bool(_) => "a boolean",
+        RuntimeValue::Number(_) => "a number",
+        RuntimeValue::String(_) => "a string",
+        RuntimeValue::Bytes(_) => "bytes",
+        RuntimeValue::Array(_) => "an array",
+        RuntimeValue::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_conversion_names() {
+        assert_eq!(Conversion::from_str("int").unwrap(), Conversion::Integer);
+        assert_eq!(Conversion::from_str("integer").unwrap(), Conversion::Integer);
+        assert_eq!(Conversion::from_str("float").unwrap(), Conversion::Float);
+        assert_eq!(Conversion::from_str("bool").unwrap(), Conversion::Boolean);
+        assert_eq!(Conversion::from_str("timestamp").unwrap(), Conversion::Timestamp);
+        assert_eq!(
+            Conversion::from_str("timestamp:%Y-%m-%d").unwrap(),
+            Conversion::TimestampFmt("%Y-%m-%d".to_string())
+        );
+        assert_eq!(
+            Conversion::from_str("timestamp_tz:%Y-%m-%d %z").unwrap(),
+            Conversion::TimestampTzFmt("%Y-%m-%d %z".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unknown_conversion_name_is_rejected() {
+        assert!(Conversion::from_str("not-a-real-conversion").is_err());
+    }
+
+    #[test]
+    fn test_integer_parses_and_truncates() {
+        assert_eq!(
+            Conversion::Integer.apply(&RuntimeValue::String("42.9".into())).unwrap(),
+            RuntimeValue::Number(42.0)
+        );
+        assert_eq!(
+            Conversion::Integer.apply(&RuntimeValue::Number(7.6)).unwrap(),
+            RuntimeValue::Number(7.0)
+        );
+    }
+
+    #[test]
+    fn test_integer_rejects_unparseable_string() {
+        assert!(Conversion::Integer.apply(&RuntimeValue::String("not-a-number".into())).is_err());
+    }
+
+    #[test]
+    fn test_boolean_parses_common_string_forms() {
+        assert_eq!(
+            Conversion::Boolean.apply(&RuntimeValue::String("TRUE".into())).unwrap(),
+            RuntimeValue::Bool(true)
+        );
+        assert_eq!(
+            Conversion::Boolean.apply(&RuntimeValue::String("0".into())).unwrap(),
+            RuntimeValue::Bool(false)
+        );
+    }
+
+    #[test]
+    fn test_boolean_rejects_unrecognized_string() {
+        assert!(Conversion::Boolean.apply(&RuntimeValue::String("maybe".into())).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_parses_rfc3339() {
+        let result = Conversion::Timestamp
+            .apply(&RuntimeValue::String("2024-01-01T00:00:00Z".into()))
+            .unwrap();
+        assert_eq!(result, RuntimeValue::Number(1704067200000.0));
+    }
+
+    #[test]
+    fn test_timestamp_fmt_parses_custom_format_as_utc() {
+        let result = Conversion::TimestampFmt("%Y-%m-%d".to_string())
+            .apply(&RuntimeValue::String("2024-01-01".into()))
+            .unwrap();
+        assert_eq!(result, RuntimeValue::Number(1704067200000.0));
+    }
+
+    #[test]
+    fn test_timestamp_rejects_non_string_value() {
+        assert!(Conversion::Timestamp.apply(&RuntimeValue::Number(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_rejects_malformed_string() {
+        assert!(Conversion::Timestamp
+            .apply(&RuntimeValue::String("not-a-date".into()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_as_is_returns_value_unchanged() {
+        let value = RuntimeValue::String("unchanged".into());
+        assert_eq!(Conversion::AsIs.apply(&value).unwrap(), value);
+    }
+}
@@ -0,0 +1,130 @@
+//! Source-mapped diagnostic rendering for handler errors.
+//!
+//! A raw QuickJS stack trace only carries a byte offset into the generated
+//! (wrapped) source; this module resolves that offset through a
+//! [`SourceMap`](crate::engine::compiler::SourceMap) into a line/column and
+//! a rendered code frame, which [`WasmError::with_source_context`](crate::error::WasmError::with_source_context)
+//! attaches to an error so it shows exactly where handler code failed
+//! instead of stopping at an opaque message.
+
+use crate::engine::compiler::SourceMap;
+use crate::error::{CodeSnippet, SourceLocation};
+
+/// Lines of context rendered above and below the offending line by
+/// [`render_snippet`] unless a caller asks for a different amount.
+pub const DEFAULT_CONTEXT_LINES: usize = 2;
+
+/// Parse the generated-source byte offset out of the top frame of a raw
+/// stack trace. Frames look like `"at <fn> (<offset>)"` (or a bare
+/// `"(<offset>)"`); returns `None` if the first line carries no such marker.
+pub fn parse_top_frame(stack: &str) -> Option<usize> {
+    let first_line = stack.lines().next()?;
+    let start = first_line.rfind('(')? + 1;
+    let end = start + first_line[start..].find(')')?;
+    first_line[start..end].trim().parse().ok()
+}
+
+/// Render `context_lines` of context above/below `line` (1-indexed) from
+/// `source`, trimming whatever leading indentation is common to every
+/// rendered non-blank line so the snippet isn't dominated by wrapper
+/// boilerplate indentation. `highlight_line` in the result is relative to
+/// the first rendered line, also 1-indexed.
+pub fn render_snippet(source: &str, line: usize, context_lines: usize) -> CodeSnippet {
+    let lines: Vec<&str> = source.lines().collect();
+    let start = line.saturating_sub(context_lines + 1);
+    let end = (line + context_lines).min(lines.len());
+    let included = &lines[start..end];
+
+    let indent = included
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let code = included
+        .iter()
+        .map(|l| l.get(indent..).unwrap_or(l))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    CodeSnippet {
+        code,
+        highlight_line: (line - start) as u32,
+    }
+}
+
+/// Resolve a raw stack trace's top frame through `map`, returning the
+/// [`SourceLocation`] plus a [`render_snippet`] of `display_source` around
+/// it. `display_source` is usually the same text `map` was built from, but
+/// callers can pass the original unwrapped handler source instead so the
+/// rendered frame doesn't include runtime wrapper boilerplate. Returns
+/// `None` if the top frame carries no parseable offset.
+pub fn diagnose(stack: &str, map: &SourceMap, display_source: &str) -> Option<(SourceLocation, CodeSnippet)> {
+    let offset = parse_top_frame(stack)?;
+    let (line, column) = map.get_location(offset);
+    let snippet = render_snippet(display_source, line, DEFAULT_CONTEXT_LINES);
+    Some((SourceLocation::new(line as u32, column as u32), snippet))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_top_frame_with_function_name() {
+        let stack = "at handler (42)\nat dispatch (10)";
+        assert_eq!(parse_top_frame(stack), Some(42));
+    }
+
+    #[test]
+    fn test_parse_top_frame_bare_offset() {
+        assert_eq!(parse_top_frame("(7)"), Some(7));
+    }
+
+    #[test]
+    fn test_parse_top_frame_missing_offset() {
+        assert_eq!(parse_top_frame("at handler"), None);
+        assert_eq!(parse_top_frame(""), None);
+    }
+
+    #[test]
+    fn test_render_snippet_trims_common_indentation() {
+        let source = "    function handler() {\n        throw new Error('boom');\n    }";
+        let snippet = render_snippet(source, 2, 1);
+
+        assert!(!snippet.code.lines().next().unwrap().starts_with("    "));
+        assert!(snippet.code.contains("throw new Error"));
+        assert_eq!(snippet.highlight_line, 2);
+    }
+
+    #[test]
+    fn test_render_snippet_clamps_to_source_bounds() {
+        let source = "line1\nline2";
+        let snippet = render_snippet(source, 1, 5);
+
+        assert_eq!(snippet.highlight_line, 1);
+        assert!(snippet.code.contains("line1"));
+        assert!(snippet.code.contains("line2"));
+    }
+
+    #[test]
+    fn test_diagnose_resolves_offset_to_location_and_snippet() {
+        let source = "line1\nline2\nline3";
+        let map = SourceMap::from_source(source);
+
+        let (location, snippet) = diagnose("at handler (6)", &map, source).unwrap();
+
+        assert_eq!(location.line, 2);
+        assert_eq!(location.column, 1);
+        assert!(snippet.code.contains("line2"));
+    }
+
+    #[test]
+    fn test_diagnose_returns_none_without_parseable_frame() {
+        let source = "line1\nline2";
+        let map = SourceMap::from_source(source);
+
+        assert!(diagnose("no offset here", &map, source).is_none());
+    }
+}
@@ -0,0 +1,260 @@
+//! Reproducible benchmarking harness for the compiled-handler cache.
+//!
+//! [`CacheBenchmark`] replays a trace of handler sources through a fresh
+//! [`HandlerCompiler`] built from a caller-supplied [`RuntimeConfig`], so
+//! different `max_cache_size`/[`CachePolicyKind`](crate::config::CachePolicyKind)
+//! choices can be compared against the same trace by running the benchmark
+//! once per candidate config. [`CacheBenchmark::zipfian_trace`] builds a
+//! synthetic trace with a Zipfian popularity skew over a handler set, using a
+//! seeded PRNG so the same seed always reproduces the same trace.
+
+use super::compiler::HandlerCompiler;
+use crate::config::RuntimeConfig;
+use crate::error::Result;
+use std::time::Instant;
+
+/// Replays a trace of handler sources through a [`HandlerCompiler`] and
+/// reports cache effectiveness for the config it was built with.
+pub struct CacheBenchmark {
+    config: RuntimeConfig,
+}
+
+impl CacheBenchmark {
+    /// Create a benchmark that builds its compiler from `config`
+    pub fn new(config: RuntimeConfig) -> Self {
+        Self { config }
+    }
+
+    /// Build a synthetic trace of `length` requests over `handlers`, sampled
+    /// with a Zipfian popularity distribution: `handlers[0]` is requested
+    /// most often, with frequency falling off by rank according to `skew`
+    /// (higher skew concentrates requests on fewer handlers). `seed` fixes
+    /// the PRNG so the same arguments always produce the same trace.
+    pub fn zipfian_trace(handlers: &[String], length: usize, skew: f64, seed: u64) -> Vec<String> {
+        if handlers.is_empty() {
+            return Vec::new();
+        }
+
+        let weights: Vec<f64> = (1..=handlers.len())
+            .map(|rank| 1.0 / (rank as f64).powf(skew))
+            .collect();
+        let total_weight: f64 = weights.iter().sum();
+
+        let mut cumulative = Vec::with_capacity(weights.len());
+        let mut running = 0.0;
+        for weight in &weights {
+            running += weight / total_weight;
+            cumulative.push(running);
+        }
+
+        let mut rng = Xorshift64::new(seed);
+        (0..length)
+            .map(|_| {
+                let sample = rng.next_f64();
+                let rank = cumulative.partition_point(|&c| c < sample).min(handlers.len() - 1);
+                handlers[rank].clone()
+            })
+            .collect()
+    }
+
+    /// Replay `trace` through a fresh compiler and summarize cache behavior.
+    pub fn run(&self, trace: &[String]) -> Result<BenchmarkReport> {
+        let compiler = HandlerCompiler::new(&self.config)?;
+
+        let mut cold_compile_latencies_us = Vec::new();
+        let mut cache_hit_latencies_us = Vec::new();
+        let mut bytes_resident_over_time = Vec::with_capacity(trace.len());
+
+        for source in trace {
+            let start = Instant::now();
+            let result = compiler.compile(source)?;
+            let elapsed_us = start.elapsed().as_micros() as u64;
+
+            if result.cache_hit {
+                cache_hit_latencies_us.push(elapsed_us);
+            } else {
+                cold_compile_latencies_us.push(elapsed_us);
+            }
+
+            bytes_resident_over_time.push(compiler.get_stats().cache_size_bytes);
+        }
+
+        let stats = compiler.get_stats();
+
+        Ok(BenchmarkReport {
+            requests: trace.len(),
+            hit_rate: stats.hit_rate(),
+            evictions: stats.cache_evictions,
+            avg_cold_compile_latency_us: average(&cold_compile_latencies_us),
+            avg_cache_hit_latency_us: average(&cache_hit_latencies_us),
+            steady_state_working_set_bytes: steady_state(&bytes_resident_over_time),
+            bytes_resident_over_time,
+        })
+    }
+}
+
+/// Summary of how a trace behaved against the compiled-handler cache
+#[derive(Debug, Clone)]
+pub struct BenchmarkReport {
+    /// Number of requests replayed
+    pub requests: usize,
+    /// Cache hit rate over the whole trace (0.0 - 1.0)
+    pub hit_rate: f64,
+    /// Number of evictions performed while replaying the trace
+    pub evictions: u64,
+    /// Average latency of a cold (cache-miss) compile, in microseconds
+    pub avg_cold_compile_latency_us: f64,
+    /// Average latency of a cache-hit compile, in microseconds
+    pub avg_cache_hit_latency_us: f64,
+    /// Cache-resident byte count sampled after every request, in trace order
+    pub bytes_resident_over_time: Vec<u64>,
+    /// Cache-resident bytes averaged over the trailing 20% of the trace,
+    /// once the cache has had a chance to reach a steady state
+    pub steady_state_working_set_bytes: u64,
+}
+
+/// Average of `samples`, or `0.0` if empty (e.g. a trace with no cache
+/// misses has no cold-compile latencies to average)
+fn average(samples: &[u64]) -> f64 {
+    if samples.is_empty() {
+        0.0
+    } else {
+        samples.iter().sum::<u64>() as f64 / samples.len() as f64
+    }
+}
+
+/// Average of the trailing 20% of `samples` (at least one sample), used to
+/// estimate the working-set size once the cache has warmed up
+fn steady_state(samples: &[u64]) -> u64 {
+    if samples.is_empty() {
+        return 0;
+    }
+
+    let tail_len = (samples.len() / 5).max(1);
+    let tail = &samples[samples.len() - tail_len..];
+    tail.iter().sum::<u64>() / tail_len as u64
+}
+
+/// Small, fast, deterministic PRNG (xorshift64*) used to build reproducible
+/// synthetic traces. Not suitable for cryptographic use.
+struct Xorshift64 {
+    state: u64,
+}
+
+impl Xorshift64 {
+    fn new(seed: u64) -> Self {
+        // A zero state is a fixed point for xorshift, so nudge it away from
+        // zero to guarantee the sequence actually advances.
+        Self {
+            state: seed.max(1),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform sample in `[0.0, 1.0)`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CachePolicyKind;
+
+    fn handler_set(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("return {i};")).collect()
+    }
+
+    #[test]
+    fn test_zipfian_trace_is_reproducible_for_same_seed() {
+        let handlers = handler_set(10);
+        let trace1 = CacheBenchmark::zipfian_trace(&handlers, 200, 1.0, 42);
+        let trace2 = CacheBenchmark::zipfian_trace(&handlers, 200, 1.0, 42);
+
+        assert_eq!(trace1, trace2);
+    }
+
+    #[test]
+    fn test_zipfian_trace_skews_toward_first_handler() {
+        let handlers = handler_set(10);
+        let trace = CacheBenchmark::zipfian_trace(&handlers, 2000, 1.5, 7);
+
+        let most_popular_count = trace.iter().filter(|s| *s == &handlers[0]).count();
+        let least_popular_count = trace.iter().filter(|s| *s == &handlers[9]).count();
+
+        assert!(most_popular_count > least_popular_count * 4);
+    }
+
+    #[test]
+    fn test_zipfian_trace_empty_handlers_is_empty() {
+        let trace = CacheBenchmark::zipfian_trace(&[], 100, 1.0, 1);
+        assert!(trace.is_empty());
+    }
+
+    #[test]
+    fn test_benchmark_reports_all_but_first_as_hits_for_a_single_repeated_source() {
+        let config = RuntimeConfig::default();
+        let trace: Vec<String> = std::iter::repeat("return 1;".to_string()).take(10).collect();
+
+        let report = CacheBenchmark::new(config).run(&trace).unwrap();
+
+        assert_eq!(report.requests, 10);
+        // 1 miss, 9 hits
+        assert!((report.hit_rate - 0.9).abs() < 0.01);
+        assert_eq!(report.evictions, 0);
+    }
+
+    #[test]
+    fn test_benchmark_reports_evictions_under_a_tight_entry_budget() {
+        let config = RuntimeConfig::default().with_max_cache_entries(2);
+        let handlers = handler_set(5);
+        let trace: Vec<String> = handlers.iter().cloned().collect();
+
+        let report = CacheBenchmark::new(config).run(&trace).unwrap();
+
+        assert!(report.evictions > 0);
+        assert_eq!(report.hit_rate, 0.0); // every source is distinct and seen once
+    }
+
+    #[test]
+    fn test_benchmark_lru_and_lfu_diverge_on_a_skewed_trace() {
+        let handlers = handler_set(4);
+        let trace = CacheBenchmark::zipfian_trace(&handlers, 100, 1.2, 99);
+
+        let lru_config = RuntimeConfig::default()
+            .with_max_cache_entries(2)
+            .with_cache_policy(CachePolicyKind::Lru);
+        let lfu_config = RuntimeConfig::default()
+            .with_max_cache_entries(2)
+            .with_cache_policy(CachePolicyKind::Lfu);
+
+        let lru_report = CacheBenchmark::new(lru_config).run(&trace).unwrap();
+        let lfu_report = CacheBenchmark::new(lfu_config).run(&trace).unwrap();
+
+        // Both policies should benefit from the trace's skew, but there's no
+        // guarantee which ranks higher on any given trace; assert they at
+        // least both observe some hits rather than asserting an ordering.
+        assert!(lru_report.hit_rate > 0.0);
+        assert!(lfu_report.hit_rate > 0.0);
+    }
+
+    #[test]
+    fn test_steady_state_working_set_uses_trailing_window() {
+        let samples = vec![0, 0, 0, 0, 100, 100];
+        assert_eq!(steady_state(&samples), 100);
+    }
+
+    #[test]
+    fn test_steady_state_working_set_empty_is_zero() {
+        assert_eq!(steady_state(&[]), 0);
+    }
+}
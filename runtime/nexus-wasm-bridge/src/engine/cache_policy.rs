@@ -0,0 +1,309 @@
+//! Pluggable eviction policies for the compiled-handler bytecode cache.
+//!
+//! Each policy maintains its own auxiliary ordering structure alongside the
+//! cache map itself, so picking the next eviction victim never requires a
+//! full scan of the cache.
+
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Decides which entry to evict when the compiled-handler cache exceeds its
+/// size or entry-count budget.
+pub trait CachePolicy: Send + Sync {
+    /// Record that `key` was just inserted with the given byte `size`.
+    fn on_insert(&self, key: &str, size: usize);
+
+    /// Record that `key` was just accessed (a cache hit).
+    fn on_access(&self, key: &str);
+
+    /// Record that `key` was removed from the cache (evicted or replaced).
+    fn on_remove(&self, key: &str);
+
+    /// Return the next key to evict, or `None` if nothing is tracked.
+    fn select_victim(&self) -> Option<String>;
+
+    /// Drop all tracked bookkeeping (the cache itself was cleared).
+    fn clear(&self);
+}
+
+/// Build the policy selected by [`crate::config::CachePolicyKind`].
+pub fn build(kind: crate::config::CachePolicyKind) -> Box<dyn CachePolicy> {
+    use crate::config::CachePolicyKind;
+    match kind {
+        CachePolicyKind::Lru => Box::new(Lru::default()),
+        CachePolicyKind::Lfu => Box::new(Lfu::default()),
+        CachePolicyKind::WeightedLfu => Box::new(WeightedLfu::default()),
+    }
+}
+
+/// Evicts the least-recently-used entry. Recency is tracked as a monotonic
+/// touch sequence rather than a wall-clock timestamp, so ordering never
+/// needs to be recomputed against the current time.
+#[derive(Default)]
+pub struct Lru {
+    next_seq: AtomicU64,
+    by_seq: Mutex<BTreeMap<u64, String>>,
+    seq_of: Mutex<HashMap<String, u64>>,
+}
+
+impl Lru {
+    fn touch(&self, key: &str) {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let mut by_seq = self.by_seq.lock();
+        let mut seq_of = self.seq_of.lock();
+        if let Some(old) = seq_of.insert(key.to_string(), seq) {
+            by_seq.remove(&old);
+        }
+        by_seq.insert(seq, key.to_string());
+    }
+}
+
+impl CachePolicy for Lru {
+    fn on_insert(&self, key: &str, _size: usize) {
+        self.touch(key);
+    }
+
+    fn on_access(&self, key: &str) {
+        self.touch(key);
+    }
+
+    fn on_remove(&self, key: &str) {
+        if let Some(seq) = self.seq_of.lock().remove(key) {
+            self.by_seq.lock().remove(&seq);
+        }
+    }
+
+    fn select_victim(&self) -> Option<String> {
+        self.by_seq.lock().iter().next().map(|(_, k)| k.clone())
+    }
+
+    fn clear(&self) {
+        self.by_seq.lock().clear();
+        self.seq_of.lock().clear();
+    }
+}
+
+/// Evicts the least-frequently-used entry, breaking ties between equally
+/// frequent entries by insertion order (oldest inserted first; accesses
+/// don't change an entry's tie-break position).
+#[derive(Default)]
+pub struct Lfu {
+    next_seq: AtomicU64,
+    by_count: Mutex<BTreeMap<(u64, u64), String>>,
+    state_of: Mutex<HashMap<String, (u64, u64)>>,
+}
+
+impl Lfu {
+    fn record(&self, key: &str, count: u64) {
+        let mut by_count = self.by_count.lock();
+        let mut state_of = self.state_of.lock();
+        let seq = state_of
+            .get(key)
+            .map(|&(_, seq)| seq)
+            .unwrap_or_else(|| self.next_seq.fetch_add(1, Ordering::Relaxed));
+        if let Some(old) = state_of.insert(key.to_string(), (count, seq)) {
+            by_count.remove(&old);
+        }
+        by_count.insert((count, seq), key.to_string());
+    }
+}
+
+impl CachePolicy for Lfu {
+    fn on_insert(&self, key: &str, _size: usize) {
+        self.record(key, 1);
+    }
+
+    fn on_access(&self, key: &str) {
+        let count = self
+            .state_of
+            .lock()
+            .get(key)
+            .map(|&(c, _)| c + 1)
+            .unwrap_or(1);
+        self.record(key, count);
+    }
+
+    fn on_remove(&self, key: &str) {
+        if let Some(state) = self.state_of.lock().remove(key) {
+            self.by_count.lock().remove(&state);
+        }
+    }
+
+    fn select_victim(&self) -> Option<String> {
+        self.by_count.lock().iter().next().map(|(_, k)| k.clone())
+    }
+
+    fn clear(&self) {
+        self.by_count.lock().clear();
+        self.state_of.lock().clear();
+    }
+}
+
+/// Evicts by a weighted score blending frequency and entry size: large,
+/// rarely-accessed entries are ranked ahead of small, frequently-accessed
+/// ones.
+///
+/// The textbook formula for this score is
+/// `access_count / (now - last_accessed).as_secs_f64().max(1.0)`, scaled by
+/// entry size. Evaluated at the instant of a touch (insert or access) the
+/// age term is always clamped to its floor of `1.0` (no time has yet
+/// elapsed since the touch), which collapses the live formula to
+/// `access_count / size` at that moment. Rather than continuously
+/// re-evaluating every entry's age against the current time -- which would
+/// put us back to an O(n) scan on every eviction -- this policy recomputes
+/// that collapsed score only when a key is touched, and uses the touch
+/// sequence as a tie-break between entries with an identical score so that,
+/// among equally-ranked entries, the one touched longest ago is evicted
+/// first. An entry that hasn't been touched in a while therefore keeps the
+/// score it had at its last touch rather than one reflecting how cold it
+/// has since become.
+#[derive(Default)]
+pub struct WeightedLfu {
+    next_seq: AtomicU64,
+    by_score: Mutex<BTreeMap<(u64, u64), String>>,
+    state_of: Mutex<HashMap<String, WeightedState>>,
+}
+
+struct WeightedState {
+    order_key: (u64, u64),
+    access_count: u64,
+    size: usize,
+}
+
+impl WeightedLfu {
+    fn record(&self, key: &str, access_count: u64, size: usize) {
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let score = access_count as f64 / (size.max(1) as f64);
+        let order_key = (score.to_bits(), seq);
+
+        let mut by_score = self.by_score.lock();
+        let mut state_of = self.state_of.lock();
+        if let Some(old) = state_of.insert(
+            key.to_string(),
+            WeightedState {
+                order_key,
+                access_count,
+                size,
+            },
+        ) {
+            by_score.remove(&old.order_key);
+        }
+        by_score.insert(order_key, key.to_string());
+    }
+}
+
+impl CachePolicy for WeightedLfu {
+    fn on_insert(&self, key: &str, size: usize) {
+        self.record(key, 1, size);
+    }
+
+    fn on_access(&self, key: &str) {
+        let (count, size) = self
+            .state_of
+            .lock()
+            .get(key)
+            .map(|s| (s.access_count + 1, s.size))
+            .unwrap_or((1, 0));
+        self.record(key, count, size);
+    }
+
+    fn on_remove(&self, key: &str) {
+        if let Some(state) = self.state_of.lock().remove(key) {
+            self.by_score.lock().remove(&state.order_key);
+        }
+    }
+
+    fn select_victim(&self) -> Option<String> {
+        self.by_score.lock().iter().next().map(|(_, k)| k.clone())
+    }
+
+    fn clear(&self) {
+        self.by_score.lock().clear();
+        self.state_of.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_evicts_least_recently_touched() {
+        let policy = Lru::default();
+        policy.on_insert("a", 10);
+        policy.on_insert("b", 10);
+        policy.on_insert("c", 10);
+        policy.on_access("a");
+
+        assert_eq!(policy.select_victim(), Some("b".to_string()));
+    }
+
+    #[test]
+    fn test_lru_forgets_removed_keys() {
+        let policy = Lru::default();
+        policy.on_insert("a", 10);
+        policy.on_remove("a");
+
+        assert_eq!(policy.select_victim(), None);
+    }
+
+    #[test]
+    fn test_lfu_evicts_least_frequently_accessed() {
+        let policy = Lfu::default();
+        policy.on_insert("a", 10);
+        policy.on_insert("b", 10);
+        policy.on_access("a");
+        policy.on_access("a");
+
+        assert_eq!(policy.select_victim(), Some("b".to_string()));
+    }
+
+    #[test]
+    fn test_lfu_breaks_ties_by_insertion_order() {
+        let policy = Lfu::default();
+        policy.on_insert("a", 10);
+        policy.on_insert("b", 10);
+
+        assert_eq!(policy.select_victim(), Some("a".to_string()));
+    }
+
+    #[test]
+    fn test_weighted_lfu_evicts_large_cold_before_small_hot() {
+        let policy = WeightedLfu::default();
+        policy.on_insert("large_cold", 1_000_000);
+        policy.on_insert("small_hot", 100);
+        for _ in 0..10 {
+            policy.on_access("small_hot");
+        }
+
+        assert_eq!(policy.select_victim(), Some("large_cold".to_string()));
+    }
+
+    #[test]
+    fn test_weighted_lfu_clear_removes_all_tracked_entries() {
+        let policy = WeightedLfu::default();
+        policy.on_insert("a", 10);
+        policy.on_insert("b", 10);
+        policy.clear();
+
+        assert_eq!(policy.select_victim(), None);
+    }
+
+    #[test]
+    fn test_build_selects_policy_from_kind() {
+        use crate::config::CachePolicyKind;
+
+        let lru = build(CachePolicyKind::Lru);
+        lru.on_insert("a", 10);
+        assert_eq!(lru.select_victim(), Some("a".to_string()));
+
+        let lfu = build(CachePolicyKind::Lfu);
+        lfu.on_insert("a", 10);
+        assert_eq!(lfu.select_victim(), Some("a".to_string()));
+
+        let weighted = build(CachePolicyKind::WeightedLfu);
+        weighted.on_insert("a", 10);
+        assert_eq!(weighted.select_victim(), Some("a".to_string()));
+    }
+}
@@ -0,0 +1,192 @@
+//! Cooperative cancellation handles for in-flight executions.
+//!
+//! The only abort mechanism [`WasmRuntime::execute_handler`](super::WasmRuntime::execute_handler)
+//! has today is `ResourceLimits::timeout_ms` — fine for a runaway handler,
+//! useless for a kernel that wants to tear down a panel whose handler is
+//! stuck in a long host-call chain well before that deadline. This mirrors
+//! the Deno runtime's waker/interrupt pattern: a [`CancellationFlag`] is a
+//! cooperative signal raced via `tokio::select!` against the execution
+//! future at its real suspension points (`InstancePool::acquire`'s wait for
+//! a free instance, and the execution future itself), rather than anything
+//! that reaches into `WasmInstance` and stops it mid-instruction — this
+//! engine's `execute_sync` has no internal checkpoint to interrupt (see its
+//! doc comment), so "cancel" here means "stop waiting on it and report
+//! cancelled", not "halt the WASM instance instantly".
+//!
+//! A [`CancellationRegistry`] owns the flags, keyed by an opaque execution
+//! id minted by [`CancellationRegistry::create_handle`] (exposed to Node as
+//! `NexusRuntime::create_abort_handle`) and consumed by
+//! [`CancellationRegistry::cancel`] (`NexusRuntime::cancel`).
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+/// A single-fire cooperative cancellation signal. Cheap to poll
+/// ([`Self::is_cancelled`]) and cheap to wait on ([`Self::cancelled`]) from
+/// inside a `tokio::select!` branch.
+#[derive(Default)]
+pub struct CancellationFlag {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationFlag {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether [`Self::cancel`] has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Fire the flag, waking anyone currently awaiting [`Self::cancelled`].
+    /// Idempotent: cancelling twice is a no-op the second time.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    /// Resolve once [`Self::cancel`] has been (or is) called. Registers for
+    /// the next notification before checking the flag, so a `cancel()` that
+    /// races in between the check and the await can't be missed.
+    pub async fn cancelled(&self) {
+        let notified = self.notify.notified();
+        tokio::pin!(notified);
+        if !self.is_cancelled() {
+            notified.await;
+        }
+    }
+}
+
+/// Owns the [`CancellationFlag`] for every execution id currently in flight,
+/// minted by [`Self::create_handle`] and raced against by
+/// [`super::WasmRuntime::execute_handler`].
+#[derive(Default)]
+pub struct CancellationRegistry {
+    handles: Mutex<HashMap<String, Arc<CancellationFlag>>>,
+}
+
+impl CancellationRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mint a fresh execution id with a not-yet-cancelled flag, for a caller
+    /// to pass into a later `execute_handler` call and hand to
+    /// [`Self::cancel`] if it needs to abort that execution early.
+    pub fn create_handle(&self) -> String {
+        let id = Uuid::new_v4().to_string();
+        self.handles
+            .lock()
+            .insert(id.clone(), Arc::new(CancellationFlag::new()));
+        id
+    }
+
+    /// Signal cancellation for `execution_id`. Returns `false` if no flag is
+    /// registered under that id — already finished (and cleaned up via
+    /// [`Self::remove`]), or never created.
+    pub fn cancel(&self, execution_id: &str) -> bool {
+        match self.handles.lock().get(execution_id) {
+            Some(flag) => {
+                flag.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Look up the flag for `execution_id`, registering a fresh one if this
+    /// is the first time it's been seen — lets a caller pass its own
+    /// application-level id straight into `execute_handler` without a
+    /// separate `create_handle` round-trip.
+    pub fn handle_for(&self, execution_id: &str) -> Arc<CancellationFlag> {
+        Arc::clone(
+            self.handles
+                .lock()
+                .entry(execution_id.to_string())
+                .or_insert_with(|| Arc::new(CancellationFlag::new())),
+        )
+    }
+
+    /// Drop the flag for `execution_id` once its execution has finished, so
+    /// the registry doesn't grow unbounded across many short-lived
+    /// executions.
+    pub fn remove(&self, execution_id: &str) {
+        self.handles.lock().remove(execution_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancelled_resolves_immediately_once_already_cancelled() {
+        let flag = CancellationFlag::new();
+        flag.cancel();
+
+        tokio::time::timeout(std::time::Duration::from_millis(50), flag.cancelled())
+            .await
+            .expect("cancelled() should resolve without waiting");
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waits_until_cancel_is_called() {
+        let flag = Arc::new(CancellationFlag::new());
+
+        let waiter = {
+            let flag = Arc::clone(&flag);
+            tokio::spawn(async move { flag.cancelled().await })
+        };
+
+        // Give the waiter a chance to register before firing the flag.
+        tokio::task::yield_now().await;
+        flag.cancel();
+
+        tokio::time::timeout(std::time::Duration::from_millis(50), waiter)
+            .await
+            .expect("cancelled() should resolve after cancel()")
+            .expect("waiter task should not panic");
+    }
+
+    #[test]
+    fn test_cancel_unknown_id_returns_false() {
+        let registry = CancellationRegistry::new();
+        assert!(!registry.cancel("does-not-exist"));
+    }
+
+    #[test]
+    fn test_create_handle_then_cancel_marks_the_flag() {
+        let registry = CancellationRegistry::new();
+        let id = registry.create_handle();
+
+        assert!(!registry.handle_for(&id).is_cancelled());
+        assert!(registry.cancel(&id));
+        assert!(registry.handle_for(&id).is_cancelled());
+    }
+
+    #[test]
+    fn test_handle_for_unknown_id_registers_a_fresh_flag() {
+        let registry = CancellationRegistry::new();
+        let flag = registry.handle_for("ad-hoc-id");
+        assert!(!flag.is_cancelled());
+        assert!(registry.cancel("ad-hoc-id"));
+    }
+
+    #[test]
+    fn test_remove_drops_the_flag() {
+        let registry = CancellationRegistry::new();
+        let id = registry.create_handle();
+        registry.remove(&id);
+
+        // cancel() on a removed id finds no flag in the map and reports
+        // false; unlike `handle_for`, it never registers a fresh one.
+        assert!(!registry.cancel(&id));
+    }
+}
@@ -3,15 +3,20 @@
 //! This module handles compiling JavaScript handler code to QuickJS bytecode
 //! and caching the results for performance.
 
+use super::cache_policy::{self, CachePolicy};
+use super::chunk_store::ChunkStore;
 use crate::config::RuntimeConfig;
 use crate::error::{Result, RuntimeError};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::{Notify, Semaphore};
 use tracing::{debug, info, warn};
 
 /// Compiled handler result
@@ -23,10 +28,13 @@ pub struct CompiledHandler {
     pub source_map: Option<SourceMap>,
     /// Whether this was a cache hit
     pub cache_hit: bool,
+    /// Shared post-init memory image for this handler, present only when
+    /// `RuntimeConfig::shared_init_image` is enabled
+    pub init_image: Option<SharedInitImage>,
 }
 
 /// Source map for error location mapping
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SourceMap {
     /// Original source code
     pub source: String,
@@ -82,6 +90,87 @@ impl SourceMap {
     }
 }
 
+/// Number of distinct `__nexus_*` host functions every wrapped handler
+/// imports (see [`wrap_handler_source`]); fixed regardless of handler body,
+/// since every handler shares the same `$state`/`$emit`/`$view`/`$ext`/`$log`
+/// globals.
+const WRAPPER_IMPORTED_FUNCTION_COUNT: u32 = 14;
+
+/// On-disk cache format version. Bump this whenever [`DiskCachePayload`]'s
+/// shape changes so caches written by an older binary are detected and
+/// discarded (triggering a recompile) instead of being misread.
+const DISK_CACHE_FORMAT_VERSION: u32 = 1;
+
+/// Where a disk-cache entry's bytecode actually lives.
+#[derive(Serialize, Deserialize)]
+enum BytecodeStorage {
+    /// The bytecode is stored inline in the payload.
+    Inline(Vec<u8>),
+    /// The bytecode is split across content-defined chunks in the shared
+    /// [`ChunkStore`], named here in order.
+    Chunks(Vec<String>),
+}
+
+/// The bytecode and source map persisted for one disk-cache entry, encoded
+/// with `rmp_serde` and wrapped in a [`DiskCacheEnvelope`] before being
+/// written to disk.
+#[derive(Serialize, Deserialize)]
+struct DiskCachePayload {
+    bytecode: BytecodeStorage,
+    source_map: Option<SourceMap>,
+}
+
+/// On-disk container for a cache entry: a format version, the encoded
+/// [`DiskCachePayload`], and a SHA-256 of that payload so truncated or
+/// otherwise corrupt files are detected on read rather than handed back as
+/// bytecode.
+#[derive(Serialize, Deserialize)]
+struct DiskCacheEnvelope {
+    format_version: u32,
+    payload: Vec<u8>,
+    sha256: Vec<u8>,
+}
+
+/// Simulated size of the linear memory captured by a [`SharedInitImage`],
+/// standing in for whatever a real QuickJS wrapper init leaves behind (see
+/// `HandlerCompilerInner::build_init_image`)
+const INIT_IMAGE_BYTES: u64 = 1024 * 1024;
+
+/// A handler's post-init linear memory, captured once per compiled
+/// bytecode and reused so every instance that executes the handler clones
+/// its starting memory from this image instead of repeating
+/// initialization; see `RuntimeConfig::shared_init_image`.
+///
+/// Cheaply cloneable: clones share the same underlying bytes via `Arc`.
+/// [`crate::engine::instance::WasmInstance::clone_from_image`] is what
+/// turns a clone into an independent writable copy.
+#[derive(Clone)]
+pub struct SharedInitImage {
+    memory: Arc<Vec<u8>>,
+    memory_used: u64,
+}
+
+impl SharedInitImage {
+    /// How much memory the captured image holds
+    pub fn memory_used(&self) -> u64 {
+        self.memory_used
+    }
+
+    /// The captured memory bytes, shared via `Arc`
+    pub fn memory(&self) -> &Arc<Vec<u8>> {
+        &self.memory
+    }
+}
+
+/// The memory bytes persisted for one on-disk init-image entry, encoded
+/// with `rmp_serde` and wrapped in a [`DiskCacheEnvelope`] before being
+/// written to disk, the same framing used for bytecode disk-cache entries.
+#[derive(Serialize, Deserialize)]
+struct InitImagePayload {
+    memory_used: u64,
+    memory: Vec<u8>,
+}
+
 /// Cache entry with metadata
 struct CacheEntry {
     /// Compiled bytecode
@@ -114,8 +203,28 @@ impl CacheEntry {
     }
 }
 
+/// A compilation shared by every caller currently requesting the same
+/// cache key, so that concurrent [`HandlerCompiler::compile_async`] calls
+/// for an uncached source compile exactly once.
+struct InFlightSlot {
+    /// Set once the leader's compilation finishes (`Ok`) or fails (`Err`,
+    /// carrying a message since [`RuntimeError`] isn't `Clone`)
+    result: Mutex<Option<std::result::Result<(Vec<u8>, SourceMap), String>>>,
+    /// Wakes every follower blocked in `compile_async` once `result` is set
+    notify: Notify,
+}
+
+impl InFlightSlot {
+    fn new() -> Self {
+        Self {
+            result: Mutex::new(None),
+            notify: Notify::new(),
+        }
+    }
+}
+
 /// Handler compiler with caching
-pub struct HandlerCompiler {
+struct HandlerCompilerInner {
     /// Configuration
     config: RuntimeConfig,
     /// In-memory cache
@@ -132,11 +241,35 @@ pub struct HandlerCompiler {
     max_cache_size: usize,
     /// Current cache size in bytes
     cache_size: AtomicU64,
+    /// Maximum number of entries (0 = unlimited)
+    max_cache_entries: usize,
+    /// Number of evictions performed
+    cache_evictions: AtomicU64,
+    /// Eviction policy deciding which entry to evict when the cache is full
+    policy: Box<dyn CachePolicy>,
+    /// Content-addressed chunk store backing disk-cache deduplication, if
+    /// `config.enable_disk_cache_dedup` is set
+    chunk_store: Option<ChunkStore>,
+    /// Sum of bytecode lengths ever written through the chunked path, i.e.
+    /// how many bytes disk-cache entries would occupy without dedup
+    disk_cache_logical_bytes: AtomicU64,
+    /// Bounds how many `compile_async`/`warm` compilations run at once
+    compile_semaphore: Arc<Semaphore>,
+    /// Compilations currently executing on the worker pool
+    active_compilations: AtomicU64,
+    /// Compilations waiting for a free worker-pool slot
+    queued_compilations: AtomicU64,
+    /// In-flight compilations by cache key, so concurrent `compile_async`
+    /// callers for the same uncached source share one compilation
+    in_flight: Mutex<HashMap<String, Arc<InFlightSlot>>>,
+    /// Shared post-init memory images by bytecode cache key, built lazily
+    /// when `config.shared_init_image` is set
+    init_images: RwLock<HashMap<String, SharedInitImage>>,
 }
 
-impl HandlerCompiler {
+impl HandlerCompilerInner {
     /// Create a new compiler
-    pub fn new(config: &RuntimeConfig) -> Result<Self> {
+    fn new(config: &RuntimeConfig) -> Result<Self> {
         let disk_cache_dir = config
             .cache_dir
             .as_ref()
@@ -148,8 +281,17 @@ impl HandlerCompiler {
                 path
             });
 
+        let chunk_store = if config.enable_disk_cache_dedup {
+            disk_cache_dir
+                .as_ref()
+                .and_then(|dir| ChunkStore::new(dir).ok())
+        } else {
+            None
+        };
+
         info!(
             disk_cache = disk_cache_dir.is_some(),
+            disk_cache_dedup = chunk_store.is_some(),
             "Initialized handler compiler"
         );
 
@@ -162,11 +304,169 @@ impl HandlerCompiler {
             total_compilations: AtomicU64::new(0),
             max_cache_size: config.max_cache_size_bytes.unwrap_or(64 * 1024 * 1024), // 64MB default
             cache_size: AtomicU64::new(0),
+            max_cache_entries: config.max_cache_entries,
+            cache_evictions: AtomicU64::new(0),
+            policy: cache_policy::build(config.cache_policy),
+            chunk_store,
+            disk_cache_logical_bytes: AtomicU64::new(0),
+            compile_semaphore: Arc::new(Semaphore::new(config.max_concurrent_compilations.max(1))),
+            active_compilations: AtomicU64::new(0),
+            queued_compilations: AtomicU64::new(0),
+            in_flight: Mutex::new(HashMap::new()),
+            init_images: RwLock::new(HashMap::new()),
         })
     }
 
+    /// Get the shared init image for `cache_key`, building and persisting
+    /// it if this is the first handler compiled with that key since
+    /// startup. Only called when `config.shared_init_image` is set.
+    fn init_image_for(&self, cache_key: &str) -> SharedInitImage {
+        if let Some(image) = self.init_images.read().get(cache_key).cloned() {
+            return image;
+        }
+
+        if let Some(image) = self.load_init_image_from_disk(cache_key) {
+            self.init_images
+                .write()
+                .insert(cache_key.to_string(), image.clone());
+            return image;
+        }
+
+        let image = self.build_init_image();
+        self.put_init_image_to_disk(cache_key, &image);
+        self.init_images
+            .write()
+            .insert(cache_key.to_string(), image.clone());
+        image
+    }
+
+    /// Simulate running a compiled handler's QuickJS wrapper init once and
+    /// capturing the resulting linear memory. In a real implementation this
+    /// would instantiate the module, execute its start function, and
+    /// snapshot the memory it left behind; here we stand that in with a
+    /// fixed-size zeroed image, the same placeholder size `execute_sync`
+    /// uses for simulated memory usage.
+    fn build_init_image(&self) -> SharedInitImage {
+        SharedInitImage {
+            memory: Arc::new(vec![0u8; INIT_IMAGE_BYTES as usize]),
+            memory_used: INIT_IMAGE_BYTES,
+        }
+    }
+
+    /// Get an init image from disk. Returns `None` on any read, decode,
+    /// version, or integrity failure, in which case the caller rebuilds it.
+    fn load_init_image_from_disk(&self, cache_key: &str) -> Option<SharedInitImage> {
+        let cache_dir = self.disk_cache_dir.as_ref()?;
+        let path = cache_dir.join(format!("{}.img", cache_key));
+
+        let bytes = std::fs::read(&path).ok()?;
+        let envelope: DiskCacheEnvelope = rmp_serde::from_slice(&bytes).ok()?;
+
+        if envelope.format_version != DISK_CACHE_FORMAT_VERSION {
+            debug!(path = %path.display(), "Discarding init image from a different format version");
+            return None;
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(&envelope.payload);
+        if hasher.finalize().as_slice() != envelope.sha256.as_slice() {
+            warn!(path = %path.display(), "Discarding init image with a mismatched checksum");
+            return None;
+        }
+
+        let payload: InitImagePayload = rmp_serde::from_slice(&envelope.payload).ok()?;
+        debug!(path = %path.display(), "Read shared init image from disk cache");
+        Some(SharedInitImage {
+            memory: Arc::new(payload.memory),
+            memory_used: payload.memory_used,
+        })
+    }
+
+    /// Put an init image to disk, wrapped in the same versioned, checksummed
+    /// envelope used for bytecode disk-cache entries.
+    fn put_init_image_to_disk(&self, cache_key: &str, image: &SharedInitImage) {
+        let Some(cache_dir) = &self.disk_cache_dir else {
+            return;
+        };
+        let path = cache_dir.join(format!("{}.img", cache_key));
+
+        let payload = match rmp_serde::to_vec(&InitImagePayload {
+            memory_used: image.memory_used,
+            memory: (*image.memory).clone(),
+        }) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!(error = %e, "Failed to encode init image payload");
+                return;
+            }
+        };
+
+        let mut hasher = Sha256::new();
+        hasher.update(&payload);
+        let sha256 = hasher.finalize().to_vec();
+
+        let envelope = DiskCacheEnvelope {
+            format_version: DISK_CACHE_FORMAT_VERSION,
+            payload,
+            sha256,
+        };
+
+        match rmp_serde::to_vec(&envelope) {
+            Ok(bytes) => {
+                if let Err(e) = std::fs::write(&path, bytes) {
+                    warn!(path = %path.display(), error = %e, "Failed to write init image");
+                } else {
+                    debug!(path = %path.display(), "Wrote shared init image to disk cache");
+                }
+            }
+            Err(e) => warn!(error = %e, "Failed to encode init image envelope"),
+        }
+    }
+
+    /// Reject `source` if, once wrapped, it would need more host imports or
+    /// defined functions than `config.module_limits` allows. The table,
+    /// memory and memory-page limits are not checked per handler here.
+    /// Only enforced in [`crate::config::PoolingStrategy::Pooling`]
+    /// mode, where the pool preallocates fixed-shape slots up front and so
+    /// cannot accommodate a module that doesn't fit them; `OnDemand` mode
+    /// instantiates each module freely and isn't bounded this way.
+    fn check_module_limits(&self, source: &str) -> Result<()> {
+        if self.config.pooling_strategy != crate::config::PoolingStrategy::Pooling {
+            return Ok(());
+        }
+
+        let limits = &self.config.module_limits;
+
+        if WRAPPER_IMPORTED_FUNCTION_COUNT > limits.max_imported_functions {
+            return Err(crate::config::ConfigError::InvalidValue {
+                field: "module_limits.max_imported_functions".into(),
+                reason: format!(
+                    "handler requires {WRAPPER_IMPORTED_FUNCTION_COUNT} host imports, which exceeds the configured limit of {}",
+                    limits.max_imported_functions
+                ),
+            }
+            .into());
+        }
+
+        let defined_functions = estimate_defined_functions(source);
+        if defined_functions > limits.max_defined_functions {
+            return Err(crate::config::ConfigError::InvalidValue {
+                field: "module_limits.max_defined_functions".into(),
+                reason: format!(
+                    "handler defines an estimated {defined_functions} functions, which exceeds the configured limit of {}",
+                    limits.max_defined_functions
+                ),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
     /// Compile handler code to bytecode
-    pub fn compile(&self, source: &str) -> Result<CompiledHandler> {
+    fn compile(&self, source: &str) -> Result<CompiledHandler> {
+        self.check_module_limits(source)?;
+
         let cache_key = self.compute_cache_key(source);
 
         // Try memory cache first
@@ -177,6 +477,7 @@ impl HandlerCompiler {
                 bytecode: entry.bytecode.clone(),
                 source_map: entry.source_map.clone(),
                 cache_hit: true,
+                init_image: self.init_image_if_enabled(&cache_key),
             });
         }
 
@@ -184,14 +485,15 @@ impl HandlerCompiler {
         if let Some(entry) = self.get_from_disk_cache(&cache_key) {
             self.cache_hits.fetch_add(1, Ordering::Relaxed);
             debug!(key = %cache_key, "Disk cache hit");
-            
+
             // Promote to memory cache
             self.put_to_cache(&cache_key, entry.bytecode.clone(), entry.source_map.clone());
-            
+
             return Ok(CompiledHandler {
                 bytecode: entry.bytecode,
                 source_map: entry.source_map,
                 cache_hit: true,
+                init_image: self.init_image_if_enabled(&cache_key),
             });
         }
 
@@ -204,15 +506,25 @@ impl HandlerCompiler {
 
         // Store in caches
         self.put_to_cache(&cache_key, bytecode.clone(), Some(source_map.clone()));
-        self.put_to_disk_cache(&cache_key, &bytecode);
+        self.put_to_disk_cache(&cache_key, &bytecode, Some(&source_map));
 
         Ok(CompiledHandler {
             bytecode,
             source_map: Some(source_map),
             cache_hit: false,
+            init_image: self.init_image_if_enabled(&cache_key),
         })
     }
 
+    /// `init_image_for`, but only when `config.shared_init_image` is set;
+    /// the common guard shared by every [`CompiledHandler`] construction
+    /// site so callers don't pay for image building when the feature is off.
+    fn init_image_if_enabled(&self, cache_key: &str) -> Option<SharedInitImage> {
+        self.config
+            .shared_init_image
+            .then(|| self.init_image_for(cache_key))
+    }
+
     /// Compute cache key from source
     fn compute_cache_key(&self, source: &str) -> String {
         let mut hasher = Sha256::new();
@@ -232,7 +544,8 @@ impl HandlerCompiler {
         if let Some(entry) = cache.get_mut(key) {
             entry.last_accessed = Instant::now();
             entry.access_count += 1;
-            
+            self.policy.on_access(key);
+
             return Some(CacheEntry {
                 bytecode: entry.bytecode.clone(),
                 source_map: entry.source_map.clone(),
@@ -251,96 +564,192 @@ impl HandlerCompiler {
         let entry = CacheEntry::new(bytecode, source_map);
         let entry_size = entry.size as u64;
 
-        // Evict if necessary
+        // Evict if necessary to stay within the byte budget
         while self.cache_size.load(Ordering::Relaxed) + entry_size > self.max_cache_size as u64 {
-            if !self.evict_lru() {
+            if !self.evict_one() {
                 break;
             }
         }
 
+        // Evict if necessary to stay within the entry-count budget
+        if self.max_cache_entries > 0 {
+            while self.cache.read().len() >= self.max_cache_entries {
+                if !self.evict_one() {
+                    break;
+                }
+            }
+        }
+
         let mut cache = self.cache.write();
         if let Some(old) = cache.insert(key.to_string(), entry) {
             self.cache_size.fetch_sub(old.size as u64, Ordering::Relaxed);
         }
         self.cache_size.fetch_add(entry_size, Ordering::Relaxed);
+        self.policy.on_insert(key, entry_size as usize);
     }
 
-    /// Evict least recently used entry
-    fn evict_lru(&self) -> bool {
-        let mut cache = self.cache.write();
-        
-        if cache.is_empty() {
+    /// Evict the entry chosen by the configured [`CachePolicy`]
+    fn evict_one(&self) -> bool {
+        let Some(key) = self.policy.select_victim() else {
             return false;
-        }
+        };
 
-        // Find LRU entry
-        let lru_key = cache
-            .iter()
-            .min_by_key(|(_, e)| e.last_accessed)
-            .map(|(k, _)| k.clone());
-
-        if let Some(key) = lru_key {
-            if let Some(entry) = cache.remove(&key) {
-                self.cache_size.fetch_sub(entry.size as u64, Ordering::Relaxed);
-                debug!(key = %key, "Evicted LRU cache entry");
-                return true;
-            }
+        let mut cache = self.cache.write();
+        if let Some(entry) = cache.remove(&key) {
+            drop(cache);
+            self.cache_size.fetch_sub(entry.size as u64, Ordering::Relaxed);
+            self.cache_evictions.fetch_add(1, Ordering::Relaxed);
+            self.policy.on_remove(&key);
+            debug!(key = %key, "Evicted cache entry");
+            true
+        } else {
+            // The policy's bookkeeping drifted from the cache map (e.g. a
+            // key it tracked was never actually inserted); drop it and let
+            // the caller retry with the next victim.
+            drop(cache);
+            self.policy.on_remove(&key);
+            false
         }
-
-        false
     }
 
-    /// Get from disk cache
+    /// Get from disk cache. Returns `None` on any read, decode, version, or
+    /// integrity failure, in which case the caller recompiles from source
+    /// rather than risk handing back corrupt bytecode.
     fn get_from_disk_cache(&self, key: &str) -> Option<CacheEntry> {
         let cache_dir = self.disk_cache_dir.as_ref()?;
         let path = cache_dir.join(format!("{}.qjsc", key));
 
-        match std::fs::read(&path) {
-            Ok(bytecode) => {
-                debug!(path = %path.display(), "Read from disk cache");
-                Some(CacheEntry::new(bytecode, None))
-            }
-            Err(_) => None,
+        let bytes = std::fs::read(&path).ok()?;
+        let envelope: DiskCacheEnvelope = rmp_serde::from_slice(&bytes).ok()?;
+
+        if envelope.format_version != DISK_CACHE_FORMAT_VERSION {
+            debug!(path = %path.display(), "Discarding disk cache entry from a different format version");
+            return None;
         }
+
+        let mut hasher = Sha256::new();
+        hasher.update(&envelope.payload);
+        if hasher.finalize().as_slice() != envelope.sha256.as_slice() {
+            warn!(path = %path.display(), "Discarding disk cache entry with a mismatched checksum");
+            return None;
+        }
+
+        let payload: DiskCachePayload = rmp_serde::from_slice(&envelope.payload).ok()?;
+        let bytecode = match payload.bytecode {
+            BytecodeStorage::Inline(bytecode) => bytecode,
+            BytecodeStorage::Chunks(hashes) => self.chunk_store.as_ref()?.get(&hashes).ok()?,
+        };
+        debug!(path = %path.display(), "Read from disk cache");
+        Some(CacheEntry::new(bytecode, payload.source_map))
     }
 
-    /// Put to disk cache
-    fn put_to_disk_cache(&self, key: &str, bytecode: &[u8]) {
+    /// Put to disk cache, wrapped in a versioned, checksummed envelope. When
+    /// `config.enable_disk_cache_dedup` is on, the bytecode is split into
+    /// content-defined chunks and stored in the shared [`ChunkStore`]
+    /// instead of inline.
+    fn put_to_disk_cache(&self, key: &str, bytecode: &[u8], source_map: Option<&SourceMap>) {
         if let Some(cache_dir) = &self.disk_cache_dir {
             let path = cache_dir.join(format!("{}.qjsc", key));
-            
-            if let Err(e) = std::fs::write(&path, bytecode) {
-                warn!(path = %path.display(), error = %e, "Failed to write disk cache");
+
+            let storage = if let Some(chunk_store) = &self.chunk_store {
+                match chunk_store.put(bytecode) {
+                    Ok(hashes) => {
+                        self.disk_cache_logical_bytes
+                            .fetch_add(bytecode.len() as u64, Ordering::Relaxed);
+                        BytecodeStorage::Chunks(hashes)
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "Failed to write bytecode chunks, falling back to inline storage");
+                        BytecodeStorage::Inline(bytecode.to_vec())
+                    }
+                }
             } else {
-                debug!(path = %path.display(), "Wrote to disk cache");
+                BytecodeStorage::Inline(bytecode.to_vec())
+            };
+
+            let payload = match rmp_serde::to_vec(&DiskCachePayload {
+                bytecode: storage,
+                source_map: source_map.cloned(),
+            }) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!(error = %e, "Failed to encode disk cache payload");
+                    return;
+                }
+            };
+
+            let mut hasher = Sha256::new();
+            hasher.update(&payload);
+            let sha256 = hasher.finalize().to_vec();
+
+            let envelope = DiskCacheEnvelope {
+                format_version: DISK_CACHE_FORMAT_VERSION,
+                payload,
+                sha256,
+            };
+
+            match rmp_serde::to_vec(&envelope) {
+                Ok(bytes) => {
+                    if let Err(e) = std::fs::write(&path, bytes) {
+                        warn!(path = %path.display(), error = %e, "Failed to write disk cache");
+                    } else {
+                        debug!(path = %path.display(), "Wrote to disk cache");
+                    }
+                }
+                Err(e) => warn!(error = %e, "Failed to encode disk cache envelope"),
             }
         }
     }
 
     /// Compile source to bytecode
     fn compile_source(&self, source: &str) -> Result<(Vec<u8>, SourceMap)> {
-        // Wrap source in handler function
-        let wrapped = self.wrap_handler(source);
-        let source_map = SourceMap::from_source(&wrapped);
-
-        // In a real implementation, this would:
-        // 1. Load QuickJS WASM module
-        // 2. Call JS_Compile to compile to bytecode
-        // 3. Return the bytecode
-        //
-        // For now, we simulate compilation by storing the source as "bytecode"
-        // This is a placeholder that will be replaced with actual QuickJS compilation
-        
-        let bytecode = wrapped.as_bytes().to_vec();
-
-        Ok((bytecode, source_map))
+        Ok(compile_bytecode(source))
     }
 
     /// Wrap handler source in runtime wrapper
     fn wrap_handler(&self, source: &str) -> String {
-        // The wrapper provides the handler interface
-        format!(
-            r#"(function(__nexus_state, __nexus_args, __nexus_scope) {{
+        wrap_handler_source(source)
+    }
+}
+
+/// Synchronous, state-free compilation step shared by both [`HandlerCompiler::compile`]
+/// and the `compile_async`/`warm` worker-pool path, which runs it inside
+/// `tokio::task::spawn_blocking` and so cannot borrow `&self` across the
+/// blocking call.
+fn compile_bytecode(source: &str) -> (Vec<u8>, SourceMap) {
+    // Wrap source in handler function
+    let wrapped = wrap_handler_source(source);
+    let source_map = SourceMap::from_source(&wrapped);
+
+    // In a real implementation, this would:
+    // 1. Load QuickJS WASM module
+    // 2. Call JS_Compile to compile to bytecode
+    // 3. Return the bytecode
+    //
+    // For now, we simulate compilation by storing the source as "bytecode"
+    // This is a placeholder that will be replaced with actual QuickJS compilation
+
+    let bytecode = wrapped.as_bytes().to_vec();
+
+    (bytecode, source_map)
+}
+
+/// Estimate how many functions a handler body defines, for
+/// [`HandlerCompilerInner::check_module_limits`]. There's no real WASM
+/// module to introspect (see [`compile_bytecode`]), so this counts
+/// `function` keywords and arrow-function bodies in the raw source as a
+/// stand-in for an actual function table.
+fn estimate_defined_functions(source: &str) -> u32 {
+    let function_keywords = source.matches("function").count();
+    let arrow_functions = source.matches("=>").count();
+    (function_keywords + arrow_functions) as u32
+}
+
+/// Wrap handler source in the runtime wrapper that provides the handler
+/// interface ($state, $args, $emit, $view, $ext, $log).
+fn wrap_handler_source(source: &str) -> String {
+    format!(
+        r#"(function(__nexus_state, __nexus_args, __nexus_scope) {{
     // Inject globals
     const $state = {{
         get: (key) => __nexus_state_get(key),
@@ -348,6 +757,7 @@ impl HandlerCompiler {
         delete: (key) => __nexus_state_delete(key),
         has: (key) => __nexus_state_has(key),
         keys: () => __nexus_state_keys(),
+        compareAndSet: (key, expected, value) => __nexus_state_compare_and_set(key, expected, value),
     }};
 
     const $args = __nexus_args;
@@ -386,27 +796,173 @@ impl HandlerCompiler {
     // Handler code
     {source}
 }})"#
-        )
-    }
+    )
+}
 
+impl HandlerCompilerInner {
     /// Get cache statistics
-    pub fn get_stats(&self) -> CompilerStats {
+    fn get_stats(&self) -> CompilerStats {
         let cache = self.cache.read();
-        
+
+        let (unique_chunk_count, dedup_ratio) = match &self.chunk_store {
+            Some(chunk_store) => {
+                let logical_bytes = self.disk_cache_logical_bytes.load(Ordering::Relaxed);
+                let unique_bytes = chunk_store.unique_chunk_bytes();
+                let ratio = if unique_bytes == 0 {
+                    1.0
+                } else {
+                    logical_bytes as f64 / unique_bytes as f64
+                };
+                (chunk_store.unique_chunk_count(), ratio)
+            }
+            None => (0, 1.0),
+        };
+
         CompilerStats {
             cache_entries: cache.len(),
             cache_size_bytes: self.cache_size.load(Ordering::Relaxed),
             cache_hits: self.cache_hits.load(Ordering::Relaxed),
             cache_misses: self.cache_misses.load(Ordering::Relaxed),
             total_compilations: self.total_compilations.load(Ordering::Relaxed),
+            cache_evictions: self.cache_evictions.load(Ordering::Relaxed),
+            unique_chunk_count,
+            dedup_ratio,
+            active_compilations: self.active_compilations.load(Ordering::Relaxed),
+            queued_compilations: self.queued_compilations.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Compile handler code to bytecode, offloading the actual compilation
+    /// to the bounded worker pool and deduplicating concurrent requests for
+    /// the same uncached source so they compile exactly once.
+    async fn compile_async(&self, source: &str) -> Result<CompiledHandler> {
+        self.check_module_limits(source)?;
+
+        let cache_key = self.compute_cache_key(source);
+
+        if let Some(entry) = self.get_from_cache(&cache_key) {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+            debug!(key = %cache_key, "Cache hit (async)");
+            return Ok(CompiledHandler {
+                bytecode: entry.bytecode.clone(),
+                source_map: entry.source_map.clone(),
+                cache_hit: true,
+                init_image: self.init_image_if_enabled(&cache_key),
+            });
+        }
+
+        if let Some(entry) = self.get_from_disk_cache(&cache_key) {
+            self.cache_hits.fetch_add(1, Ordering::Relaxed);
+            debug!(key = %cache_key, "Disk cache hit (async)");
+            self.put_to_cache(&cache_key, entry.bytecode.clone(), entry.source_map.clone());
+            return Ok(CompiledHandler {
+                bytecode: entry.bytecode,
+                source_map: entry.source_map,
+                cache_hit: true,
+                init_image: self.init_image_if_enabled(&cache_key),
+            });
+        }
+
+        let (slot, is_leader) = {
+            let mut in_flight = self.in_flight.lock();
+            match in_flight.entry(cache_key.clone()) {
+                Entry::Occupied(e) => (Arc::clone(e.get()), false),
+                Entry::Vacant(e) => {
+                    let slot = Arc::new(InFlightSlot::new());
+                    e.insert(Arc::clone(&slot));
+                    (slot, true)
+                }
+            }
+        };
+
+        let outcome = if is_leader {
+            debug!(key = %cache_key, "Cache miss (async), compiling");
+            self.run_leader_compilation(&cache_key, source, &slot).await
+        } else {
+            self.await_in_flight(&slot).await
+        };
+
+        outcome
+            .map(|(bytecode, source_map)| CompiledHandler {
+                bytecode,
+                source_map: Some(source_map),
+                cache_hit: false,
+                init_image: self.init_image_if_enabled(&cache_key),
+            })
+            .map_err(RuntimeError::Compilation)
+    }
+
+    /// Run the actual compilation on the worker pool for the leader of an
+    /// in-flight group, then publish the result to every follower waiting
+    /// on `slot`.
+    async fn run_leader_compilation(
+        &self,
+        cache_key: &str,
+        source: &str,
+        slot: &InFlightSlot,
+    ) -> std::result::Result<(Vec<u8>, SourceMap), String> {
+        self.queued_compilations.fetch_add(1, Ordering::Relaxed);
+        let permit = Arc::clone(&self.compile_semaphore).acquire_owned().await;
+        self.queued_compilations.fetch_sub(1, Ordering::Relaxed);
+
+        let outcome = match permit {
+            Err(_) => Err("compile worker pool is shut down".to_string()),
+            Ok(permit) => {
+                self.active_compilations.fetch_add(1, Ordering::Relaxed);
+                let source_owned = source.to_string();
+                let joined = tokio::task::spawn_blocking(move || {
+                    let _permit = permit;
+                    compile_bytecode(&source_owned)
+                })
+                .await;
+                self.active_compilations.fetch_sub(1, Ordering::Relaxed);
+
+                match joined {
+                    Ok((bytecode, source_map)) => {
+                        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+                        self.total_compilations.fetch_add(1, Ordering::Relaxed);
+                        self.put_to_cache(cache_key, bytecode.clone(), Some(source_map.clone()));
+                        self.put_to_disk_cache(cache_key, &bytecode, Some(&source_map));
+                        Ok((bytecode, source_map))
+                    }
+                    Err(e) => Err(format!("compilation task panicked: {e}")),
+                }
+            }
+        };
+
+        *slot.result.lock() = Some(outcome.clone());
+        slot.notify.notify_waiters();
+        self.in_flight.lock().remove(cache_key);
+
+        outcome
+    }
+
+    /// Wait for the leader of an in-flight compilation to publish its
+    /// result. Checks `slot.result` both before and after subscribing to
+    /// `notify` so a result published between the two checks is never
+    /// missed.
+    async fn await_in_flight(
+        &self,
+        slot: &InFlightSlot,
+    ) -> std::result::Result<(Vec<u8>, SourceMap), String> {
+        loop {
+            if let Some(outcome) = slot.result.lock().clone() {
+                return outcome;
+            }
+            let notified = slot.notify.notified();
+            if let Some(outcome) = slot.result.lock().clone() {
+                return outcome;
+            }
+            notified.await;
         }
     }
 
     /// Clear all caches
-    pub fn clear_cache(&self) {
+    fn clear_cache(&self) {
         let mut cache = self.cache.write();
         cache.clear();
         self.cache_size.store(0, Ordering::Relaxed);
+        self.policy.clear();
 
         // Clear disk cache
         if let Some(cache_dir) = &self.disk_cache_dir {
@@ -419,6 +975,11 @@ impl HandlerCompiler {
             }
         }
 
+        if let Some(chunk_store) = &self.chunk_store {
+            chunk_store.clear();
+            self.disk_cache_logical_bytes.store(0, Ordering::Relaxed);
+        }
+
         info!("Cleared all caches");
     }
 }
@@ -436,6 +997,20 @@ pub struct CompilerStats {
     pub cache_misses: u64,
     /// Total compilations performed
     pub total_compilations: u64,
+    /// Number of evictions performed
+    pub cache_evictions: u64,
+    /// Number of distinct bytecode chunks currently stored in the disk
+    /// cache's chunk store (0 if dedup is disabled)
+    pub unique_chunk_count: usize,
+    /// Ratio of logical bytecode bytes written to the disk cache versus
+    /// bytes actually occupied by unique chunks on disk (1.0 if dedup is
+    /// disabled or nothing has been written yet)
+    pub dedup_ratio: f64,
+    /// Number of compilations currently executing on the background worker
+    /// pool (see `compile_async`/`warm`)
+    pub active_compilations: u64,
+    /// Number of compilations currently waiting for a free worker-pool slot
+    pub queued_compilations: u64,
 }
 
 impl CompilerStats {
@@ -450,6 +1025,75 @@ impl CompilerStats {
     }
 }
 
+/// Handler compiler with caching
+///
+/// Cheaply cloneable: clones share the same underlying cache and worker
+/// pool, so `warm`'s background task can hold its own handle without
+/// borrowing from the caller.
+#[derive(Clone)]
+pub struct HandlerCompiler {
+    inner: Arc<HandlerCompilerInner>,
+}
+
+impl HandlerCompiler {
+    /// Create a new compiler
+    pub fn new(config: &RuntimeConfig) -> Result<Self> {
+        Ok(Self {
+            inner: Arc::new(HandlerCompilerInner::new(config)?),
+        })
+    }
+
+    /// Compile handler code to bytecode
+    pub fn compile(&self, source: &str) -> Result<CompiledHandler> {
+        self.inner.compile(source)
+    }
+
+    /// Compile handler code to bytecode, offloading the actual compilation
+    /// to a bounded background worker pool (sized by
+    /// `RuntimeConfig::max_concurrent_compilations`) instead of blocking the
+    /// caller. Checks the memory and disk caches first, same as [`Self::compile`].
+    ///
+    /// Concurrent calls for the same uncached source are deduplicated by
+    /// cache key: only one of them actually compiles, and all of them
+    /// observe the same result.
+    pub async fn compile_async(&self, source: &str) -> Result<CompiledHandler> {
+        self.inner.compile_async(source).await
+    }
+
+    /// Pre-compile `sources` in the background and populate both cache
+    /// tiers, without blocking the caller. Returns a handle the caller may
+    /// await to know when warm-up has finished; dropping it lets warm-up
+    /// continue unattended.
+    pub fn warm(&self, sources: &[String]) -> tokio::task::JoinHandle<()> {
+        let inner = Arc::clone(&self.inner);
+        let sources = sources.to_vec();
+        tokio::spawn(async move {
+            for source in sources {
+                if let Err(e) = inner.compile_async(&source).await {
+                    warn!(error = %e, "Failed to warm compiler cache for handler");
+                }
+            }
+        })
+    }
+
+    /// Get cache statistics
+    pub fn get_stats(&self) -> CompilerStats {
+        self.inner.get_stats()
+    }
+
+    /// Clear all caches
+    pub fn clear_cache(&self) {
+        self.inner.clear_cache()
+    }
+
+    /// Compute the cache key for `source`, exposed for tests that need to
+    /// locate a disk cache entry directly.
+    #[cfg(test)]
+    fn compute_cache_key(&self, source: &str) -> String {
+        self.inner.compute_cache_key(source)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -553,6 +1197,56 @@ mod tests {
         assert!((stats.hit_rate() - 0.333).abs() < 0.01);
     }
 
+    #[test]
+    fn test_max_cache_entries_evicts_lru() {
+        let config = RuntimeConfig::default().with_max_cache_entries(2);
+        let compiler = HandlerCompiler::new(&config).unwrap();
+
+        compiler.compile("return 1;").unwrap();
+        compiler.compile("return 2;").unwrap();
+        compiler.compile("return 3;").unwrap();
+
+        let stats = compiler.get_stats();
+        assert_eq!(stats.cache_entries, 2);
+        assert_eq!(stats.cache_evictions, 1);
+    }
+
+    #[test]
+    fn test_lfu_cache_policy_keeps_more_frequently_compiled_entry() {
+        let config = RuntimeConfig::default()
+            .with_max_cache_entries(2)
+            .with_cache_policy(crate::config::CachePolicyKind::Lfu);
+        let compiler = HandlerCompiler::new(&config).unwrap();
+
+        compiler.compile("return 1;").unwrap();
+        compiler.compile("return 1;").unwrap(); // re-access bumps its frequency
+        compiler.compile("return 2;").unwrap();
+        compiler.compile("return 3;").unwrap(); // forces an eviction
+
+        // "return 2;" was only ever touched once, so it's the LFU victim;
+        // "return 1;" survives because it was accessed twice.
+        let result = compiler.compile("return 1;").unwrap();
+        assert!(result.cache_hit);
+
+        let stats = compiler.get_stats();
+        assert_eq!(stats.cache_entries, 2);
+        assert_eq!(stats.cache_evictions, 1);
+    }
+
+    #[test]
+    fn test_unlimited_cache_entries_never_evicts_on_count() {
+        let config = RuntimeConfig::default().with_max_cache_entries(0);
+        let compiler = HandlerCompiler::new(&config).unwrap();
+
+        for i in 0..10 {
+            compiler.compile(&format!("return {i};")).unwrap();
+        }
+
+        let stats = compiler.get_stats();
+        assert_eq!(stats.cache_entries, 10);
+        assert_eq!(stats.cache_evictions, 0);
+    }
+
     #[test]
     fn test_clear_cache() {
         let compiler = create_compiler();
@@ -563,4 +1257,279 @@ mod tests {
         compiler.clear_cache();
         assert_eq!(compiler.get_stats().cache_entries, 0);
     }
+
+    fn unique_temp_dir(label: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir().join(format!("nexus-compiler-test-{}-{}", label, nanos))
+    }
+
+    #[test]
+    fn test_disk_cache_dedup_round_trip() {
+        let dir = unique_temp_dir("dedup-roundtrip");
+        let config = RuntimeConfig::default()
+            .with_cache_dir(dir.clone())
+            .with_disk_cache_dedup(true);
+        let compiler = HandlerCompiler::new(&config).unwrap();
+
+        let source = "return $state.get('count');";
+        let first = compiler.compile(source).unwrap();
+        assert!(!first.cache_hit);
+
+        compiler.clear_cache();
+
+        let second = compiler.compile(source).unwrap();
+        assert!(second.cache_hit);
+        assert_eq!(second.bytecode, first.bytecode);
+
+        let stats = compiler.get_stats();
+        assert!(stats.unique_chunk_count > 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_disk_cache_dedup_shares_chunks_across_similar_handlers() {
+        let dir = unique_temp_dir("dedup-shared");
+        let config = RuntimeConfig::default()
+            .with_cache_dir(dir.clone())
+            .with_disk_cache_dedup(true);
+        let compiler = HandlerCompiler::new(&config).unwrap();
+
+        // Both handlers share the same `wrap_handler` boilerplate, so they
+        // should share most of their chunks even though their own source
+        // lines differ.
+        compiler.compile("return 1;").unwrap();
+        let stats_after_first = compiler.get_stats();
+
+        compiler.compile("return 2;").unwrap();
+        let stats_after_second = compiler.get_stats();
+
+        assert!(
+            stats_after_second.unique_chunk_count < stats_after_first.unique_chunk_count * 2,
+            "expected the second handler's common boilerplate chunks to be deduplicated"
+        );
+        assert!(stats_after_second.dedup_ratio > 1.0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_disk_cache_round_trip_preserves_source_map() {
+        let dir = unique_temp_dir("roundtrip");
+        let config = RuntimeConfig::default().with_cache_dir(dir.clone());
+        let compiler = HandlerCompiler::new(&config).unwrap();
+
+        let source = "return $state.get('count');";
+        let first = compiler.compile(source).unwrap();
+        assert!(!first.cache_hit);
+
+        // Drop the in-memory cache but keep the disk cache, simulating a
+        // cold start
+        compiler.clear_cache();
+
+        let second = compiler.compile(source).unwrap();
+        assert!(second.cache_hit);
+        assert_eq!(second.bytecode, first.bytecode);
+        assert_eq!(
+            second.source_map.as_ref().map(|m| m.source.clone()),
+            first.source_map.as_ref().map(|m| m.source.clone())
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_disk_cache_discards_entry_with_wrong_format_version() {
+        let dir = unique_temp_dir("bad-version");
+        let config = RuntimeConfig::default().with_cache_dir(dir.clone());
+        let compiler = HandlerCompiler::new(&config).unwrap();
+
+        let source = "return 1;";
+        compiler.compile(source).unwrap();
+        compiler.clear_cache();
+
+        let key = compiler.compute_cache_key(source);
+        let path = dir.join(format!("{}.qjsc", key));
+        let mut envelope: DiskCacheEnvelope =
+            rmp_serde::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        envelope.format_version = DISK_CACHE_FORMAT_VERSION + 1;
+        std::fs::write(&path, rmp_serde::to_vec(&envelope).unwrap()).unwrap();
+
+        let result = compiler.compile(source).unwrap();
+        assert!(!result.cache_hit, "a future-versioned entry must be discarded, not trusted");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_disk_cache_discards_entry_with_corrupt_payload() {
+        let dir = unique_temp_dir("corrupt");
+        let config = RuntimeConfig::default().with_cache_dir(dir.clone());
+        let compiler = HandlerCompiler::new(&config).unwrap();
+
+        let source = "return 1;";
+        compiler.compile(source).unwrap();
+        compiler.clear_cache();
+
+        let key = compiler.compute_cache_key(source);
+        let path = dir.join(format!("{}.qjsc", key));
+        let mut envelope: DiskCacheEnvelope =
+            rmp_serde::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        envelope.payload.push(0xFF); // corrupt the payload without updating its checksum
+        std::fs::write(&path, rmp_serde::to_vec(&envelope).unwrap()).unwrap();
+
+        let result = compiler.compile(source).unwrap();
+        assert!(!result.cache_hit, "a payload that fails its checksum must be discarded");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_compile_async_matches_sync_cache_behavior() {
+        let compiler = create_compiler();
+        let source = "return $state.get('count');";
+
+        let first = compiler.compile_async(source).await.unwrap();
+        assert!(!first.cache_hit);
+
+        let second = compiler.compile_async(source).await.unwrap();
+        assert!(second.cache_hit);
+        assert_eq!(first.bytecode, second.bytecode);
+
+        // The sync path should see the same cache entry.
+        let third = compiler.compile(source).unwrap();
+        assert!(third.cache_hit);
+    }
+
+    #[tokio::test]
+    async fn test_compile_async_concurrent_calls_compile_exactly_once() {
+        let config = RuntimeConfig::default().with_max_concurrent_compilations(2);
+        let compiler = HandlerCompiler::new(&config).unwrap();
+        let source = "return 'shared';";
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let compiler = compiler.clone();
+                let source = source.to_string();
+                tokio::spawn(async move { compiler.compile_async(&source).await.unwrap() })
+            })
+            .collect();
+
+        for handle in handles {
+            let compiled = handle.await.unwrap();
+            assert!(!compiled.bytecode.is_empty());
+        }
+
+        let stats = compiler.get_stats();
+        assert_eq!(
+            stats.total_compilations, 1,
+            "all concurrent callers for the same source should share a single compilation"
+        );
+        assert_eq!(stats.active_compilations, 0);
+        assert_eq!(stats.queued_compilations, 0);
+    }
+
+    #[test]
+    fn test_module_limits_ignored_in_on_demand_mode() {
+        let config = RuntimeConfig::default().with_module_limits(crate::config::ModuleLimits {
+            max_imported_functions: 0,
+            max_defined_functions: 0,
+            max_tables: 1,
+            max_memories: 1,
+            max_memory_pages: 1,
+            max_instances: 1,
+        });
+        let compiler = HandlerCompiler::new(&config).unwrap();
+
+        assert!(compiler.compile("return 1;").is_ok());
+    }
+
+    #[test]
+    fn test_pooling_mode_rejects_handler_over_defined_function_limit() {
+        let config = RuntimeConfig::default()
+            .with_pooling_strategy(crate::config::PoolingStrategy::Pooling)
+            .with_module_limits(crate::config::ModuleLimits {
+                max_defined_functions: 1,
+                ..crate::config::ModuleLimits::default()
+            });
+        let compiler = HandlerCompiler::new(&config).unwrap();
+
+        let source = "function a() {} function b() {} function c() {}";
+        let err = compiler.compile(source).unwrap_err();
+        assert!(matches!(err, RuntimeError::Config(_)));
+    }
+
+    #[test]
+    fn test_pooling_mode_accepts_handler_within_module_limits() {
+        let config = RuntimeConfig::default()
+            .with_pooling_strategy(crate::config::PoolingStrategy::Pooling)
+            .with_module_limits(crate::config::ModuleLimits::default());
+        let compiler = HandlerCompiler::new(&config).unwrap();
+
+        assert!(compiler.compile("return 1;").is_ok());
+    }
+
+    #[test]
+    fn test_init_image_absent_when_disabled() {
+        let compiler = create_compiler();
+        let result = compiler.compile("return 1;").unwrap();
+        assert!(result.init_image.is_none());
+    }
+
+    #[test]
+    fn test_init_image_present_and_shared_across_cache_hits() {
+        let config = RuntimeConfig::default().with_shared_init_image(true);
+        let compiler = HandlerCompiler::new(&config).unwrap();
+        let source = "return $state.get('count');";
+
+        let first = compiler.compile(source).unwrap();
+        let image = first.init_image.expect("init image should be built when enabled");
+
+        // A cache-hit recompile should reuse the same underlying image bytes.
+        let second = compiler.compile(source).unwrap();
+        let image2 = second.init_image.expect("init image should still be present on cache hit");
+        assert!(Arc::ptr_eq(image.memory(), image2.memory()));
+    }
+
+    #[test]
+    fn test_init_image_survives_disk_round_trip() {
+        let dir = unique_temp_dir("init-image-roundtrip");
+        let config = RuntimeConfig::default()
+            .with_cache_dir(dir.clone())
+            .with_shared_init_image(true);
+        let compiler = HandlerCompiler::new(&config).unwrap();
+        let source = "return 1;";
+
+        let first = compiler.compile(source).unwrap();
+        let image = first.init_image.unwrap();
+
+        // New compiler instance, same disk cache dir, simulating a cold start.
+        let compiler2 = HandlerCompiler::new(&config).unwrap();
+        let second = compiler2.compile(source).unwrap();
+        let image2 = second.init_image.unwrap();
+
+        assert_eq!(image.memory_used(), image2.memory_used());
+        assert_eq!(*image.memory(), *image2.memory());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_warm_populates_cache_for_all_sources() {
+        let compiler = create_compiler();
+        let sources = vec!["return 1;".to_string(), "return 2;".to_string()];
+
+        compiler.warm(&sources).await.unwrap();
+
+        let stats = compiler.get_stats();
+        assert_eq!(stats.cache_entries, 2);
+        assert_eq!(stats.total_compilations, 2);
+
+        for source in &sources {
+            assert!(compiler.compile(source).unwrap().cache_hit);
+        }
+    }
 }
@@ -0,0 +1,273 @@
+//! Per-handler code-coverage collection, V8 "precise coverage" shaped.
+//!
+//! Mirrors Deno's `CoverageCollector` built on an inspector session: once
+//! [`CoverageCollector::start`] is called, every `execute_handler` call
+//! records a hit against the executing handler's script, and
+//! [`CoverageCollector::take`] drains the accumulated
+//! counts into the same `scriptId`/`functions`/`ranges` JSON shape V8's
+//! `Profiler.takePreciseCoverage` emits, so existing tooling built against
+//! that shape (c8, istanbul's v8 provider) can consume it unmodified.
+//!
+//! [`WasmInstance::execute_sync`](super::instance::WasmInstance) has no real
+//! bytecode to step through a branch at a time (see its doc comment), so a
+//! hit can't be attributed to a specific line or branch within the handler
+//! the way a real interpreter's block tracing would. Each recorded
+//! execution is instead credited to a single range spanning the handler's
+//! whole source, with `count` incremented once per run — coarse per-handler
+//! invocation coverage rather than true line coverage, but enough for
+//! gating "was this handler exercised at all" in the kernel's test tooling.
+//!
+//! An `execute_compiled_handler` call records nothing: no source map
+//! travels with raw bytecode, and [`CoverageCollector::record_execution`]
+//! ignores an execution without one.
+
+use super::compiler::SourceMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// One covered range within a function, V8's `{startOffset, endOffset,
+/// count}` shape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageRange {
+    /// Byte offset into the script's source where this range begins
+    pub start_offset: u64,
+    /// Byte offset into the script's source where this range ends
+    pub end_offset: u64,
+    /// Number of times this range was hit
+    pub count: u64,
+}
+
+/// One function's coverage entry, V8's `{functionName, ranges,
+/// isBlockCoverage}` shape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionCoverage {
+    /// The handler name this entry covers
+    pub function_name: String,
+    /// Covered ranges within this function
+    pub ranges: Vec<CoverageRange>,
+    /// Always `false`: see the module doc comment on why this collector
+    /// can't attribute hits to individual blocks
+    pub is_block_coverage: bool,
+}
+
+/// One script's coverage entry, V8's `{scriptId, url, functions}` shape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptCoverage {
+    /// Stable id for this handler's script, derived from its source so
+    /// repeated executions accumulate onto the same entry
+    pub script_id: String,
+    /// The handler's panel/name, standing in for a script URL
+    pub url: String,
+    /// Per-function coverage for this script (always one entry: the
+    /// handler body itself, since handlers have no nested functions visible
+    /// to this simulated engine)
+    pub functions: Vec<FunctionCoverage>,
+    /// The handler's original source, carried alongside the ranges (which
+    /// are only byte offsets) so a consumer can map them back to lines
+    /// without a separate fetch of the handler source — the same reason
+    /// `JsLocation::source_snippet` rides along with an error's location.
+    /// Always set for entries this collector produces; a handler run through
+    /// `execute_compiled_handler` has no source and gets no entry at all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_snippet: Option<String>,
+}
+
+/// Top-level report shape, matching `Profiler.takePreciseCoverage`'s
+/// `{result: [...]}` envelope
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CoverageReport {
+    /// Per-script coverage since the last [`CoverageCollector::take`]
+    pub result: Vec<ScriptCoverage>,
+}
+
+/// Accumulated coverage for one script, keyed by [`script_id`] in
+/// [`CoverageCollector::scripts`]
+struct ScriptAccumulator {
+    url: String,
+    function_name: String,
+    end_offset: u64,
+    count: u64,
+    source_snippet: Option<String>,
+}
+
+/// Derive a stable script id for `source`, so every execution of the same
+/// handler body accumulates onto the same [`ScriptCoverage`] entry instead
+/// of producing a new one each time.
+fn script_id(source: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(source);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Collects per-handler coverage while enabled via [`Self::start`].
+pub struct CoverageCollector {
+    enabled: AtomicBool,
+    scripts: Mutex<HashMap<String, ScriptAccumulator>>,
+}
+
+impl Default for CoverageCollector {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            scripts: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl CoverageCollector {
+    /// Create a collector with recording disabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Begin (or continue) recording executions. Doesn't clear anything
+    /// already accumulated, so a `start` after a `take` just resumes
+    /// counting from zero for scripts `take` drained, and keeps adding to
+    /// any script `take` hasn't been called for yet.
+    pub fn start(&self) {
+        self.enabled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether [`Self::start`] has been called; [`Self::take`] leaves
+    /// recording enabled
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Record one execution of `handler_name`'s source, crediting a hit to
+    /// its whole-source range. A no-op if recording isn't enabled, so
+    /// callers can unconditionally call this on every execution without
+    /// checking [`Self::is_enabled`] themselves.
+    pub fn record_execution(&self, handler_name: &str, source_map: Option<&SourceMap>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let Some(source_map) = source_map else {
+            return;
+        };
+
+        let id = script_id(source_map.source.as_bytes());
+        let end_offset = source_map.source.len() as u64;
+
+        let mut scripts = self.scripts.lock();
+        scripts
+            .entry(id)
+            .and_modify(|acc| acc.count += 1)
+            .or_insert_with(|| ScriptAccumulator {
+                url: handler_name.to_string(),
+                function_name: handler_name.to_string(),
+                end_offset,
+                count: 1,
+                source_snippet: Some(source_map.source.clone()),
+            });
+    }
+
+    /// Drain every count accumulated since the last `take` (or since
+    /// [`Self::start`], if this is the first) into a V8-shaped
+    /// [`CoverageReport`]. Recording stays enabled; call [`Self::start`]
+    /// again only if it was never called at all.
+    pub fn take(&self) -> CoverageReport {
+        let mut scripts = self.scripts.lock();
+        let result = scripts
+            .drain()
+            .map(|(id, acc)| ScriptCoverage {
+                script_id: id,
+                url: acc.url,
+                functions: vec![FunctionCoverage {
+                    function_name: acc.function_name,
+                    ranges: vec![CoverageRange {
+                        start_offset: 0,
+                        end_offset: acc.end_offset,
+                        count: acc.count,
+                    }],
+                    is_block_coverage: false,
+                }],
+                source_snippet: acc.source_snippet,
+            })
+            .collect();
+
+        CoverageReport { result }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_map(source: &str) -> SourceMap {
+        SourceMap::from_source(source)
+    }
+
+    #[test]
+    fn test_record_execution_is_noop_when_disabled() {
+        let collector = CoverageCollector::new();
+        collector.record_execution("my-handler", Some(&source_map("return 1;")));
+
+        assert!(collector.take().result.is_empty());
+    }
+
+    #[test]
+    fn test_record_execution_accumulates_count_per_script() {
+        let collector = CoverageCollector::new();
+        collector.start();
+
+        let map = source_map("return 1;");
+        collector.record_execution("my-handler", Some(&map));
+        collector.record_execution("my-handler", Some(&map));
+
+        let report = collector.take();
+        assert_eq!(report.result.len(), 1);
+        let script = &report.result[0];
+        assert_eq!(script.url, "my-handler");
+        assert_eq!(script.functions.len(), 1);
+        assert_eq!(script.functions[0].ranges.len(), 1);
+        assert_eq!(script.functions[0].ranges[0].count, 2);
+        assert_eq!(
+            script.functions[0].ranges[0].end_offset,
+            "return 1;".len() as u64
+        );
+        assert_eq!(script.source_snippet.as_deref(), Some("return 1;"));
+    }
+
+    #[test]
+    fn test_take_drains_and_resets_counts() {
+        let collector = CoverageCollector::new();
+        collector.start();
+
+        let map = source_map("return 1;");
+        collector.record_execution("my-handler", Some(&map));
+        assert_eq!(collector.take().result[0].functions[0].ranges[0].count, 1);
+
+        collector.record_execution("my-handler", Some(&map));
+        assert_eq!(collector.take().result[0].functions[0].ranges[0].count, 1);
+    }
+
+    #[test]
+    fn test_different_handlers_get_distinct_script_ids() {
+        let collector = CoverageCollector::new();
+        collector.start();
+
+        collector.record_execution("a", Some(&source_map("return 1;")));
+        collector.record_execution("b", Some(&source_map("return 2;")));
+
+        let report = collector.take();
+        assert_eq!(report.result.len(), 2);
+    }
+
+    #[test]
+    fn test_record_execution_without_source_map_is_ignored() {
+        let collector = CoverageCollector::new();
+        collector.start();
+
+        collector.record_execution("precompiled-handler", None);
+
+        assert!(collector.take().result.is_empty());
+    }
+}
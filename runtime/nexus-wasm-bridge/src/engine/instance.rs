@@ -2,15 +2,17 @@
 //!
 //! Each WasmInstance represents a single QuickJS runtime running in WasmEdge.
 
-use super::compiler::CompiledHandler;
+use super::compiler::{CompiledHandler, SharedInitImage};
 use crate::config::{ResourceLimits, RuntimeConfig};
 use crate::context::{
-    AsyncResult, ExecutionContext, ExecutionStatus, RuntimeValue, WasmContext, WasmResult,
+    AsyncResult, ExecutionContext, ExecutionStatus, JoinMode, RuntimeValue, WasmContext, WasmResult,
 };
 use crate::error::{Result, RuntimeError, WasmError};
+use crate::event_sink::EventSink;
 use crate::host_functions::{events, extension, logging, state, view, SharedContext};
 use crate::metrics::ExecutionMetrics;
 use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Instant;
 use uuid::Uuid;
@@ -18,6 +20,29 @@ use uuid::Uuid;
 /// Unique instance ID
 pub type InstanceId = String;
 
+/// Simulated number of basic blocks charged per execution. `execute_internal`
+/// has no real bytecode to walk (see its doc comment), so gas is charged as
+/// if the handler ran this many blocks rather than at real block boundaries.
+const SIMULATED_BLOCK_COUNT: u64 = 4;
+
+/// Gas cost charged per simulated basic block
+const GAS_PER_BLOCK: u64 = 100;
+
+/// WASM page size in bytes, used to convert simulated memory growth into a
+/// page count for the memory-grow gas charge
+const WASM_PAGE_BYTES: u64 = 64 * 1024;
+
+/// Gas cost charged per page of simulated memory growth
+const GAS_PER_MEMORY_PAGE: u64 = 10;
+
+/// Total gas consumed so far, derived from the configured `limit` and the
+/// context's current `gas_remaining` (works whether or not a limit is set,
+/// since an unset limit still initializes `gas_remaining` to `u64::MAX`)
+fn gas_used(limit: u64, remaining: u64) -> u64 {
+    let initial = if limit == 0 { u64::MAX } else { limit };
+    initial.saturating_sub(remaining)
+}
+
 /// WASM instance state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InstanceState {
@@ -31,6 +56,38 @@ pub enum InstanceState {
     Terminated,
 }
 
+/// Tracks whether this instance's `RuntimeConfig::low_memory_hook` has fired
+/// for the current memory-pressure episode, re-evaluated on every linear
+/// memory grow by [`WasmInstance::note_memory_grow`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LowMemoryStatus {
+    /// Remaining memory (`memory_limit_bytes - memory_used`) is still at or
+    /// above the configured `low_memory_threshold_bytes`
+    ConditionNotSatisfied,
+    /// Remaining memory has dropped below the threshold; the hook has not
+    /// fired yet for this episode
+    Ready,
+    /// The hook has fired for this episode; it will not fire again until
+    /// usage drops back above the threshold, re-arming `ConditionNotSatisfied`
+    Executed,
+}
+
+/// An opaque, cheaply-cloneable snapshot of a [`WasmInstance`]'s linear
+/// memory and memory/execution bookkeeping, taken with
+/// [`WasmInstance::snapshot`] and reinstated with [`WasmInstance::restore`].
+///
+/// The memory is held behind an `Arc` so taking a snapshot is the only copy
+/// paid for; cloning the snapshot itself (to fork a warm instance, or to
+/// hold it across an async suspension for later rollback) is just an
+/// `Arc::clone`.
+#[derive(Debug, Clone)]
+pub struct InstanceSnapshot {
+    memory: Arc<Vec<u8>>,
+    memory_used: u64,
+    memory_peak: u64,
+    execution_count: u64,
+}
+
 /// A single WASM instance
 pub struct WasmInstance {
     /// Unique instance ID
@@ -45,14 +102,44 @@ pub struct WasmInstance {
     memory_used: u64,
     /// Peak memory in bytes
     memory_peak: u64,
+    /// Simulated linear memory pages, sized to `memory_used`. Stands in for
+    /// the QuickJS WASM module's real linear memory (see
+    /// [`Self::execute_sync`]); snapshotted/restored by
+    /// [`Self::snapshot`]/[`Self::restore`].
+    linear_memory: Vec<u8>,
+    /// Whether this instance's linear memory was instantiated as `shared`
+    /// (from `RuntimeConfig::enable_shared_memory`), required for
+    /// `snapshot`/`restore` to duplicate it cheaply
+    memory_shared: bool,
     /// Creation time
     created_at: Instant,
+    /// Time the instance was last returned to the pool as idle, if any
+    idle_since: Option<Instant>,
     /// Execution count
     execution_count: u64,
     /// Current execution context (if executing or suspended)
     context: Option<SharedContext>,
-    /// Suspension ID (if suspended)
-    suspension_id: Option<String>,
+    /// IDs of the extension calls this instance is waiting on (if suspended).
+    /// More than one entry means the handler fanned out several concurrent
+    /// calls (e.g. `Promise.all`); the instance only returns to `Idle` once
+    /// every one of them has been resolved via [`Self::resume`].
+    suspension_ids: HashSet<String>,
+    /// Memory-pressure episode state for `RuntimeConfig::low_memory_hook`
+    low_memory_status: LowMemoryStatus,
+    /// Linear memory and bookkeeping captured right after instantiation
+    /// (after `extra_heap_pages` growth), used by [`Self::try_fast_reset`]
+    /// to reuse the instance in place instead of a full [`Self::reset`];
+    /// only populated when `config.fast_instance_reuse` is set.
+    post_init_snapshot: Option<InstanceSnapshot>,
+    /// Set by [`Self::restore_memory`] when a cross-process
+    /// [`crate::engine::snapshot`] was just installed, so the next
+    /// [`Self::execute`] skips its usual init simulation instead of
+    /// overwriting the restored bytes; cleared once consumed.
+    primed_memory: bool,
+    /// Event sink to attach to the *next* execution's context, set by
+    /// [`Self::set_event_sink`] and consumed (taken) by [`Self::execute`] so
+    /// it only applies to the one call it was set for.
+    pending_event_sink: Option<Arc<dyn EventSink>>,
 }
 
 impl WasmInstance {
@@ -60,17 +147,38 @@ impl WasmInstance {
     pub fn new(config: &RuntimeConfig) -> Result<Self> {
         let id = Uuid::new_v4().to_string();
 
+        // Pre-grow linear memory by `extra_heap_pages` beyond what the
+        // module itself requests, so handlers that allocate early don't pay
+        // repeated grow syscalls.
+        let memory_used = config.extra_heap_pages * WASM_PAGE_BYTES;
+        let mut linear_memory = Vec::new();
+        linear_memory.resize(memory_used as usize, 0);
+
+        let post_init_snapshot = config.fast_instance_reuse.then(|| InstanceSnapshot {
+            memory: Arc::new(linear_memory.clone()),
+            memory_used,
+            memory_peak: memory_used,
+            execution_count: 0,
+        });
+
         Ok(Self {
             id,
             state: InstanceState::Idle,
             config: config.clone(),
             limits: ResourceLimits::default(),
-            memory_used: 0,
-            memory_peak: 0,
+            memory_used,
+            memory_peak: memory_used,
+            linear_memory,
+            memory_shared: config.enable_shared_memory,
             created_at: Instant::now(),
+            idle_since: None,
             execution_count: 0,
             context: None,
-            suspension_id: None,
+            suspension_ids: HashSet::new(),
+            low_memory_status: LowMemoryStatus::ConditionNotSatisfied,
+            post_init_snapshot,
+            primed_memory: false,
+            pending_event_sink: None,
         })
     }
 
@@ -94,9 +202,58 @@ impl WasmInstance {
         self.memory_peak
     }
 
-    /// Get suspension ID (if suspended)
-    pub fn suspension_id(&self) -> Option<&str> {
-        self.suspension_id.as_deref()
+    /// Get the current memory-pressure episode status
+    pub fn low_memory_status(&self) -> LowMemoryStatus {
+        self.low_memory_status
+    }
+
+    /// Get the IDs of the extension calls this instance is waiting on
+    /// (empty if not suspended)
+    pub fn suspension_ids(&self) -> &HashSet<String> {
+        &self.suspension_ids
+    }
+
+    /// Get the panel/handler identity of the in-flight execution, if any
+    /// (set while executing or suspended, `None` once it completes)
+    pub fn handler_identity(&self) -> Option<(String, String)> {
+        self.context.as_ref().map(|ctx| {
+            let context = ctx.lock();
+            (context.panel_id.clone(), context.handler_name.clone())
+        })
+    }
+
+    /// Get the time this instance was created
+    pub fn created_at(&self) -> Instant {
+        self.created_at
+    }
+
+    /// Get the time this instance was last returned to the pool as idle,
+    /// if it currently is idle
+    pub fn idle_since(&self) -> Option<Instant> {
+        self.idle_since
+    }
+
+    /// Get the number of times this instance has executed a handler
+    pub fn execution_count(&self) -> u64 {
+        self.execution_count
+    }
+
+    /// Mark the instance as having just become idle, starting its idle clock
+    pub fn mark_idle(&mut self) {
+        self.idle_since = Some(Instant::now());
+    }
+
+    /// Set the resource limits enforced by this instance's next execution
+    /// (gas budget, memory, etc.); defaults to `ResourceLimits::default()`
+    pub fn set_limits(&mut self, limits: ResourceLimits) {
+        self.limits = limits;
+    }
+
+    /// Attach an event sink to this instance's next execution only (see
+    /// [`Self::pending_event_sink`]); pass `None` to clear a previously set
+    /// one without running an execution
+    pub fn set_event_sink(&mut self, sink: Option<Arc<dyn EventSink>>) {
+        self.pending_event_sink = sink;
     }
 
     /// Reset the instance for reuse
@@ -109,8 +266,177 @@ impl WasmInstance {
 
         self.state = InstanceState::Idle;
         self.context = None;
-        self.suspension_id = None;
+        self.suspension_ids.clear();
         self.memory_used = 0;
+        self.linear_memory.clear();
+        self.idle_since = None;
+        self.low_memory_status = LowMemoryStatus::ConditionNotSatisfied;
+        self.primed_memory = false;
+        self.pending_event_sink = None;
+
+        Ok(())
+    }
+
+    /// Try to reuse the instance in place instead of a full [`Self::reset`]:
+    /// resets linear memory and bookkeeping back to the snapshot captured at
+    /// instantiation, skipping the (simulated) QuickJS context teardown and
+    /// re-instantiation. Returns `false` without changing any state if
+    /// `config.fast_instance_reuse` is off, or if the memory dirtied since
+    /// instantiation exceeds `DEFAULT_FAST_REUSE_MAX_DIRTY_BYTES`, in which
+    /// case the caller should fall back to [`Self::reset`].
+    pub fn try_fast_reset(&mut self) -> bool {
+        let Some(baseline) = &self.post_init_snapshot else {
+            return false;
+        };
+
+        let dirty_bytes = self.memory_used.saturating_sub(baseline.memory_used);
+        if dirty_bytes > crate::config::DEFAULT_FAST_REUSE_MAX_DIRTY_BYTES {
+            return false;
+        }
+
+        self.state = InstanceState::Idle;
+        self.context = None;
+        self.suspension_ids.clear();
+        self.linear_memory = (*baseline.memory).clone();
+        self.memory_used = baseline.memory_used;
+        self.memory_peak = baseline.memory_peak;
+        self.idle_since = None;
+        self.low_memory_status = LowMemoryStatus::ConditionNotSatisfied;
+
+        true
+    }
+
+    /// Capture the instance's linear memory and memory/execution bookkeeping
+    /// into an opaque, cheaply-cloneable snapshot.
+    ///
+    /// This duplicates the memory via a shared-memory copy rather than a
+    /// full re-instantiation of the module, so it only works when the
+    /// instance's linear memory is of `shared` type (see
+    /// `RuntimeConfig::enable_shared_memory`); errors otherwise rather than
+    /// falling back to an implicit, much more expensive deep copy. The
+    /// returned snapshot can be held across async suspensions and restored
+    /// later to roll back a failed transaction or fork a warm instance.
+    pub fn snapshot(&self) -> Result<InstanceSnapshot> {
+        if !self.memory_shared {
+            return Err(RuntimeError::InvalidState(
+                "Cannot snapshot instance: linear memory is not of shared type \
+                 (enable RuntimeConfig::enable_shared_memory)"
+                    .into(),
+            ));
+        }
+
+        Ok(InstanceSnapshot {
+            memory: Arc::new(self.linear_memory.clone()),
+            memory_used: self.memory_used,
+            memory_peak: self.memory_peak,
+            execution_count: self.execution_count,
+        })
+    }
+
+    /// Reinstate linear memory and memory/execution bookkeeping previously
+    /// captured by [`Self::snapshot`].
+    pub fn restore(&mut self, snapshot: &InstanceSnapshot) -> Result<()> {
+        if !self.memory_shared {
+            return Err(RuntimeError::InvalidState(
+                "Cannot restore instance: linear memory is not of shared type \
+                 (enable RuntimeConfig::enable_shared_memory)"
+                    .into(),
+            ));
+        }
+
+        self.linear_memory = (*snapshot.memory).clone();
+        self.memory_used = snapshot.memory_used;
+        self.memory_peak = snapshot.memory_peak;
+        self.execution_count = snapshot.execution_count;
+
+        Ok(())
+    }
+
+    /// Clone linear memory from a [`SharedInitImage`] instead of
+    /// instantiating from scratch, letting the pool skip repeating a
+    /// handler's init work for every instance that runs it.
+    ///
+    /// Like [`Self::snapshot`]/[`Self::restore`], this only works when the
+    /// instance's linear memory is of `shared` type (see
+    /// `RuntimeConfig::enable_shared_memory`), since that's what lets the
+    /// clone start as an independent writable copy rather than an implicit
+    /// deep copy of the image's bytes.
+    pub fn clone_from_image(&mut self, image: &SharedInitImage) -> Result<()> {
+        if !self.memory_shared {
+            return Err(RuntimeError::InvalidState(
+                "Cannot clone from shared init image: linear memory is not of shared type \
+                 (enable RuntimeConfig::enable_shared_memory)"
+                    .into(),
+            ));
+        }
+
+        self.linear_memory = (*image.memory()).as_ref().clone();
+        self.memory_used = image.memory_used();
+        self.memory_peak = self.memory_peak.max(image.memory_used());
+
+        Ok(())
+    }
+
+    /// Run a compiled handler's (simulated) initialization — the same
+    /// linear-memory setup [`Self::execute_sync`] performs before invoking
+    /// the handler body — without invoking it, so the resulting memory can
+    /// be captured by [`Self::capture_memory`] for a
+    /// [`crate::engine::snapshot`] buffer instead of being thrown away at
+    /// the end of a real execution.
+    pub fn prime_for_snapshot(&mut self, compiled: &CompiledHandler) -> Result<()> {
+        if self.state != InstanceState::Idle {
+            return Err(RuntimeError::InvalidState(format!(
+                "Instance not idle: {:?}",
+                self.state
+            )));
+        }
+
+        self.simulate_init(compiled)
+    }
+
+    /// Capture this instance's linear memory for a
+    /// [`crate::engine::snapshot`] buffer; meant to be called right after
+    /// [`Self::prime_for_snapshot`].
+    ///
+    /// Like [`Self::snapshot`], only works when linear memory is of `shared`
+    /// type, since that's what makes the copy cheap and well-defined rather
+    /// than an implicit deep copy of unrelated pages.
+    pub fn capture_memory(&self) -> Result<(Vec<u8>, u64, u64)> {
+        if !self.memory_shared {
+            return Err(RuntimeError::InvalidState(
+                "Cannot capture instance memory: linear memory is not of shared type \
+                 (enable RuntimeConfig::enable_shared_memory)"
+                    .into(),
+            ));
+        }
+
+        Ok((self.linear_memory.clone(), self.memory_used, self.memory_peak))
+    }
+
+    /// Install linear memory decoded from a [`crate::engine::snapshot`]
+    /// buffer into this (freshly acquired, `Idle`) instance, arming
+    /// [`Self::execute`] to skip its usual init simulation on the very next
+    /// call so the restored bytes aren't immediately overwritten.
+    pub fn restore_memory(&mut self, memory: Vec<u8>, memory_used: u64, memory_peak: u64) -> Result<()> {
+        if !self.memory_shared {
+            return Err(RuntimeError::InvalidState(
+                "Cannot restore instance memory: linear memory is not of shared type \
+                 (enable RuntimeConfig::enable_shared_memory)"
+                    .into(),
+            ));
+        }
+        if self.state != InstanceState::Idle {
+            return Err(RuntimeError::InvalidState(format!(
+                "Instance not idle: {:?}",
+                self.state
+            )));
+        }
+
+        self.linear_memory = memory;
+        self.memory_used = memory_used;
+        self.memory_peak = memory_peak;
+        self.primed_memory = true;
+        self.note_memory_grow();
 
         Ok(())
     }
@@ -134,7 +460,12 @@ impl WasmInstance {
         let start = Instant::now();
 
         // Create execution context
-        let exec_context = ExecutionContext::from_wasm_context(wasm_context);
+        let mut exec_context = ExecutionContext::from_wasm_context(wasm_context);
+        exec_context.set_gas_remaining(self.limits.gas_limit);
+        exec_context.set_resource_limits(&self.limits);
+        if let Some(sink) = self.pending_event_sink.take() {
+            exec_context.set_event_sink(sink);
+        }
         let shared_context: SharedContext = Arc::new(Mutex::new(exec_context));
         self.context = Some(Arc::clone(&shared_context));
 
@@ -151,12 +482,27 @@ impl WasmInstance {
 
         // Build result
         let wasm_result = match result {
+            Ok(_) if context.resource_limit_violation().is_some() => {
+                self.state = InstanceState::Idle;
+                let (kind, limit, used) = context.resource_limit_violation().unwrap();
+
+                WasmResult::resource_exhausted(
+                    WasmError::resource_exhausted(kind, limit, used),
+                    ExecutionMetrics::new()
+                        .with_duration(duration)
+                        .with_memory(self.memory_used, self.memory_peak)
+                        .with_resource_limit_exceeded(kind),
+                )
+                .with_mutations(context.state_mutations.clone())
+                .with_events(context.events.clone())
+                .with_view_commands(context.view_commands.clone())
+            }
             Ok(return_value) => {
-                // Check for suspension
-                if context.suspension.is_some() {
-                    let suspension = context.suspension.as_ref().unwrap();
+                // Check for suspension(s); more than one pending entry means
+                // the handler fanned out several concurrent extension calls
+                if context.has_pending_suspensions() {
                     self.state = InstanceState::Suspended;
-                    self.suspension_id = Some(suspension.id.clone());
+                    self.suspension_ids = context.suspensions.keys().cloned().collect();
 
                     WasmResult {
                         status: ExecutionStatus::Suspended,
@@ -164,16 +510,13 @@ impl WasmInstance {
                         state_mutations: context.state_mutations.clone(),
                         events: context.events.clone(),
                         view_commands: context.view_commands.clone(),
-                        suspension: Some(crate::context::SuspensionDetails {
-                            suspension_id: suspension.id.clone(),
-                            extension_name: suspension.extension_name.clone(),
-                            method: suspension.method.clone(),
-                            args: suspension.args.clone(),
-                        }),
+                        suspensions: context.suspensions.values().map(Into::into).collect(),
+                        join_mode: context.join_mode,
                         error: None,
                         metrics: ExecutionMetrics::new()
                             .with_duration(duration)
-                            .with_memory(self.memory_used, self.memory_peak),
+                            .with_memory(self.memory_used, self.memory_peak)
+                            .with_gas_used(gas_used(self.limits.gas_limit, context.gas_remaining)),
                     }
                 } else {
                     self.state = InstanceState::Idle;
@@ -184,14 +527,30 @@ impl WasmInstance {
                         state_mutations: context.state_mutations.clone(),
                         events: context.events.clone(),
                         view_commands: context.view_commands.clone(),
-                        suspension: None,
+                        suspensions: Vec::new(),
+                        join_mode: JoinMode::default(),
                         error: None,
                         metrics: ExecutionMetrics::new()
                             .with_duration(duration)
-                            .with_memory(self.memory_used, self.memory_peak),
+                            .with_memory(self.memory_used, self.memory_peak)
+                            .with_gas_used(gas_used(self.limits.gas_limit, context.gas_remaining)),
                     }
                 }
             }
+            Err(RuntimeError::GasExhausted { limit, used }) => {
+                self.state = InstanceState::Idle;
+
+                WasmResult::gas_exhausted(
+                    WasmError::gas_exhausted(limit, used),
+                    ExecutionMetrics::new()
+                        .with_duration(duration)
+                        .with_memory(self.memory_used, self.memory_peak)
+                        .with_gas_used(used),
+                )
+                .with_mutations(context.state_mutations.clone())
+                .with_events(context.events.clone())
+                .with_view_commands(context.view_commands.clone())
+            }
             Err(e) => {
                 self.state = InstanceState::Idle;
 
@@ -207,29 +566,126 @@ impl WasmInstance {
             }
         };
 
+        drop(context);
+        self.maybe_fire_low_memory_hook();
+
         Ok(wasm_result)
     }
 
-    /// Resume a suspended handler
-    pub async fn resume(&mut self, result: AsyncResult) -> Result<WasmResult> {
+    /// Resume a suspended handler by fulfilling one pending extension call.
+    ///
+    /// If the instance fanned out several concurrent calls, whether this
+    /// actually re-dispatches the handler depends on `join_mode`: under
+    /// [`JoinMode::All`] it leaves the instance `Suspended` on the rest until
+    /// every one of them has settled; under [`JoinMode::Any`] it resumes
+    /// immediately and abandons the remaining calls. Results are delivered to
+    /// the handler as a stable `suspension_id -> AsyncResult` map (see
+    /// [`ExecutionContext::take_resolved`]); under `All`, a rejection from
+    /// any call surfaces every rejection rather than just the first.
+    pub async fn resume(&mut self, suspension_id: &str, result: AsyncResult) -> Result<WasmResult> {
         if self.state != InstanceState::Suspended {
             return Err(RuntimeError::InvalidState(format!(
                 "Instance not suspended: {:?}",
                 self.state
             )));
         }
-
-        self.state = InstanceState::Executing;
-        self.suspension_id = None;
+        if !self.suspension_ids.contains(suspension_id) {
+            return Err(RuntimeError::InvalidState(format!(
+                "Instance is not awaiting suspension {}",
+                suspension_id
+            )));
+        }
 
         let start = Instant::now();
 
-        // Resume execution with the async result
+        let ready = {
+            let context = self
+                .context
+                .as_ref()
+                .ok_or_else(|| RuntimeError::InvalidState("No context".into()))?;
+            context.lock().resolve(suspension_id, result)
+        }
+        .unwrap_or(false);
+        self.suspension_ids.remove(suspension_id);
+
+        // Other concurrent calls are still in flight and the join condition
+        // isn't met yet; the handler can't continue past its join point.
+        if !ready {
+            let context = self.context.as_ref().unwrap().lock();
+            return Ok(WasmResult {
+                status: ExecutionStatus::Suspended,
+                return_value: None,
+                state_mutations: context.state_mutations.clone(),
+                events: context.events.clone(),
+                view_commands: context.view_commands.clone(),
+                suspensions: context.suspensions.values().map(Into::into).collect(),
+                join_mode: context.join_mode,
+                error: None,
+                metrics: ExecutionMetrics::new().with_duration(start.elapsed()),
+            });
+        }
+
+        // The join condition is met; any calls still outstanding (only
+        // possible under `JoinMode::Any`) are abandoned along with them.
+        self.suspension_ids.clear();
+
+        let (results, join_mode) = {
+            let mut context = self.context.as_ref().unwrap().lock();
+            let join_mode = context.join_mode;
+            (context.take_resolved(), join_mode)
+        };
+
+        // Under `All`, a single rejection doesn't short-circuit the others —
+        // surface every failure so the guest can see the whole batch instead
+        // of just whichever one happened to be reported first.
+        if join_mode == JoinMode::All {
+            let failures: Vec<(String, String)> = results
+                .iter()
+                .filter_map(|(id, result)| {
+                    (!result.success).then(|| {
+                        (
+                            id.clone(),
+                            result.error.clone().unwrap_or_else(|| "unknown error".into()),
+                        )
+                    })
+                })
+                .collect();
+
+            if !failures.is_empty() {
+                self.state = InstanceState::Idle;
+                let context = self.context.take().unwrap();
+                let context = context.lock();
+
+                let errors: serde_json::Map<String, serde_json::Value> = failures
+                    .iter()
+                    .map(|(id, message)| (id.clone(), serde_json::Value::String(message.clone())))
+                    .collect();
+
+                let error = WasmError::execution_error(format!(
+                    "{} of {} concurrent extension calls failed",
+                    failures.len(),
+                    results.len()
+                ))
+                .with_context(serde_json::Value::Object(errors));
+
+                return Ok(WasmResult::error(
+                    error,
+                    ExecutionMetrics::new().with_duration(start.elapsed()),
+                )
+                .with_mutations(context.state_mutations.clone())
+                .with_events(context.events.clone())
+                .with_view_commands(context.view_commands.clone()));
+            }
+        }
+
+        self.state = InstanceState::Executing;
+
+        // Resume execution with the settled results
         // In a real implementation, this would:
-        // 1. Inject the async result into the WASM memory
+        // 1. Inject the async results into the WASM memory
         // 2. Call asyncify_start_rewind to restore the stack
         // 3. Continue execution
-        let execution_result = self.resume_internal(result).await;
+        let execution_result = self.resume_internal(results).await;
 
         let duration = start.elapsed();
         let context = self
@@ -239,12 +695,27 @@ impl WasmInstance {
         let context = context.lock();
 
         let wasm_result = match execution_result {
+            Ok(_) if context.resource_limit_violation().is_some() => {
+                self.state = InstanceState::Idle;
+                self.context = None;
+                let (kind, limit, used) = context.resource_limit_violation().unwrap();
+
+                WasmResult::resource_exhausted(
+                    WasmError::resource_exhausted(kind, limit, used),
+                    ExecutionMetrics::new()
+                        .with_duration(duration)
+                        .with_gas_used(gas_used(self.limits.gas_limit, context.gas_remaining))
+                        .with_resource_limit_exceeded(kind),
+                )
+                .with_mutations(context.state_mutations.clone())
+                .with_events(context.events.clone())
+                .with_view_commands(context.view_commands.clone())
+            }
             Ok(return_value) => {
                 // Check for another suspension
-                if context.suspension.is_some() {
-                    let suspension = context.suspension.as_ref().unwrap();
+                if context.has_pending_suspensions() {
                     self.state = InstanceState::Suspended;
-                    self.suspension_id = Some(suspension.id.clone());
+                    self.suspension_ids = context.suspensions.keys().cloned().collect();
 
                     WasmResult {
                         status: ExecutionStatus::Suspended,
@@ -252,14 +723,12 @@ impl WasmInstance {
                         state_mutations: context.state_mutations.clone(),
                         events: context.events.clone(),
                         view_commands: context.view_commands.clone(),
-                        suspension: Some(crate::context::SuspensionDetails {
-                            suspension_id: suspension.id.clone(),
-                            extension_name: suspension.extension_name.clone(),
-                            method: suspension.method.clone(),
-                            args: suspension.args.clone(),
-                        }),
+                        suspensions: context.suspensions.values().map(Into::into).collect(),
+                        join_mode: context.join_mode,
                         error: None,
-                        metrics: ExecutionMetrics::new().with_duration(duration),
+                        metrics: ExecutionMetrics::new()
+                            .with_duration(duration)
+                            .with_gas_used(gas_used(self.limits.gas_limit, context.gas_remaining)),
                     }
                 } else {
                     self.state = InstanceState::Idle;
@@ -271,20 +740,41 @@ impl WasmInstance {
                         state_mutations: context.state_mutations.clone(),
                         events: context.events.clone(),
                         view_commands: context.view_commands.clone(),
-                        suspension: None,
+                        suspensions: Vec::new(),
+                        join_mode: JoinMode::default(),
                         error: None,
-                        metrics: ExecutionMetrics::new().with_duration(duration),
+                        metrics: ExecutionMetrics::new()
+                            .with_duration(duration)
+                            .with_gas_used(gas_used(self.limits.gas_limit, context.gas_remaining)),
                     }
                 }
             }
+            Err(RuntimeError::GasExhausted { limit, used }) => {
+                self.state = InstanceState::Idle;
+                self.context = None;
+
+                WasmResult::gas_exhausted(
+                    WasmError::gas_exhausted(limit, used),
+                    ExecutionMetrics::new().with_duration(duration).with_gas_used(used),
+                )
+                .with_mutations(context.state_mutations.clone())
+                .with_events(context.events.clone())
+                .with_view_commands(context.view_commands.clone())
+            }
             Err(e) => {
                 self.state = InstanceState::Idle;
                 self.context = None;
 
                 WasmResult::error(e.to_wasm_error(), ExecutionMetrics::new().with_duration(duration))
+                    .with_mutations(context.state_mutations.clone())
+                    .with_events(context.events.clone())
+                    .with_view_commands(context.view_commands.clone())
             }
         };
 
+        drop(context);
+        self.maybe_fire_low_memory_hook();
+
         Ok(wasm_result)
     }
 
@@ -292,16 +782,71 @@ impl WasmInstance {
     pub fn terminate(&mut self) {
         self.state = InstanceState::Terminated;
         self.context = None;
-        self.suspension_id = None;
+        self.suspension_ids.clear();
+    }
+
+    /// Force the instance into the `Executing` state, for pool crash-recovery
+    /// tests that need to simulate a trap/panic mid-handler without driving
+    /// a real (non-yielding) `execute()` call to completion.
+    #[cfg(test)]
+    pub(crate) fn force_executing_for_test(&mut self) {
+        self.state = InstanceState::Executing;
+    }
+
+    /// Force the instance into `Suspended` with `ids` registered as a
+    /// concurrent batch under `join_mode`, for [`Self::resume`] tests that
+    /// need a real suspended instance without a real bytecode interpreter to
+    /// produce one via [`Self::execute`].
+    #[cfg(test)]
+    pub(crate) fn force_suspended_for_test(
+        &mut self,
+        join_mode: JoinMode,
+        ids: &[&str],
+    ) {
+        use crate::context::SuspensionState;
+
+        let mut context = ExecutionContext::from_wasm_context(WasmContext::new("panel-1", "handler"));
+        context.set_join_mode(join_mode);
+        for id in ids {
+            context.add_suspension(SuspensionState {
+                id: id.to_string(),
+                seq: context.next_seq(),
+                extension_name: "http".to_string(),
+                method: "get".to_string(),
+                args: vec![],
+                gas_remaining: 100,
+            });
+        }
+
+        self.context = Some(Arc::new(Mutex::new(context)));
+        self.suspension_ids = ids.iter().map(|id| id.to_string()).collect();
+        self.state = InstanceState::Suspended;
     }
 
     /// Internal execution (simulated)
     ///
-    /// In a real implementation, this would interface with WasmEdge
+    /// In a real implementation, this would interface with WasmEdge.
+    /// The actual work is done by [`Self::execute_sync`], run inside a
+    /// `catch_unwind` here so a panicking handler traps the call instead of
+    /// unwinding through the `SharedContext` lock and aborting the host
+    /// thread.
     async fn execute_internal(
         &mut self,
-        _compiled: &CompiledHandler,
-        _context: &SharedContext,
+        compiled: &CompiledHandler,
+        context: &SharedContext,
+    ) -> Result<Option<RuntimeValue>> {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.execute_sync(compiled, context)
+        })) {
+            Ok(result) => result,
+            Err(payload) => Err(RuntimeError::Panic(crate::error::describe_panic(payload))),
+        }
+    }
+
+    fn execute_sync(
+        &mut self,
+        compiled: &CompiledHandler,
+        context: &SharedContext,
     ) -> Result<Option<RuntimeValue>> {
         // Simulated execution
         // In real implementation:
@@ -312,22 +857,143 @@ impl WasmInstance {
         // 5. Execute bytecode
         // 6. Return result
 
-        // Simulate some memory usage
-        self.memory_used = 1024 * 1024; // 1MB
-        self.memory_peak = 1024 * 1024;
+        #[cfg(test)]
+        tests::panic_if_armed();
+
+        // A cross-process snapshot restored via `restore_memory` already put
+        // this instance in its post-init state; skip re-initializing and
+        // consume the flag so the next execution on this instance runs the
+        // init simulation as usual.
+        if self.primed_memory {
+            self.primed_memory = false;
+        } else {
+            self.simulate_init(compiled)?;
+        }
+
+        // Charge gas as if the handler ran SIMULATED_BLOCK_COUNT basic
+        // blocks, plus the memory-grow cost for the pages just "allocated"
+        // above. A real implementation would charge this from the
+        // `gas(cost)` import injected at each block boundary instead.
+        let grow_pages = self.memory_used / WASM_PAGE_BYTES;
+        let cost = SIMULATED_BLOCK_COUNT * GAS_PER_BLOCK + grow_pages * GAS_PER_MEMORY_PAGE;
+
+        let mut ctx = context.lock();
+        if !ctx.charge_gas(cost) {
+            return Err(RuntimeError::GasExhausted {
+                limit: self.limits.gas_limit,
+                used: gas_used(self.limits.gas_limit, ctx.gas_remaining),
+            });
+        }
 
         Ok(None)
     }
 
-    /// Internal resume (simulated)
-    async fn resume_internal(&mut self, _result: AsyncResult) -> Result<Option<RuntimeValue>> {
+    /// Shared by [`Self::execute_sync`] and [`Self::prime_for_snapshot`]:
+    /// clone starting memory from the handler's shared init image if one
+    /// applies, otherwise simulate fresh initialization, then re-evaluate
+    /// the low-memory condition for the memory that just changed.
+    fn simulate_init(&mut self, compiled: &CompiledHandler) -> Result<()> {
+        match &compiled.init_image {
+            Some(image) if self.memory_shared => {
+                self.clone_from_image(image)?;
+            }
+            _ => {
+                // Simulate some memory usage
+                self.memory_used = 1024 * 1024; // 1MB
+                self.memory_peak = 1024 * 1024;
+                self.linear_memory.resize(self.memory_used as usize, 0);
+            }
+        }
+        self.note_memory_grow();
+
+        Ok(())
+    }
+
+    /// Internal resume (simulated); see [`Self::execute_internal`] for why
+    /// this is split into a panic-catching wrapper around a sync helper.
+    async fn resume_internal(
+        &mut self,
+        results: HashMap<String, AsyncResult>,
+    ) -> Result<Option<RuntimeValue>> {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.resume_sync(results)
+        })) {
+            Ok(result) => result,
+            Err(payload) => Err(RuntimeError::Panic(crate::error::describe_panic(payload))),
+        }
+    }
+
+    fn resume_sync(&mut self, _results: HashMap<String, AsyncResult>) -> Result<Option<RuntimeValue>> {
         // Simulated resume
         // In real implementation:
-        // 1. Inject result into WASM memory
+        // 1. Inject results into WASM memory, keyed by suspension_id
         // 2. Call asyncify_start_rewind
         // 3. Continue execution
+
+        // Continue charging against the leftover gas budget preserved in the
+        // context across the suspension (see `SuspensionDetails::gas_remaining`)
+        // rather than granting a fresh allowance.
+        let context = self
+            .context
+            .as_ref()
+            .ok_or_else(|| RuntimeError::InvalidState("No context".into()))?;
+        let mut ctx = context.lock();
+        let cost = SIMULATED_BLOCK_COUNT * GAS_PER_BLOCK;
+        if !ctx.charge_gas(cost) {
+            return Err(RuntimeError::GasExhausted {
+                limit: self.limits.gas_limit,
+                used: gas_used(self.limits.gas_limit, ctx.gas_remaining),
+            });
+        }
+
         Ok(None)
     }
+
+    /// Re-evaluate the low-memory condition against
+    /// `RuntimeConfig::low_memory_threshold_bytes`, called from the grow path
+    /// (`execute_sync`) whenever `memory_used` changes.
+    ///
+    /// Transitions `ConditionNotSatisfied` -> `Ready` once remaining memory
+    /// (`memory_limit_bytes - memory_used`) drops below the threshold; drops
+    /// straight back to `ConditionNotSatisfied` once usage recovers above it,
+    /// re-arming the episode for next time, regardless of whether the hook
+    /// had fired. A threshold of 0 disables the hook entirely.
+    fn note_memory_grow(&mut self) {
+        if self.config.low_memory_threshold_bytes == 0 {
+            return;
+        }
+
+        let remaining = self
+            .config
+            .memory_limit_bytes
+            .saturating_sub(self.memory_used);
+
+        if remaining < self.config.low_memory_threshold_bytes {
+            if self.low_memory_status == LowMemoryStatus::ConditionNotSatisfied {
+                self.low_memory_status = LowMemoryStatus::Ready;
+            }
+        } else {
+            self.low_memory_status = LowMemoryStatus::ConditionNotSatisfied;
+        }
+    }
+
+    /// Fire `RuntimeConfig::low_memory_hook` if the current episode is
+    /// `Ready`, then mark it `Executed` so it does not fire again until
+    /// `note_memory_grow` re-arms it. A no-op while `Suspended`, so the hook
+    /// never runs inside a suspended critical section; callers resolve this
+    /// naturally by invoking it only once a handler has actually reached a
+    /// resumable point (returned to `Idle` or trapped), never from the
+    /// still-awaiting-siblings branch of `resume`.
+    fn maybe_fire_low_memory_hook(&mut self) {
+        if self.state == InstanceState::Suspended || self.low_memory_status != LowMemoryStatus::Ready {
+            return;
+        }
+
+        if let Some(hook) = self.config.low_memory_hook.clone() {
+            hook.on_low_memory(&self.id, self.memory_used, self.config.memory_limit_bytes);
+        }
+        self.low_memory_status = LowMemoryStatus::Executed;
+    }
 }
 
 impl Drop for WasmInstance {
@@ -341,6 +1007,23 @@ impl Drop for WasmInstance {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell;
+
+    thread_local! {
+        /// Fault-injection switch for `test_execute_catches_panic_and_returns_to_idle`:
+        /// when armed, the next `execute_sync` call panics instead of running
+        /// its simulated body, standing in for a real handler trap since
+        /// there's no real bytecode to make misbehave.
+        static PANIC_ARMED: Cell<bool> = Cell::new(false);
+    }
+
+    /// Panics if a test has armed the fault-injection switch above; a no-op
+    /// otherwise. Called from `execute_sync` under `#[cfg(test)]`.
+    pub(super) fn panic_if_armed() {
+        if PANIC_ARMED.with(|armed| armed.replace(false)) {
+            panic!("simulated handler panic");
+        }
+    }
 
     #[test]
     fn test_instance_creation() {
@@ -377,4 +1060,424 @@ mod tests {
         instance.terminate();
         assert!(instance.reset().is_err());
     }
+
+    fn dummy_compiled() -> CompiledHandler {
+        CompiledHandler {
+            bytecode: vec![],
+            source_map: None,
+            cache_hit: false,
+            init_image: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_succeeds_with_unlimited_gas_by_default() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+
+        let result = instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, ExecutionStatus::Success);
+        assert!(result.metrics.gas_used > 0);
+    }
+
+    #[tokio::test]
+    async fn test_execute_traps_when_gas_budget_is_too_small() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+        instance.set_limits(ResourceLimits {
+            gas_limit: 10,
+            ..ResourceLimits::default()
+        });
+
+        let result = instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, ExecutionStatus::GasExhausted);
+        assert_eq!(result.error.unwrap().code, crate::error::ErrorCode::GasExhausted);
+        // The instance should be free to reuse, not stuck mid-execution
+        assert_eq!(instance.state(), InstanceState::Idle);
+    }
+
+    #[tokio::test]
+    async fn test_execute_catches_panic_and_returns_to_idle() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+        PANIC_ARMED.with(|armed| armed.set(true));
+
+        let result = instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, ExecutionStatus::Error);
+        assert_eq!(
+            result.error.unwrap().code,
+            crate::error::ErrorCode::ExecutionError
+        );
+        // A panicking handler must not leave the instance stuck mid-execution
+        assert_eq!(instance.state(), InstanceState::Idle);
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_ample_gas_reports_usage_below_limit() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+        instance.set_limits(ResourceLimits {
+            gas_limit: 100_000,
+            ..ResourceLimits::default()
+        });
+
+        let result = instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, ExecutionStatus::Success);
+        assert!(result.metrics.gas_used < 100_000);
+    }
+
+    #[test]
+    fn test_snapshot_fails_without_shared_memory() {
+        let config = RuntimeConfig::default();
+        let instance = WasmInstance::new(&config).unwrap();
+
+        assert!(instance.snapshot().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_restore_roundtrip_bookkeeping() {
+        let config = RuntimeConfig::new().with_shared_memory(true);
+        let mut instance = WasmInstance::new(&config).unwrap();
+
+        instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+        assert_eq!(instance.execution_count(), 1);
+        assert_eq!(instance.memory_used(), 1024 * 1024);
+
+        let snapshot = instance.snapshot().unwrap();
+
+        // Run again, advancing the bookkeeping past what was snapshotted
+        instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+        assert_eq!(instance.execution_count(), 2);
+
+        instance.restore(&snapshot).unwrap();
+        assert_eq!(instance.execution_count(), 1);
+        assert_eq!(instance.memory_used(), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_restore_fails_without_shared_memory() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+        let shared_config = RuntimeConfig::new().with_shared_memory(true);
+        let snapshot = WasmInstance::new(&shared_config).unwrap().snapshot().unwrap();
+
+        assert!(instance.restore(&snapshot).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_clone_is_cheap_arc_share() {
+        let config = RuntimeConfig::new().with_shared_memory(true);
+        let instance = WasmInstance::new(&config).unwrap();
+        let snapshot = instance.snapshot().unwrap();
+
+        let forked = snapshot.clone();
+        assert!(Arc::ptr_eq(&snapshot.memory, &forked.memory));
+    }
+
+    #[derive(Debug)]
+    struct CountingLowMemoryHook(std::sync::atomic::AtomicUsize);
+
+    impl crate::config::LowMemoryHook for CountingLowMemoryHook {
+        fn on_low_memory(&self, _instance_id: &str, _used_bytes: u64, _limit_bytes: u64) {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_low_memory_hook_fires_once_per_episode() {
+        let hook = Arc::new(CountingLowMemoryHook(std::sync::atomic::AtomicUsize::new(0)));
+        let config = RuntimeConfig::new()
+            .with_low_memory_threshold_bytes(32 * 1024 * 1024) // execute_sync only simulates 1MB used
+            .with_low_memory_hook(hook.clone());
+        let mut instance = WasmInstance::new(&config).unwrap();
+
+        instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+        assert_eq!(hook.0.load(std::sync::atomic::Ordering::Relaxed), 1);
+        assert_eq!(instance.low_memory_status(), LowMemoryStatus::Executed);
+
+        // Same episode (memory usage hasn't recovered above the threshold):
+        // the hook must not fire again.
+        instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+        assert_eq!(hook.0.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_low_memory_hook_disabled_with_zero_threshold() {
+        let hook = Arc::new(CountingLowMemoryHook(std::sync::atomic::AtomicUsize::new(0)));
+        let config = RuntimeConfig::new().with_low_memory_hook(hook.clone());
+        let mut instance = WasmInstance::new(&config).unwrap();
+
+        instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+
+        assert_eq!(hook.0.load(std::sync::atomic::Ordering::Relaxed), 0);
+        assert_eq!(
+            instance.low_memory_status(),
+            LowMemoryStatus::ConditionNotSatisfied
+        );
+    }
+
+    #[test]
+    fn test_extra_heap_pages_are_pre_grown_at_instantiation() {
+        let config = RuntimeConfig::new().with_extra_heap_pages(4);
+        let instance = WasmInstance::new(&config).unwrap();
+
+        assert_eq!(instance.memory_used(), 4 * WASM_PAGE_BYTES);
+    }
+
+    #[test]
+    fn test_clone_from_image_fails_without_shared_memory() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+        let image_config = RuntimeConfig::new()
+            .with_shared_memory(true)
+            .with_shared_init_image(true);
+        let compiled = super::super::compiler::HandlerCompiler::new(&image_config)
+            .unwrap()
+            .compile("return 1;")
+            .unwrap();
+        let image = compiled.init_image.unwrap();
+
+        assert!(instance.clone_from_image(&image).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute_clones_from_shared_init_image() {
+        let config = RuntimeConfig::new()
+            .with_shared_memory(true)
+            .with_shared_init_image(true);
+        let compiler = super::super::compiler::HandlerCompiler::new(&config).unwrap();
+        let compiled = compiler.compile("return 1;").unwrap();
+        assert!(compiled.init_image.is_some());
+
+        let mut instance = WasmInstance::new(&config).unwrap();
+        let result = instance
+            .execute(&compiled, WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, ExecutionStatus::Success);
+        assert_eq!(
+            instance.memory_used(),
+            compiled.init_image.unwrap().memory_used()
+        );
+    }
+
+    #[test]
+    fn test_capture_memory_fails_without_shared_memory() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+
+        instance.prime_for_snapshot(&dummy_compiled()).unwrap();
+        assert!(instance.capture_memory().is_err());
+    }
+
+    #[test]
+    fn test_restore_memory_fails_without_shared_memory() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+
+        assert!(instance.restore_memory(vec![0u8; 4], 4, 4).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_restore_memory_primes_next_execute_to_skip_init() {
+        let config = RuntimeConfig::new().with_shared_memory(true);
+        let mut instance = WasmInstance::new(&config).unwrap();
+
+        instance.prime_for_snapshot(&dummy_compiled()).unwrap();
+        let (memory, memory_used, memory_peak) = instance.capture_memory().unwrap();
+
+        let mut restored = WasmInstance::new(&config).unwrap();
+        restored
+            .restore_memory(memory.clone(), memory_used, memory_peak)
+            .unwrap();
+
+        let result = restored
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, ExecutionStatus::Success);
+        // The restored bytes survived `execute`'s init simulation instead of
+        // being overwritten by it.
+        assert_eq!(restored.memory_used(), memory_used);
+    }
+
+    #[tokio::test]
+    async fn test_primed_memory_flag_only_consumed_once() {
+        let config = RuntimeConfig::new().with_shared_memory(true);
+        let mut instance = WasmInstance::new(&config).unwrap();
+        instance.restore_memory(vec![0u8; 4], 4, 4).unwrap();
+
+        instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+        assert_eq!(instance.memory_used(), 4);
+
+        // The second execution on the same instance is a normal one, not a
+        // restore, so it runs the usual init simulation.
+        instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+        assert_eq!(instance.memory_used(), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_try_fast_reset_fails_when_disabled() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+
+        assert!(!instance.try_fast_reset());
+    }
+
+    #[tokio::test]
+    async fn test_try_fast_reset_restores_post_init_baseline() {
+        let config = RuntimeConfig::new().with_fast_instance_reuse(true);
+        let mut instance = WasmInstance::new(&config).unwrap();
+
+        instance
+            .execute(&dummy_compiled(), WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+        assert_eq!(instance.memory_used(), 1024 * 1024);
+
+        assert!(instance.try_fast_reset());
+        assert_eq!(instance.state(), InstanceState::Idle);
+        assert_eq!(instance.memory_used(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_try_fast_reset_falls_back_past_dirty_threshold() {
+        let config = RuntimeConfig::new()
+            .with_fast_instance_reuse(true)
+            .with_memory_limit(64 * 1024 * 1024);
+        let mut instance = WasmInstance::new(&config).unwrap();
+
+        // Simulate a dirty set larger than the fast-reuse threshold.
+        instance.memory_used = crate::config::DEFAULT_FAST_REUSE_MAX_DIRTY_BYTES + 1;
+
+        assert!(!instance.try_fast_reset());
+    }
+
+    #[tokio::test]
+    async fn test_resume_under_all_stays_suspended_until_every_call_settles() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+        instance.force_suspended_for_test(JoinMode::All, &["a", "b"]);
+
+        let result = instance
+            .resume("a", AsyncResult::success(RuntimeValue::Null))
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, ExecutionStatus::Suspended);
+        assert_eq!(instance.state(), InstanceState::Suspended);
+        assert!(instance.suspension_ids().contains("b"));
+        assert!(!instance.suspension_ids().contains("a"));
+    }
+
+    #[tokio::test]
+    async fn test_resume_under_all_completes_once_every_call_settles() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+        instance.force_suspended_for_test(JoinMode::All, &["a", "b"]);
+
+        instance
+            .resume("a", AsyncResult::success(RuntimeValue::Null))
+            .await
+            .unwrap();
+        let result = instance
+            .resume("b", AsyncResult::success(RuntimeValue::Null))
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, ExecutionStatus::Success);
+        assert_eq!(instance.state(), InstanceState::Idle);
+    }
+
+    #[tokio::test]
+    async fn test_resume_under_any_completes_on_first_settle_and_abandons_rest() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+        instance.force_suspended_for_test(JoinMode::Any, &["a", "b"]);
+
+        let result = instance
+            .resume("a", AsyncResult::success(RuntimeValue::Null))
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, ExecutionStatus::Success);
+        assert_eq!(instance.state(), InstanceState::Idle);
+        assert!(instance.suspension_ids().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resume_under_all_surfaces_every_failure_once_ready() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+        instance.force_suspended_for_test(JoinMode::All, &["a", "b"]);
+
+        instance
+            .resume("a", AsyncResult::error("timeout"))
+            .await
+            .unwrap();
+        let result = instance
+            .resume("b", AsyncResult::error("connection reset"))
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, ExecutionStatus::Error);
+        assert_eq!(instance.state(), InstanceState::Idle);
+        let error = result.error.unwrap();
+        let context = error.context.unwrap();
+        assert!(context.get("a").is_some());
+        assert!(context.get("b").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_resume_under_all_with_single_successful_call_resumes_the_handler() {
+        let config = RuntimeConfig::default();
+        let mut instance = WasmInstance::new(&config).unwrap();
+        instance.force_suspended_for_test(JoinMode::All, &["a"]);
+
+        let result = instance
+            .resume("a", AsyncResult::success(RuntimeValue::Null))
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, ExecutionStatus::Success);
+    }
 }
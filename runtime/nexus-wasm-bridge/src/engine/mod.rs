@@ -3,20 +3,53 @@
 //! This module provides the core WasmRuntime that manages WASM instance
 //! pooling, compilation caching, and handler execution.
 
+pub mod benchmark;
+pub mod cache_policy;
+pub mod cancellation;
+pub mod chunk_store;
 pub mod compiler;
+pub mod coverage;
 pub mod instance;
 pub mod pool;
+pub mod snapshot;
 
-use crate::config::RuntimeConfig;
-use crate::context::{AsyncResult, WasmContext, WasmResult};
+use crate::config::{ResourceLimits, RuntimeConfig};
+use crate::context::{AsyncResult, ExecutionStatus, WasmContext, WasmResult};
 use crate::error::{Result, RuntimeError, WasmError};
-use crate::metrics::{ExecutionMetrics, ExecutionTimer, MetricsCollector};
+use crate::event_sink::EventSink;
+use crate::host_functions::op_driver::{HostOpFn, HostOpRegistry};
+use cancellation::CancellationRegistry;
+use coverage::{CoverageCollector, CoverageReport};
+use crate::metrics::{ExecutionMetrics, ExecutionTimer, HandlerKey, MetricsCollector, Phase};
 use compiler::HandlerCompiler;
+use futures::stream::{FuturesUnordered, StreamExt};
+use parking_lot::RwLock;
 use pool::InstancePool;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::time::{timeout, Duration};
 use tracing::{debug, error, info, instrument, warn};
 
+/// One handler to run as part of an [`WasmRuntime::execute_batch`] call —
+/// either source code (compiled fresh, or a cache hit) or pre-compiled
+/// bytecode, matching the split between `execute_handler` and
+/// `execute_compiled_handler`.
+pub enum BatchHandler {
+    /// Handler source, compiled the same way `execute_handler` would
+    Source(String),
+    /// Pre-compiled bytecode, run the same way `execute_compiled_handler` would
+    Bytecode(Vec<u8>),
+}
+
+/// A single job in an [`WasmRuntime::execute_batch`] call: a handler plus
+/// its own execution context, independent of every other job in the batch.
+pub struct BatchJob {
+    /// The handler to run
+    pub handler: BatchHandler,
+    /// This job's execution context
+    pub context: WasmContext,
+}
+
 /// The main WASM runtime
 pub struct WasmRuntime {
     /// Runtime configuration
@@ -27,6 +60,17 @@ pub struct WasmRuntime {
     compiler: HandlerCompiler,
     /// Metrics collector
     metrics: Arc<MetricsCollector>,
+    /// Host-side answerers [`Self::execute_handler`] consults before leaving
+    /// a suspension for the caller's manual `resume_handler`; see
+    /// [`Self::register_host_ops`]
+    host_ops: RwLock<HostOpRegistry>,
+    /// Per-handler coverage, recorded on every [`Self::execute_handler`]
+    /// call while enabled via [`Self::start_coverage`]
+    coverage: CoverageCollector,
+    /// Cooperative abort flags for in-flight executions, raced against by
+    /// [`Self::execute_handler`] when called with an execution id from
+    /// [`Self::create_abort_handle`]
+    cancellation: CancellationRegistry,
 }
 
 impl WasmRuntime {
@@ -49,53 +93,264 @@ impl WasmRuntime {
             pool,
             compiler,
             metrics,
+            host_ops: RwLock::new(HostOpRegistry::new()),
+            coverage: CoverageCollector::new(),
+            cancellation: CancellationRegistry::new(),
         })
     }
 
+    /// Mint a fresh execution id that can be passed as `execution_id` into
+    /// [`Self::execute_handler`] and later handed to [`Self::cancel`] to
+    /// abort that specific execution before its timeout elapses.
+    pub fn create_abort_handle(&self) -> String {
+        self.cancellation.create_handle()
+    }
+
+    /// Signal cancellation for a previously-created execution id. Returns
+    /// `false` if `execution_id` isn't currently in flight (already
+    /// finished, or never created).
+    pub fn cancel(&self, execution_id: &str) -> bool {
+        self.cancellation.cancel(execution_id)
+    }
+
+    /// Begin (or continue) recording per-handler coverage on every
+    /// subsequent [`Self::execute_handler`] call; see [`CoverageCollector`]
+    /// for why this can only credit a whole handler invocation rather than
+    /// individual lines. [`Self::execute_compiled_handler`] calls carry no
+    /// source map and are not recorded.
+    pub fn start_coverage(&self) {
+        self.coverage.start();
+    }
+
+    /// Drain coverage accumulated since the last [`Self::take_coverage`] (or
+    /// since [`Self::start_coverage`], if this is the first call) into a
+    /// V8-shaped [`CoverageReport`]. Recording stays enabled.
+    pub fn take_coverage(&self) -> CoverageReport {
+        self.coverage.take()
+    }
+
+    /// Register host-side answerers for extension calls, keyed as
+    /// `"extension.method"` (e.g. `"http.get"`), so [`Self::execute_handler`]
+    /// can resolve a matching suspension inline through its op-driver instead
+    /// of leaving it for the caller's manual `resume_handler`. Ops registered
+    /// here apply to every subsequent `execute_handler` call on this runtime;
+    /// registering the same key again replaces the previous answerer.
+    ///
+    /// Meant to be driven by a one-time `register_host_ops` call from the
+    /// N-API side, wrapping each `ThreadsafeFunction` into a [`HostOpFn`]
+    /// via `call_async`.
+    pub fn register_host_ops(&self, ops: HashMap<String, Arc<HostOpFn>>) {
+        self.host_ops.write().merge(ops);
+    }
+
     /// Execute a handler in WASM sandbox
+    ///
+    /// `execution_id`, if given, is raced via `tokio::select!` against both
+    /// the pool-acquire wait and the execution future itself: a matching
+    /// [`Self::cancel`] call wins the race and this returns `Ok` with
+    /// `status: Error` / `code: Cancelled` instead of waiting out the rest
+    /// of `limits.timeout_ms`. See [`cancellation`] for why this can
+    /// interrupt the *wait*, not the WASM instance mid-instruction.
     #[instrument(skip(self, handler_code, context), fields(panel_id = %context.panel_id, handler = %context.handler_name))]
     pub async fn execute_handler(
         &self,
         handler_code: &str,
         context: WasmContext,
-        timeout_ms: u32,
+        limits: &ResourceLimits,
+        execution_id: Option<&str>,
     ) -> Result<WasmResult> {
-        let timer = ExecutionTimer::start();
+        let mut timer = ExecutionTimer::start();
+        let handler_name = context.handler_name.clone();
+        let handler_key = HandlerKey::new(context.panel_id.clone(), context.handler_name.clone());
+        let cancel_flag = execution_id.map(|id| self.cancellation.handle_for(id));
 
         // Compile handler (may be cached)
         let compiled = self.compiler.compile(handler_code)?;
         let cache_hit = compiled.cache_hit;
 
         debug!(cache_hit = cache_hit, "Handler compiled");
-
-        // Acquire instance from pool
-        let instance = self.pool.acquire().await?;
+        self.coverage.record_execution(&handler_name, compiled.source_map.as_ref());
+
+        // Acquire instance from pool, honoring cancellation while we wait
+        let mut instance = match &cancel_flag {
+            Some(flag) => {
+                tokio::select! {
+                    biased;
+                    _ = flag.cancelled() => {
+                        self.cancellation.remove(execution_id.unwrap());
+                        let metrics = timer.into_metrics(cache_hit);
+                        self.metrics.record_execution(handler_key, &metrics, false);
+                        self.metrics.record_error("CANCELLED");
+                        return Ok(WasmResult::error(WasmError::cancelled(), metrics));
+                    }
+                    acquired = self.pool.acquire() => acquired?,
+                }
+            }
+            None => self.pool.acquire().await?,
+        };
+        instance.set_limits(limits.clone());
 
         debug!("Acquired WASM instance from pool");
 
-        // Execute with timeout
-        let timeout_duration = Duration::from_millis(timeout_ms as u64);
-        let result = timeout(timeout_duration, instance.execute(&compiled, context)).await;
+        // Execute with timeout, racing cancellation alongside it
+        let timeout_duration = Duration::from_millis(limits.timeout_ms as u64);
+        timer.enter(Phase::Execute);
+        let outcome = {
+            let mut exec_fut = instance.execute(&compiled, context);
+            tokio::pin!(exec_fut);
+            match &cancel_flag {
+                Some(flag) => tokio::select! {
+                    biased;
+                    _ = flag.cancelled() => None,
+                    res = timeout(timeout_duration, &mut exec_fut) => Some(res),
+                },
+                None => Some(timeout(timeout_duration, &mut exec_fut).await),
+            }
+        };
+        timer.exit(Phase::Execute);
 
         // Release instance back to pool
+        self.pool.release(instance);
+        if let Some(id) = execution_id {
+            self.cancellation.remove(id);
+        }
+
+        // Handle cancellation/timeout
+        let wasm_result = match outcome {
+            None => {
+                let metrics = timer.into_metrics(cache_hit);
+                self.metrics.record_execution(handler_key, &metrics, false);
+                self.metrics.record_error("CANCELLED");
+                return Ok(WasmResult::error(WasmError::cancelled(), metrics));
+            }
+            Some(Ok(Ok(result))) => result,
+            Some(Ok(Err(e))) => {
+                let metrics = timer.into_metrics(cache_hit);
+                self.metrics.record_execution(handler_key, &metrics, false);
+                self.metrics.record_error(&e.to_wasm_error().code.to_string());
+                return Err(e);
+            }
+            Some(Err(_)) => {
+                warn!(timeout_ms = limits.timeout_ms, "Handler execution timed out");
+                let metrics = timer.into_metrics(cache_hit);
+                self.metrics.record_execution(handler_key, &metrics, false);
+                self.metrics.record_error("TIMEOUT");
+                return Ok(WasmResult::error(
+                    WasmError::timeout(limits.timeout_ms),
+                    metrics,
+                ));
+            }
+        };
+
+        let wasm_result = self.drive_suspensions_with_ops(wasm_result).await?;
+
+        let metrics = timer.into_metrics(cache_hit);
+        let success = wasm_result.error.is_none();
+        self.metrics.record_execution(handler_key, &metrics, success);
+
+        Ok(wasm_result)
+    }
+
+    /// Resolve as many of `wasm_result`'s pending suspensions as
+    /// [`Self::register_host_ops`] covers, inline, before handing the result
+    /// back to the caller — the op-driver fast path described on
+    /// [`Self::register_host_ops`].
+    ///
+    /// Each pass collects every suspension that has a registered op and
+    /// drives them concurrently via a local `FuturesUnordered`, resuming
+    /// through [`Self::resume_handler`] as each settles (which may itself
+    /// produce fresh suspensions the handler registered once unblocked, so
+    /// this loops rather than running a single pass). Stops and returns as
+    /// soon as a pass finds a suspension with no matching op, leaving it
+    /// (and anything still pending) for the caller's manual `resume_handler`.
+    /// A no-op if `wasm_result` isn't `Suspended` or no ops are registered.
+    async fn drive_suspensions_with_ops(&self, mut wasm_result: WasmResult) -> Result<WasmResult> {
+        while wasm_result.status == ExecutionStatus::Suspended && !wasm_result.suspensions.is_empty() {
+            let mut driven = FuturesUnordered::new();
+            let mut any_uncovered = false;
+            {
+                let registry = self.host_ops.read();
+                for suspension in &wasm_result.suspensions {
+                    match registry.get(&suspension.extension_name, &suspension.method) {
+                        Some(op) => {
+                            let suspension_id = suspension.suspension_id.clone();
+                            let args = suspension.args.clone();
+                            driven.push(async move { (suspension_id, op(args).await) });
+                        }
+                        None => any_uncovered = true,
+                    }
+                }
+            }
+
+            if driven.is_empty() {
+                break;
+            }
+
+            while let Some((suspension_id, outcome)) = driven.next().await {
+                let async_result = match outcome {
+                    Ok(value) => AsyncResult::success(value),
+                    Err(e) => AsyncResult::error(e.message),
+                };
+                wasm_result = self.resume_handler(&suspension_id, async_result).await?;
+                if wasm_result.status != ExecutionStatus::Suspended {
+                    break;
+                }
+            }
+
+            if any_uncovered {
+                break;
+            }
+        }
+
+        Ok(wasm_result)
+    }
+
+    /// Like [`Self::execute_handler`], but attaches `sink` to the execution
+    /// so every event, view command, and state mutation is forwarded to it
+    /// the moment it's recorded, instead of only being visible in the
+    /// returned [`WasmResult`] once the handler finishes. Meant for callers
+    /// (like the N-API `execute_handler_streaming` binding) that want to
+    /// start rendering/forwarding output before execution completes.
+    #[instrument(skip(self, handler_code, context, sink), fields(panel_id = %context.panel_id, handler = %context.handler_name))]
+    pub async fn execute_handler_streaming(
+        &self,
+        handler_code: &str,
+        context: WasmContext,
+        limits: &ResourceLimits,
+        sink: Arc<dyn EventSink>,
+    ) -> Result<WasmResult> {
+        let mut timer = ExecutionTimer::start();
+        let handler_key = HandlerKey::new(context.panel_id.clone(), context.handler_name.clone());
+
+        let compiled = self.compiler.compile(handler_code)?;
+        let cache_hit = compiled.cache_hit;
+
+        let mut instance = self.pool.acquire().await?;
+        instance.set_limits(limits.clone());
+        instance.set_event_sink(Some(sink));
+
+        let timeout_duration = Duration::from_millis(limits.timeout_ms as u64);
+        timer.enter(Phase::Execute);
+        let result = timeout(timeout_duration, instance.execute(&compiled, context)).await;
+        timer.exit(Phase::Execute);
+
         self.pool.release(instance);
 
-        // Handle timeout
         let wasm_result = match result {
             Ok(Ok(result)) => result,
             Ok(Err(e)) => {
                 let metrics = timer.into_metrics(cache_hit);
-                self.metrics.record_execution(&metrics, false);
+                self.metrics.record_execution(handler_key, &metrics, false);
                 self.metrics.record_error(&e.to_wasm_error().code.to_string());
                 return Err(e);
             }
             Err(_) => {
-                warn!(timeout_ms = timeout_ms, "Handler execution timed out");
+                warn!(timeout_ms = limits.timeout_ms, "Streaming handler execution timed out");
                 let metrics = timer.into_metrics(cache_hit);
-                self.metrics.record_execution(&metrics, false);
+                self.metrics.record_execution(handler_key, &metrics, false);
                 self.metrics.record_error("TIMEOUT");
                 return Ok(WasmResult::error(
-                    WasmError::timeout(timeout_ms),
+                    WasmError::timeout(limits.timeout_ms),
                     metrics,
                 ));
             }
@@ -103,7 +358,7 @@ impl WasmRuntime {
 
         let metrics = timer.into_metrics(cache_hit);
         let success = wasm_result.error.is_none();
-        self.metrics.record_execution(&metrics, success);
+        self.metrics.record_execution(handler_key, &metrics, success);
 
         Ok(wasm_result)
     }
@@ -115,51 +370,172 @@ impl WasmRuntime {
         Ok(compiled.bytecode)
     }
 
-    /// Execute pre-compiled handler bytecode
+    /// Execute pre-compiled handler bytecode. `execution_id` behaves exactly
+    /// as it does on [`Self::execute_handler`].
     #[instrument(skip(self, bytecode, context), fields(panel_id = %context.panel_id, handler = %context.handler_name))]
     pub async fn execute_compiled_handler(
         &self,
         bytecode: &[u8],
         context: WasmContext,
-        timeout_ms: u32,
+        limits: &ResourceLimits,
+        execution_id: Option<&str>,
     ) -> Result<WasmResult> {
-        let timer = ExecutionTimer::start();
+        let mut timer = ExecutionTimer::start();
+        let handler_key = HandlerKey::new(context.panel_id.clone(), context.handler_name.clone());
+        let cancel_flag = execution_id.map(|id| self.cancellation.handle_for(id));
 
         // Create compiled handler from bytecode
         let compiled = compiler::CompiledHandler {
             bytecode: bytecode.to_vec(),
             source_map: None,
             cache_hit: true, // Pre-compiled is always a "cache hit"
+            init_image: None,
+        };
+
+        // No source map travels with raw bytecode, so there's nothing to
+        // credit a coverage hit against here; see `CoverageCollector`'s doc
+        // comment.
+        self.coverage.record_execution(&context.handler_name, compiled.source_map.as_ref());
+
+        // Acquire instance from pool, honoring cancellation while we wait
+        let mut instance = match &cancel_flag {
+            Some(flag) => {
+                tokio::select! {
+                    biased;
+                    _ = flag.cancelled() => {
+                        self.cancellation.remove(execution_id.unwrap());
+                        let metrics = timer.into_metrics(true);
+                        self.metrics.record_execution(handler_key, &metrics, false);
+                        return Ok(WasmResult::error(WasmError::cancelled(), metrics));
+                    }
+                    acquired = self.pool.acquire() => acquired?,
+                }
+            }
+            None => self.pool.acquire().await?,
         };
+        instance.set_limits(limits.clone());
+
+        // Execute with timeout, racing cancellation alongside it
+        let timeout_duration = Duration::from_millis(limits.timeout_ms as u64);
+        timer.enter(Phase::Execute);
+        let outcome = {
+            let mut exec_fut = instance.execute(&compiled, context);
+            tokio::pin!(exec_fut);
+            match &cancel_flag {
+                Some(flag) => tokio::select! {
+                    biased;
+                    _ = flag.cancelled() => None,
+                    res = timeout(timeout_duration, &mut exec_fut) => Some(res),
+                },
+                None => Some(timeout(timeout_duration, &mut exec_fut).await),
+            }
+        };
+        timer.exit(Phase::Execute);
 
-        // Acquire instance from pool
-        let instance = self.pool.acquire().await?;
+        // Release instance back to pool
+        self.pool.release(instance);
+        if let Some(id) = execution_id {
+            self.cancellation.remove(id);
+        }
+
+        // Handle cancellation/timeout
+        let wasm_result = match outcome {
+            None => {
+                let metrics = timer.into_metrics(true);
+                self.metrics.record_execution(handler_key, &metrics, false);
+                return Ok(WasmResult::error(WasmError::cancelled(), metrics));
+            }
+            Some(Ok(Ok(result))) => result,
+            Some(Ok(Err(e))) => {
+                let metrics = timer.into_metrics(true);
+                self.metrics.record_execution(handler_key, &metrics, false);
+                return Err(e);
+            }
+            Some(Err(_)) => {
+                let metrics = timer.into_metrics(true);
+                self.metrics.record_execution(handler_key, &metrics, false);
+                return Ok(WasmResult::error(WasmError::timeout(limits.timeout_ms), metrics));
+            }
+        };
 
-        // Execute with timeout
-        let timeout_duration = Duration::from_millis(timeout_ms as u64);
+        let metrics = timer.into_metrics(true);
+        let success = wasm_result.error.is_none();
+        self.metrics.record_execution(handler_key, &metrics, success);
+
+        Ok(wasm_result)
+    }
+
+    /// Capture a freshly-compiled handler's post-init linear memory into a
+    /// self-contained, versioned buffer (see [`snapshot::encode`]) that can
+    /// be persisted across processes and later handed to
+    /// [`Self::restore_from_snapshot`], skipping both compilation and
+    /// initialization on restore.
+    ///
+    /// Requires `RuntimeConfig::enable_shared_memory`, the same precondition
+    /// as `WasmInstance::snapshot`/`clone_from_image`, since only shared
+    /// linear memory can be cheaply and safely duplicated out of the
+    /// instance.
+    #[instrument(skip(self, handler_code))]
+    pub async fn snapshot_instance(&self, handler_code: &str) -> Result<Vec<u8>> {
+        let compiled = self.compiler.compile(handler_code)?;
+
+        let mut instance = self.pool.acquire().await?;
+        let captured = instance
+            .prime_for_snapshot(&compiled)
+            .and_then(|_| instance.capture_memory());
+        self.pool.release(instance);
+
+        let (memory, memory_used, memory_peak) = captured?;
+        snapshot::encode(&compiled, memory, memory_used, memory_peak)
+    }
+
+    /// Restore a buffer previously produced by [`Self::snapshot_instance`]
+    /// into a freshly acquired instance and execute it, skipping both
+    /// compilation and initialization — the reverse of `snapshot_instance`.
+    #[instrument(skip(self, snapshot, context), fields(panel_id = %context.panel_id, handler = %context.handler_name))]
+    pub async fn restore_from_snapshot(
+        &self,
+        snapshot: &[u8],
+        context: WasmContext,
+        limits: &ResourceLimits,
+    ) -> Result<WasmResult> {
+        let (compiled, memory, memory_used, memory_peak) = snapshot::decode(snapshot)?;
+
+        let mut timer = ExecutionTimer::start();
+        let handler_key = HandlerKey::new(context.panel_id.clone(), context.handler_name.clone());
+
+        let mut instance = self.pool.acquire().await?;
+        instance.set_limits(limits.clone());
+        if let Err(e) = instance.restore_memory(memory, memory_used, memory_peak) {
+            self.pool.release(instance);
+            return Err(e);
+        }
+
+        let timeout_duration = Duration::from_millis(limits.timeout_ms as u64);
+        timer.enter(Phase::Execute);
         let result = timeout(timeout_duration, instance.execute(&compiled, context)).await;
+        timer.exit(Phase::Execute);
 
-        // Release instance back to pool
         self.pool.release(instance);
 
-        // Handle timeout
         let wasm_result = match result {
             Ok(Ok(result)) => result,
             Ok(Err(e)) => {
                 let metrics = timer.into_metrics(true);
-                self.metrics.record_execution(&metrics, false);
+                self.metrics.record_execution(handler_key, &metrics, false);
                 return Err(e);
             }
             Err(_) => {
+                warn!(timeout_ms = limits.timeout_ms, "Restored handler execution timed out");
                 let metrics = timer.into_metrics(true);
-                self.metrics.record_execution(&metrics, false);
-                return Ok(WasmResult::error(WasmError::timeout(timeout_ms), metrics));
+                self.metrics.record_execution(handler_key, &metrics, false);
+                return Ok(WasmResult::error(WasmError::timeout(limits.timeout_ms), metrics));
             }
         };
 
         let metrics = timer.into_metrics(true);
         let success = wasm_result.error.is_none();
-        self.metrics.record_execution(&metrics, success);
+        self.metrics.record_execution(handler_key, &metrics, success);
 
         Ok(wasm_result)
     }
@@ -177,22 +553,69 @@ impl WasmRuntime {
         );
 
         // Get the suspended instance from the pool
-        let instance = self
+        let mut instance = self
             .pool
             .get_suspended(suspension_id)
             .ok_or_else(|| RuntimeError::Suspension("Suspension not found".into()))?;
 
         // Resume execution
-        let timer = ExecutionTimer::start();
-        let wasm_result = instance.resume(result).await?;
+        let handler_key = instance
+            .handler_identity()
+            .map(|(panel_id, handler_name)| HandlerKey::new(panel_id, handler_name))
+            .unwrap_or_else(|| HandlerKey::new("unknown", "unknown"));
+
+        let mut timer = ExecutionTimer::start();
+        timer.enter(Phase::Execute);
+        let wasm_result = instance.resume(suspension_id, result).await?;
+        timer.exit(Phase::Execute);
         let metrics = timer.into_metrics(true);
 
         let success = wasm_result.error.is_none();
-        self.metrics.record_execution(&metrics, success);
+        self.metrics.record_execution(handler_key, &metrics, success);
 
         Ok(wasm_result)
     }
 
+    /// Run many independent handlers concurrently across the instance pool
+    /// via a `FuturesUnordered`, instead of the caller awaiting a separate
+    /// `execute_handler`/`execute_compiled_handler` per job — lets the
+    /// pool's warm `min_instances`/`max_instances` set get saturated in one
+    /// call, and amortizes the N-API boundary crossing when a kernel needs
+    /// to fan out many small handlers at once (e.g. re-rendering a list of
+    /// panels). `results[i]` corresponds to `jobs[i]`, regardless of which
+    /// order jobs actually finish in.
+    ///
+    /// Each job is isolated: a handler erroring, timing out, or (for
+    /// `BatchHandler::Source`) failing to compile yields its own error
+    /// `WasmResult` rather than aborting the rest of the batch. `limits`
+    /// applies uniformly to every job; none of them participate in
+    /// cancellation (batch jobs don't take an `execution_id`).
+    #[instrument(skip(self, jobs))]
+    pub async fn execute_batch(&self, jobs: Vec<BatchJob>, limits: &ResourceLimits) -> Vec<WasmResult> {
+        let total = jobs.len();
+        let mut driven = FuturesUnordered::new();
+        for (index, job) in jobs.into_iter().enumerate() {
+            driven.push(async move {
+                let result = match job.handler {
+                    BatchHandler::Source(code) => {
+                        self.execute_handler(&code, job.context, limits, None).await
+                    }
+                    BatchHandler::Bytecode(bytecode) => {
+                        self.execute_compiled_handler(&bytecode, job.context, limits, None).await
+                    }
+                };
+                (index, result.unwrap_or_else(|e| WasmResult::error(e.to_wasm_error(), ExecutionMetrics::default())))
+            });
+        }
+
+        let mut slots: Vec<Option<WasmResult>> = (0..total).map(|_| None).collect();
+        while let Some((index, result)) = driven.next().await {
+            slots[index] = Some(result);
+        }
+
+        slots.into_iter().map(|r| r.expect("every batch index is filled exactly once")).collect()
+    }
+
     /// Get runtime statistics
     pub fn get_stats(&self) -> crate::metrics::RuntimeStats {
         crate::metrics::RuntimeStats {
@@ -200,13 +623,19 @@ impl WasmRuntime {
             active_instances: self.pool.active_count(),
             available_instances: self.pool.available_count(),
             cache_hit_rate: self.metrics.cache_hit_rate(),
+            fast_reuse_hit_rate: self.pool.fast_reuse_hit_rate(),
             avg_execution_time_us: self.metrics.avg_execution_time_us(),
             total_memory_bytes: self.pool.total_memory(),
+            low_memory_pending_instances: self.pool.low_memory_pending_count(),
+            memory_model: self.config.memory_model(),
         }
     }
 
     /// Get Prometheus metrics
     pub fn get_prometheus_metrics(&self) -> String {
+        let compiler_stats = self.compiler.get_stats();
+        self.metrics
+            .update_cache_stats(compiler_stats.cache_evictions, compiler_stats.cache_size_bytes);
         self.metrics.to_prometheus()
     }
 
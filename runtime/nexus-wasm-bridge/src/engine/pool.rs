@@ -10,26 +10,69 @@ use parking_lot::{Mutex, RwLock};
 use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::Semaphore;
+use std::time::Instant;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tracing::{debug, info, warn};
 
 /// Instance wrapper for pool management
+///
+/// Holds an [`OwnedSemaphorePermit`] so that capacity is always restored when
+/// the guard is dropped, whether or not the caller calls [`InstancePool::release`]
+/// explicitly. This makes leaking a `PooledInstance` (e.g. via an early return or
+/// a panicking caller) safe instead of permanently stranding pool capacity.
 pub struct PooledInstance {
     /// The actual WASM instance
-    pub instance: WasmInstance,
+    instance: Option<WasmInstance>,
     /// Pool reference for release
     pool: Arc<InstancePoolInner>,
+    /// Owned permit restoring semaphore capacity on drop
+    permit: Option<OwnedSemaphorePermit>,
 }
 
 impl PooledInstance {
+    fn new(instance: WasmInstance, pool: Arc<InstancePoolInner>, permit: OwnedSemaphorePermit) -> Self {
+        Self {
+            instance: Some(instance),
+            pool,
+            permit: Some(permit),
+        }
+    }
+
     /// Get a reference to the instance
     pub fn inner(&self) -> &WasmInstance {
-        &self.instance
+        self.instance.as_ref().expect("instance taken")
     }
 
     /// Get a mutable reference to the instance
     pub fn inner_mut(&mut self) -> &mut WasmInstance {
-        &mut self.instance
+        self.instance.as_mut().expect("instance taken")
+    }
+
+    /// Release this instance back to the pool immediately.
+    ///
+    /// Equivalent to dropping the guard, but lets callers return capacity
+    /// eagerly instead of waiting for scope exit.
+    pub fn release(mut self) {
+        self.release_inner();
+    }
+
+    /// Shared teardown logic used by both eager `release` and `Drop`.
+    fn release_inner(&mut self) {
+        let Some(instance) = self.instance.take() else {
+            return;
+        };
+
+        if let Some(permit) = self.permit.take() {
+            if self.pool.try_consume_shrink() {
+                // The pool was resized smaller while this instance was
+                // checked out; forgetting the permit permanently removes it
+                // from the semaphore instead of returning it.
+                permit.forget();
+            }
+            // Otherwise the permit is dropped here, restoring capacity.
+        }
+
+        self.pool.finish_release(instance);
     }
 }
 
@@ -37,13 +80,19 @@ impl std::ops::Deref for PooledInstance {
     type Target = WasmInstance;
 
     fn deref(&self) -> &Self::Target {
-        &self.instance
+        self.inner()
     }
 }
 
 impl std::ops::DerefMut for PooledInstance {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.instance
+        self.inner_mut()
+    }
+}
+
+impl Drop for PooledInstance {
+    fn drop(&mut self) {
+        self.release_inner();
     }
 }
 
@@ -53,18 +102,347 @@ struct InstancePoolInner {
     config: RuntimeConfig,
     /// Available instances (LIFO for cache locality)
     available: Mutex<VecDeque<WasmInstance>>,
-    /// Suspended instances by suspension ID
-    suspended: RwLock<HashMap<String, WasmInstance>>,
+    /// Suspended instances by instance ID. An instance stays here as long as
+    /// it has at least one pending extension call (see
+    /// [`WasmInstance::suspension_ids`]); `suspension_index` maps each of
+    /// those ids back to the instance holding it.
+    suspended: RwLock<HashMap<InstanceId, WasmInstance>>,
+    /// Maps a pending suspension ID to the instance awaiting it, so
+    /// [`InstancePoolInner::get_suspended`] can look an instance up by any
+    /// one of several concurrent suspension ids it may be holding
+    suspension_index: RwLock<HashMap<String, InstanceId>>,
     /// Semaphore to limit concurrent instances
-    semaphore: Semaphore,
+    semaphore: Arc<Semaphore>,
+    /// Current configured maximum instance count, mutable via `resize`
+    max_instances: AtomicUsize,
+    /// Permits still owed back to the semaphore from a `resize` shrink that
+    /// could not be satisfied immediately; consumed by `try_consume_shrink`
+    /// as active instances are released
+    pending_shrink: AtomicUsize,
     /// Total instances created
     instances_created: AtomicUsize,
     /// Active (checked out) instances
     active_count: AtomicUsize,
+    /// Tasks currently blocked waiting for a permit in `acquire`
+    waiting_count: AtomicUsize,
     /// Total memory used by pool
     total_memory: AtomicU64,
+    /// Number of instances restarted after crashing (released while still
+    /// `Executing`, or failing `reset()`)
+    restart_count: AtomicUsize,
+    /// Releases reused in place via `WasmInstance::try_fast_reset` (see
+    /// `RuntimeConfig::fast_instance_reuse`)
+    fast_reuse_hits: AtomicUsize,
+    /// Releases that fell back to a full `reset()` despite
+    /// `fast_instance_reuse` being enabled, because the dirty set exceeded
+    /// `DEFAULT_FAST_REUSE_MAX_DIRTY_BYTES`
+    fast_reuse_misses: AtomicUsize,
+    /// Set once `restart_count` trips the `max_restarts` circuit breaker;
+    /// `acquire` refuses new checkouts while this is true
+    degraded: std::sync::atomic::AtomicBool,
     /// Shutdown flag
     shutdown: RwLock<bool>,
+    /// Background supervisor task handle, stopped on `shutdown`
+    supervisor: Mutex<Option<tokio::task::JoinHandle<()>>>,
+}
+
+impl InstancePoolInner {
+    /// Return an instance to its proper home (available/suspended/terminated)
+    /// and adjust bookkeeping. Called once per `PooledInstance`, either eagerly
+    /// via [`PooledInstance::release`] or automatically on drop.
+    fn finish_release(&self, mut instance: WasmInstance) {
+        self.active_count.fetch_sub(1, Ordering::Relaxed);
+        self.total_memory.fetch_sub(instance.memory_used(), Ordering::Relaxed);
+
+        match instance.state() {
+            InstanceState::Idle => {
+                let fast_reused = self.config.fast_instance_reuse && instance.try_fast_reset();
+                if self.config.fast_instance_reuse {
+                    if fast_reused {
+                        self.fast_reuse_hits.fetch_add(1, Ordering::Relaxed);
+                    } else {
+                        self.fast_reuse_misses.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+
+                if fast_reused || instance.reset().is_ok() {
+                    instance.mark_idle();
+                    let mut available = self.available.lock();
+                    available.push_back(instance);
+                    debug!(fast_reused, "Returned instance to pool");
+                } else {
+                    debug!("Instance reset failed, dropping");
+                    instance.terminate();
+                    self.record_crash_and_replace();
+                }
+            }
+            InstanceState::Suspended => {
+                if !instance.suspension_ids().is_empty() {
+                    let instance_id = instance.id().to_string();
+                    let mut index = self.suspension_index.write();
+                    for suspension_id in instance.suspension_ids() {
+                        index.insert(suspension_id.clone(), instance_id.clone());
+                    }
+                    drop(index);
+
+                    debug!(
+                        id = %instance_id,
+                        pending = instance.suspension_ids().len(),
+                        "Moved instance to suspended"
+                    );
+                    self.suspended.write().insert(instance_id, instance);
+                } else {
+                    warn!("Suspended instance has no pending suspension IDs");
+                    instance.terminate();
+                }
+            }
+            InstanceState::Executing => {
+                warn!("Releasing executing instance - treating as a crash");
+                instance.terminate();
+                self.record_crash_and_replace();
+            }
+            InstanceState::Terminated => {
+                debug!("Dropping terminated instance");
+            }
+        }
+    }
+
+    /// Record an instance crash (released mid-`Executing`, or failing
+    /// `reset()`), trip the `max_restarts` circuit breaker if the pool has
+    /// crashed too often, and otherwise eagerly construct a replacement so
+    /// `available` doesn't drift below `min_instances`.
+    fn record_crash_and_replace(&self) {
+        let count = self.restart_count.fetch_add(1, Ordering::SeqCst) + 1;
+        warn!(restart_count = count, "Instance crashed, supervising restart");
+
+        if self.config.max_restarts > 0 && count as u32 >= self.config.max_restarts {
+            if !self.degraded.swap(true, Ordering::SeqCst) {
+                warn!(
+                    max_restarts = self.config.max_restarts,
+                    "Instance pool tripped restart circuit breaker, entering degraded state"
+                );
+            }
+            return;
+        }
+
+        let min_instances = self.config.min_instances.unwrap_or(1) as usize;
+        let mut available = self.available.lock();
+        if available.len() < min_instances {
+            match WasmInstance::new(&self.config) {
+                Ok(replacement) => {
+                    self.instances_created.fetch_add(1, Ordering::Relaxed);
+                    available.push_back(replacement);
+                    debug!("Constructed replacement instance after crash");
+                }
+                Err(e) => {
+                    warn!("Failed to construct replacement instance after crash: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Periodic supervision pass run by the background supervisor task:
+    /// evicts stale instances just like `cleanup_stale`, plus whatever other
+    /// health checks the supervisor accumulates over time.
+    fn supervise_tick(&self) {
+        self.cleanup_stale();
+    }
+
+    /// Evict instances that have exceeded the configured recycling policy
+    /// (max lifetime, max idle time, or max reuse count).
+    ///
+    /// Evicted instances are not counted in `total_memory` since idle and
+    /// suspended instances are only added to that counter while checked out
+    /// (see [`InstancePoolInner::finish_release`]), so no adjustment is
+    /// needed here beyond terminating them and dropping them from their map.
+    fn cleanup_stale(&self) {
+        let mut evicted = 0usize;
+
+        {
+            let mut available = self.available.lock();
+            let before = available.len();
+            available.retain_mut(|instance| {
+                if self.should_recycle(instance) {
+                    instance.terminate();
+                    false
+                } else {
+                    true
+                }
+            });
+            evicted += before - available.len();
+        }
+
+        {
+            // Lock order is `suspension_index` then `suspended` throughout
+            // this module (see `get_suspended`), to avoid a lock-ordering
+            // deadlock between this pass and a concurrent resume.
+            let mut index = self.suspension_index.write();
+            let mut suspended = self.suspended.write();
+            let before = suspended.len();
+            suspended.retain(|id, instance| {
+                if self.should_recycle(instance) {
+                    debug!(id = %id, "Recycling stale suspended instance");
+                    for suspension_id in instance.suspension_ids() {
+                        index.remove(suspension_id);
+                    }
+                    instance.terminate();
+                    false
+                } else {
+                    true
+                }
+            });
+            evicted += before - suspended.len();
+        }
+
+        if evicted > 0 {
+            debug!(evicted, "Recycled stale pooled instances");
+        }
+    }
+
+    /// Whether a pooled instance has exceeded its configured recycling
+    /// policy and should be terminated instead of handed out again.
+    ///
+    /// A limit of `0` for any of the three policy fields means "unlimited"
+    /// for that dimension.
+    fn should_recycle(&self, instance: &WasmInstance) -> bool {
+        let now = Instant::now();
+
+        if self.config.max_instance_lifetime_secs > 0
+            && now.duration_since(instance.created_at()).as_secs()
+                >= self.config.max_instance_lifetime_secs
+        {
+            return true;
+        }
+
+        if self.config.max_idle_time_secs > 0 {
+            if let Some(idle_since) = instance.idle_since() {
+                if now.duration_since(idle_since).as_secs() >= self.config.max_idle_time_secs {
+                    return true;
+                }
+            }
+        }
+
+        if self.config.max_reuses > 0 && instance.execution_count() >= self.config.max_reuses as u64
+        {
+            return true;
+        }
+
+        false
+    }
+
+    /// Turn a freshly-acquired semaphore permit into a checked-out
+    /// `PooledInstance`, reusing an idle instance if one is available and
+    /// not expired, or creating a new one otherwise. Shared by `acquire`
+    /// and `acquire_timeout`.
+    fn instance_for_permit(
+        self: &Arc<Self>,
+        permit: OwnedSemaphorePermit,
+    ) -> Result<PooledInstance> {
+        // Try to get an existing instance, skipping (and terminating) any
+        // that have exceeded the configured recycling policy
+        let instance = {
+            let mut available = self.available.lock();
+            let mut found = None;
+            while let Some(mut candidate) = available.pop_back() {
+                // LIFO for better cache locality
+                if self.should_recycle(&candidate) {
+                    debug!(id = %candidate.id(), "Recycling expired pooled instance");
+                    candidate.terminate();
+                    continue;
+                }
+                found = Some(candidate);
+                break;
+            }
+            found
+        };
+
+        let instance = match instance {
+            Some(mut inst) => {
+                // Reset the instance for reuse
+                inst.reset()?;
+                debug!(id = %inst.id(), "Reusing pooled instance");
+                inst
+            }
+            None => {
+                // Create new instance
+                let inst = WasmInstance::new(&self.config)?;
+                self.instances_created.fetch_add(1, Ordering::Relaxed);
+                debug!(id = %inst.id(), "Created new instance");
+                inst
+            }
+        };
+
+        self.active_count.fetch_add(1, Ordering::Relaxed);
+        self.total_memory
+            .fetch_add(instance.memory_used(), Ordering::Relaxed);
+
+        Ok(PooledInstance::new(instance, Arc::clone(self), permit))
+    }
+
+    /// Resize the pool's capacity in place, without recreating the semaphore
+    /// or any existing instances.
+    fn resize(&self, new_max: usize) {
+        let old_max = self.max_instances.swap(new_max, Ordering::SeqCst);
+
+        match new_max.cmp(&old_max) {
+            std::cmp::Ordering::Equal => {}
+            std::cmp::Ordering::Greater => {
+                // Growing never blocks: just hand out more permits.
+                let delta = new_max - old_max;
+                self.semaphore.add_permits(delta);
+                info!(old_max, new_max, "Grew instance pool");
+            }
+            std::cmp::Ordering::Less => {
+                let delta = old_max - new_max;
+
+                // Shed idle instances first; no point holding onto ones we
+                // no longer have capacity for.
+                {
+                    let mut available = self.available.lock();
+                    let to_drain = delta.min(available.len());
+                    for _ in 0..to_drain {
+                        if let Some(mut instance) = available.pop_front() {
+                            instance.terminate();
+                        }
+                    }
+                }
+
+                // Reclaim as much capacity as is sitting idle in the
+                // semaphore right now, without blocking.
+                let mut remaining = delta;
+                while remaining > 0 {
+                    match Arc::clone(&self.semaphore).try_acquire_owned() {
+                        Ok(permit) => {
+                            permit.forget();
+                            remaining -= 1;
+                        }
+                        Err(_) => break,
+                    }
+                }
+
+                // Anything still checked out by active instances is
+                // reclaimed lazily as those instances are released (see
+                // `try_consume_shrink`), so shrinking below the current
+                // active count is tolerated rather than blocking here.
+                if remaining > 0 {
+                    self.pending_shrink.fetch_add(remaining, Ordering::SeqCst);
+                }
+
+                info!(old_max, new_max, "Shrinking instance pool");
+            }
+        }
+    }
+
+    /// Consume one unit of pending shrinkage, if any remains. Returns `true`
+    /// if the caller should forget its semaphore permit instead of letting
+    /// it restore capacity normally.
+    fn try_consume_shrink(&self) -> bool {
+        self.pending_shrink
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                n.checked_sub(1)
+            })
+            .is_ok()
+    }
 }
 
 /// WASM instance pool
@@ -86,15 +464,32 @@ impl InstancePool {
             config: config.clone(),
             available: Mutex::new(VecDeque::with_capacity(max_instances)),
             suspended: RwLock::new(HashMap::new()),
-            semaphore: Semaphore::new(max_instances),
+            suspension_index: RwLock::new(HashMap::new()),
+            semaphore: Arc::new(Semaphore::new(max_instances)),
+            max_instances: AtomicUsize::new(max_instances),
+            pending_shrink: AtomicUsize::new(0),
             instances_created: AtomicUsize::new(0),
             active_count: AtomicUsize::new(0),
+            waiting_count: AtomicUsize::new(0),
             total_memory: AtomicU64::new(0),
+            restart_count: AtomicUsize::new(0),
+            fast_reuse_hits: AtomicUsize::new(0),
+            fast_reuse_misses: AtomicUsize::new(0),
+            degraded: std::sync::atomic::AtomicBool::new(false),
             shutdown: RwLock::new(false),
+            supervisor: Mutex::new(None),
         });
 
-        // Pre-warm pool with minimum instances
-        let min_instances = config.min_instances.unwrap_or(1) as usize;
+        // Pre-warm pool with minimum instances. In `Pooling` mode the whole
+        // `max_instances` slab is preallocated up front, trading startup
+        // cost for `acquire` never paying per-call allocation cost; see
+        // `crate::config::PoolingStrategy`.
+        let min_instances = if config.pooling_strategy == crate::config::PoolingStrategy::Pooling
+        {
+            max_instances
+        } else {
+            config.min_instances.unwrap_or(1) as usize
+        };
         {
             let mut available = inner.available.lock();
             for _ in 0..min_instances {
@@ -112,126 +507,176 @@ impl InstancePool {
             debug!(count = available.len(), "Pre-warmed pool");
         }
 
+        // Spawn the background supervisor: periodically evicts stale
+        // instances and runs health checks. Holds only a `Weak` reference so
+        // it exits on its own once the pool is dropped, and is aborted
+        // explicitly on `shutdown` for a prompt stop.
+        let supervisor_inner = Arc::downgrade(&inner);
+        let interval_secs = inner.config.supervision_interval_secs.max(1);
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+            ticker.tick().await; // first tick fires immediately; skip it
+            loop {
+                ticker.tick().await;
+                let Some(inner) = supervisor_inner.upgrade() else {
+                    break;
+                };
+                if *inner.shutdown.read() {
+                    break;
+                }
+                inner.supervise_tick();
+            }
+        });
+        *inner.supervisor.lock() = Some(handle);
+
         Ok(Self { inner })
     }
 
     /// Acquire an instance from the pool
     ///
     /// This will either return an existing idle instance or create a new one
-    /// if the pool has capacity. Blocks if all instances are in use.
+    /// if the pool has capacity. Blocks indefinitely if all instances are in
+    /// use; see [`Self::acquire_timeout`] for a bounded variant.
     pub async fn acquire(&self) -> Result<PooledInstance> {
         // Check shutdown
         if *self.inner.shutdown.read() {
             return Err(RuntimeError::Shutdown("Pool is shut down".into()));
         }
+        if self.inner.degraded.load(Ordering::Relaxed) {
+            return Err(RuntimeError::Degraded(
+                "Pool tripped its restart circuit breaker".into(),
+            ));
+        }
 
-        // Acquire semaphore permit (blocks if at capacity)
-        let _permit = self
-            .inner
-            .semaphore
-            .acquire()
-            .await
-            .map_err(|_| RuntimeError::Shutdown("Pool semaphore closed".into()))?;
+        // Acquire an owned semaphore permit (blocks if at capacity). Being owned,
+        // it travels with the `PooledInstance` and restores capacity on drop even
+        // if the caller never calls `release` explicitly.
+        self.inner.waiting_count.fetch_add(1, Ordering::Relaxed);
+        let permit = Arc::clone(&self.inner.semaphore).acquire_owned().await;
+        self.inner.waiting_count.fetch_sub(1, Ordering::Relaxed);
+        let permit = permit.map_err(|_| RuntimeError::Shutdown("Pool semaphore closed".into()))?;
 
-        // Try to get an existing instance
-        let instance = {
-            let mut available = self.inner.available.lock();
-            available.pop_back() // LIFO for better cache locality
-        };
+        self.inner.instance_for_permit(permit)
+    }
 
-        let instance = match instance {
-            Some(mut inst) => {
-                // Reset the instance for reuse
-                inst.reset()?;
-                debug!(id = %inst.id(), "Reusing pooled instance");
-                inst
-            }
-            None => {
-                // Create new instance
-                let inst = WasmInstance::new(&self.inner.config)?;
-                self.inner.instances_created.fetch_add(1, Ordering::Relaxed);
-                debug!(id = %inst.id(), "Created new instance");
-                inst
+    /// Acquire an instance from the pool, failing with [`RuntimeError::Timeout`]
+    /// instead of blocking forever if no capacity is available within `timeout`.
+    ///
+    /// Useful for callers that want to detect pool starvation (all instances
+    /// checked out) rather than hang indefinitely.
+    pub async fn acquire_timeout(&self, timeout: std::time::Duration) -> Result<PooledInstance> {
+        if *self.inner.shutdown.read() {
+            return Err(RuntimeError::Shutdown("Pool is shut down".into()));
+        }
+        if self.inner.degraded.load(Ordering::Relaxed) {
+            return Err(RuntimeError::Degraded(
+                "Pool tripped its restart circuit breaker".into(),
+            ));
+        }
+
+        self.inner.waiting_count.fetch_add(1, Ordering::Relaxed);
+        let permit = tokio::time::timeout(
+            timeout,
+            Arc::clone(&self.inner.semaphore).acquire_owned(),
+        )
+        .await;
+        self.inner.waiting_count.fetch_sub(1, Ordering::Relaxed);
+
+        let permit = match permit {
+            Ok(Ok(permit)) => permit,
+            Ok(Err(_)) => return Err(RuntimeError::Shutdown("Pool semaphore closed".into())),
+            Err(_) => {
+                return Err(RuntimeError::Timeout(format!(
+                    "Timed out after {:?} waiting for a pooled instance",
+                    timeout
+                )))
             }
         };
 
-        self.inner.active_count.fetch_add(1, Ordering::Relaxed);
-        self.inner.total_memory.fetch_add(
-            instance.memory_used(),
-            Ordering::Relaxed,
-        );
+        self.inner.instance_for_permit(permit)
+    }
 
-        // Forget the permit - we track active count ourselves
-        std::mem::forget(_permit);
+    /// Get the number of tasks currently blocked in `acquire`/`acquire_timeout`
+    /// waiting for a permit. A sustained non-zero value indicates the pool is
+    /// saturated and `max_instances` may need to be raised.
+    pub fn waiting_count(&self) -> usize {
+        self.inner.waiting_count.load(Ordering::Relaxed)
+    }
 
-        Ok(PooledInstance {
-            instance,
-            pool: Arc::clone(&self.inner),
-        })
+    /// Resize the pool's capacity in place without recreating the semaphore
+    /// or any existing instances.
+    ///
+    /// Growing adds permits immediately and never blocks. Shrinking drains
+    /// and terminates idle instances first, then reclaims whatever capacity
+    /// isn't currently checked out; any remainder is reclaimed lazily as
+    /// active instances are released, so shrinking below the current active
+    /// count is tolerated rather than blocking.
+    pub fn resize(&self, new_max: usize) {
+        self.inner.resize(new_max);
     }
 
     /// Release an instance back to the pool
-    pub fn release(&self, mut pooled: PooledInstance) {
-        self.inner.active_count.fetch_sub(1, Ordering::Relaxed);
-        self.inner.total_memory.fetch_sub(
-            pooled.instance.memory_used(),
-            Ordering::Relaxed,
-        );
+    ///
+    /// Equivalent to dropping the guard; kept as an explicit method for callers
+    /// that want to signal release eagerly rather than at scope exit.
+    pub fn release(&self, pooled: PooledInstance) {
+        pooled.release();
+    }
 
-        // Add permit back
-        self.inner.semaphore.add_permits(1);
+    /// Get the instance awaiting a given suspension ID.
+    ///
+    /// An instance may be tracked under several concurrent suspension ids at
+    /// once (see [`WasmInstance::suspension_ids`]); this looks it up by any
+    /// one of them via [`InstancePoolInner::suspension_index`] and checks it
+    /// out of `suspended` entirely, including its other still-pending ids —
+    /// if [`WasmInstance::resume`] determines some of those are still in
+    /// flight, releasing the returned guard re-inserts it under whatever ids
+    /// remain.
+    ///
+    /// The index lookup and the `suspended` removal happen under one held
+    /// `suspension_index` write lock, so a concurrent `get_suspended` racing
+    /// for a sibling id of the same `Promise.all` fan-out can't observe the
+    /// instance as still present after this call has already checked it out
+    /// — it either blocks until this call finishes (then correctly sees the
+    /// id gone) rather than spuriously removing the instance out from under
+    /// this call, or loses the race and returns `None` for the caller to
+    /// retry, the same contract as the pool-saturated case below.
+    ///
+    /// Resuming a suspended instance re-acquires pool capacity; if the pool is
+    /// momentarily saturated the instance is put back into the suspended map
+    /// and `None` is returned so the caller can retry.
+    pub fn get_suspended(&self, suspension_id: &str) -> Option<PooledInstance> {
+        let mut index = self.inner.suspension_index.write();
+        let instance_id = index.get(suspension_id)?.clone();
+        let instance = self.inner.suspended.write().remove(&instance_id)?;
 
-        // Check if instance should be returned to pool
-        match pooled.instance.state() {
-            InstanceState::Idle => {
-                // Return to available pool
-                if let Ok(()) = pooled.instance.reset() {
-                    let mut available = self.inner.available.lock();
-                    available.push_back(pooled.instance);
-                    debug!("Returned instance to pool");
-                } else {
-                    debug!("Instance reset failed, dropping");
-                    pooled.instance.terminate();
-                }
+        for other_id in instance.suspension_ids() {
+            index.remove(other_id);
+        }
+        drop(index);
+
+        match Arc::clone(&self.inner.semaphore).try_acquire_owned() {
+            Ok(permit) => {
+                self.inner.active_count.fetch_add(1, Ordering::Relaxed);
+                self.inner.total_memory.fetch_add(
+                    instance.memory_used(),
+                    Ordering::Relaxed,
+                );
+                Some(PooledInstance::new(instance, Arc::clone(&self.inner), permit))
             }
-            InstanceState::Suspended => {
-                // Move to suspended map
-                if let Some(suspension_id) = pooled.instance.suspension_id() {
-                    let mut suspended = self.inner.suspended.write();
-                    suspended.insert(suspension_id.to_string(), pooled.instance);
-                    debug!(suspension_id = suspension_id, "Moved instance to suspended");
-                } else {
-                    warn!("Suspended instance has no suspension ID");
-                    pooled.instance.terminate();
+            Err(_) => {
+                warn!(suspension_id = suspension_id, "Pool saturated, deferring resume");
+                let mut index = self.inner.suspension_index.write();
+                for id in instance.suspension_ids() {
+                    index.insert(id.clone(), instance_id.clone());
                 }
-            }
-            InstanceState::Executing => {
-                warn!("Releasing executing instance - this shouldn't happen");
-                pooled.instance.terminate();
-            }
-            InstanceState::Terminated => {
-                // Already terminated, just drop
-                debug!("Dropping terminated instance");
+                drop(index);
+                self.inner.suspended.write().insert(instance_id, instance);
+                None
             }
         }
     }
 
-    /// Get a suspended instance by suspension ID
-    pub fn get_suspended(&self, suspension_id: &str) -> Option<PooledInstance> {
-        let mut suspended = self.inner.suspended.write();
-        suspended.remove(suspension_id).map(|instance| {
-            self.inner.active_count.fetch_add(1, Ordering::Relaxed);
-            self.inner.total_memory.fetch_add(
-                instance.memory_used(),
-                Ordering::Relaxed,
-            );
-            PooledInstance {
-                instance,
-                pool: Arc::clone(&self.inner),
-            }
-        })
-    }
-
     /// Get count of active (checked out) instances
     pub fn active_count(&self) -> usize {
         self.inner.active_count.load(Ordering::Relaxed)
@@ -257,11 +702,41 @@ impl InstancePool {
         self.inner.instances_created.load(Ordering::Relaxed)
     }
 
+    /// Count idle/suspended instances currently mid low-memory episode
+    /// (`LowMemoryStatus::Ready` or `Executed`; see [`crate::config::LowMemoryHook`]).
+    /// Instances that are checked out are excluded, the same snapshot caveat
+    /// as [`Self::total_memory`].
+    pub fn low_memory_pending_count(&self) -> usize {
+        use super::instance::LowMemoryStatus;
+
+        let available = self
+            .inner
+            .available
+            .lock()
+            .iter()
+            .filter(|instance| instance.low_memory_status() != LowMemoryStatus::ConditionNotSatisfied)
+            .count();
+        let suspended = self
+            .inner
+            .suspended
+            .read()
+            .values()
+            .filter(|instance| instance.low_memory_status() != LowMemoryStatus::ConditionNotSatisfied)
+            .count();
+
+        available + suspended
+    }
+
     /// Shutdown the pool
     pub async fn shutdown(&self) {
         info!("Shutting down instance pool");
         *self.inner.shutdown.write() = true;
 
+        // Stop the background supervisor
+        if let Some(handle) = self.inner.supervisor.lock().take() {
+            handle.abort();
+        }
+
         // Close semaphore
         self.inner.semaphore.close();
 
@@ -284,17 +759,36 @@ impl InstancePool {
         info!("Instance pool shut down");
     }
 
-    /// Clean up stale suspended instances
-    pub fn cleanup_stale(&self, max_age_secs: u64) {
-        let mut suspended = self.inner.suspended.write();
-        let now = std::time::Instant::now();
+    /// Evict instances that have exceeded the configured recycling policy
+    /// (max lifetime, max idle time, or max reuse count).
+    pub fn cleanup_stale(&self) {
+        self.inner.cleanup_stale();
+    }
 
-        suspended.retain(|id, _instance| {
-            // In a real implementation, we'd check instance.suspended_at
-            // For now, we keep all instances
-            debug!(suspension_id = %id, "Checking stale suspension");
-            true
-        });
+    /// Get the number of instances that have been restarted after crashing
+    /// (released while still `Executing`, or failing `reset()`).
+    pub fn restart_count(&self) -> usize {
+        self.inner.restart_count.load(Ordering::Relaxed)
+    }
+
+    /// Fraction of idle releases reused in place via
+    /// `WasmInstance::try_fast_reset` rather than a full `reset()` (0.0 if
+    /// `fast_instance_reuse` is disabled or no instance has been released yet)
+    pub fn fast_reuse_hit_rate(&self) -> f64 {
+        let hits = self.inner.fast_reuse_hits.load(Ordering::Relaxed);
+        let misses = self.inner.fast_reuse_misses.load(Ordering::Relaxed);
+        let total = hits + misses;
+        if total == 0 {
+            0.0
+        } else {
+            hits as f64 / total as f64
+        }
+    }
+
+    /// Whether the pool has tripped its `max_restarts` circuit breaker and is
+    /// refusing new `acquire` calls.
+    pub fn is_degraded(&self) -> bool {
+        self.inner.degraded.load(Ordering::Relaxed)
     }
 }
 
@@ -378,6 +872,182 @@ mod tests {
         assert!(pool.acquire().await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_fast_instance_reuse_tracks_hit_rate() {
+        let config = RuntimeConfig::default().with_fast_instance_reuse(true);
+        let pool = InstancePool::new(&config).unwrap();
+
+        let instance = pool.acquire().await.unwrap();
+        pool.release(instance);
+
+        assert_eq!(pool.fast_reuse_hit_rate(), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_fast_reuse_hit_rate_is_zero_when_disabled() {
+        let pool = create_pool();
+
+        let instance = pool.acquire().await.unwrap();
+        pool.release(instance);
+
+        assert_eq!(pool.fast_reuse_hit_rate(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_pooling_strategy_prewarms_full_slab() {
+        let config = RuntimeConfig::default()
+            .with_max_instances(3)
+            .with_pooling_strategy(crate::config::PoolingStrategy::Pooling);
+        let pool = InstancePool::new(&config).unwrap();
+
+        assert_eq!(pool.available_count(), 3);
+        assert_eq!(pool.instances_created(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_resize_grow_never_blocks() {
+        let config = RuntimeConfig::default().with_max_instances(1);
+        let pool = InstancePool::new(&config).unwrap();
+
+        let i1 = pool.acquire().await.unwrap();
+        pool.resize(2);
+
+        // Capacity for a second concurrent instance is now available.
+        let i2 = pool.acquire().await.unwrap();
+        assert_eq!(pool.active_count(), 2);
+
+        pool.release(i1);
+        pool.release(i2);
+    }
+
+    #[tokio::test]
+    async fn test_resize_shrink_reclaims_lazily() {
+        let config = RuntimeConfig::default().with_max_instances(2);
+        let pool = InstancePool::new(&config).unwrap();
+
+        let i1 = pool.acquire().await.unwrap();
+        let i2 = pool.acquire().await.unwrap();
+
+        // Shrink below the currently active count; neither instance should
+        // be disturbed, and a third acquire must wait for one to be released.
+        pool.resize(1);
+        assert_eq!(pool.active_count(), 2);
+
+        pool.release(i1);
+
+        // The released permit was reclaimed instead of restoring capacity,
+        // so the pool is now genuinely at its new, smaller max.
+        let acquire_result =
+            tokio::time::timeout(std::time::Duration::from_millis(50), pool.acquire()).await;
+        assert!(acquire_result.is_err(), "pool should stay at reduced capacity");
+
+        pool.release(i2);
+    }
+
+    #[tokio::test]
+    async fn test_releasing_executing_instance_is_recorded_as_a_crash() {
+        let pool = create_pool();
+
+        let mut instance = pool.acquire().await.unwrap();
+        // Force the instance into `Executing` without going through a full
+        // `execute()` call, simulating a trap/panic mid-handler.
+        instance.inner_mut().force_executing_for_test();
+        pool.release(instance);
+
+        assert_eq!(pool.restart_count(), 1);
+        assert!(!pool.is_degraded());
+    }
+
+    #[tokio::test]
+    async fn test_restart_circuit_breaker_trips_after_max_restarts() {
+        let config = RuntimeConfig::default().with_max_restarts(2);
+        let pool = InstancePool::new(&config).unwrap();
+
+        for _ in 0..2 {
+            let mut instance = pool.acquire().await.unwrap();
+            instance.inner_mut().force_executing_for_test();
+            pool.release(instance);
+        }
+
+        assert_eq!(pool.restart_count(), 2);
+        assert!(pool.is_degraded());
+
+        let err = pool.acquire().await.unwrap_err();
+        assert!(matches!(err, RuntimeError::Degraded(_)));
+    }
+
+    #[tokio::test]
+    async fn test_acquire_timeout_on_saturated_pool() {
+        let config = RuntimeConfig::default().with_max_instances(1);
+        let pool = InstancePool::new(&config).unwrap();
+
+        let _held = pool.acquire().await.unwrap();
+        assert_eq!(pool.waiting_count(), 0);
+
+        let err = pool
+            .acquire_timeout(std::time::Duration::from_millis(10))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RuntimeError::Timeout(_)));
+        assert_eq!(pool.waiting_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_timeout_succeeds_with_capacity() {
+        let pool = create_pool();
+
+        let instance = pool
+            .acquire_timeout(std::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(pool.active_count(), 1);
+        pool.release(instance);
+    }
+
+    #[tokio::test]
+    async fn test_max_reuses_recycles_instance() {
+        use crate::context::WasmContext;
+        use crate::engine::compiler::CompiledHandler;
+
+        let config = RuntimeConfig::default().with_max_reuses(1);
+        let pool = InstancePool::new(&config).unwrap();
+        let compiled = CompiledHandler {
+            bytecode: vec![],
+            source_map: None,
+            cache_hit: false,
+            init_image: None,
+        };
+
+        let mut instance = pool.acquire().await.unwrap();
+        let id1 = instance.id().to_string();
+        instance
+            .inner_mut()
+            .execute(&compiled, WasmContext::new("panel-1", "handler"))
+            .await
+            .unwrap();
+        pool.release(instance);
+
+        let instance = pool.acquire().await.unwrap();
+        let id2 = instance.id().to_string();
+        pool.release(instance);
+
+        assert_ne!(id1, id2, "Instance should be recycled after max_reuses");
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_stale_respects_unlimited_idle_time() {
+        let config = RuntimeConfig::default();
+        let pool = InstancePool::new(&config).unwrap();
+
+        let instance = pool.acquire().await.unwrap();
+        pool.release(instance);
+        assert!(pool.available_count() > 0);
+
+        // Default policy is unlimited, so cleanup_stale is a no-op
+        pool.cleanup_stale();
+        assert!(pool.available_count() > 0);
+    }
+
     #[tokio::test]
     async fn test_pool_stats() {
         let pool = create_pool();
@@ -0,0 +1,201 @@
+//! Cross-process instance snapshots.
+//!
+//! Unlike [`crate::engine::instance::InstanceSnapshot`] (an in-process,
+//! `Arc`-shared rollback point shared between instances that are already
+//! running), [`encode`]/[`decode`] here serialize a handler's compiled
+//! bytecode plus its post-init linear memory into a self-contained buffer
+//! that can be persisted to disk or handed to another process, then used to
+//! resurrect an instance later without repeating compilation or
+//! initialization.
+
+use super::compiler::{CompiledHandler, SourceMap};
+use crate::error::{Result, RuntimeError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Identifies a buffer produced by [`encode`] before any of its other fields
+/// are trusted, so a buffer from an unrelated source is rejected outright
+/// instead of partially decoded.
+const SNAPSHOT_MAGIC: [u8; 8] = *b"NXSNAP01";
+
+/// Bumped whenever [`SnapshotPayload`]'s shape changes, so a snapshot
+/// written by an older or newer binary is rejected rather than misread.
+const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// On-the-wire header, validated before any of `payload` is trusted: magic
+/// bytes, a format version, the engine version that produced it, and a hash
+/// of the bytecode it was taken from.
+#[derive(Serialize, Deserialize)]
+struct SnapshotHeader {
+    magic: [u8; 8],
+    format_version: u32,
+    engine_version: String,
+    bytecode_sha256: Vec<u8>,
+    payload: Vec<u8>,
+    payload_sha256: Vec<u8>,
+}
+
+/// The serialized compiled module and its captured post-init memory,
+/// encoded with `rmp_serde` and wrapped in a [`SnapshotHeader`] before being
+/// handed back to the caller, the same framing `engine::compiler` uses for
+/// its own disk-cache entries.
+#[derive(Serialize, Deserialize)]
+struct SnapshotPayload {
+    bytecode: Vec<u8>,
+    source_map: Option<SourceMap>,
+    memory: Vec<u8>,
+    memory_used: u64,
+    memory_peak: u64,
+}
+
+/// Encode a compiled handler and its primed post-init memory into a
+/// versioned, checksummed buffer suitable for persisting across processes.
+pub fn encode(
+    compiled: &CompiledHandler,
+    memory: Vec<u8>,
+    memory_used: u64,
+    memory_peak: u64,
+) -> Result<Vec<u8>> {
+    let payload_bytes = rmp_serde::to_vec(&SnapshotPayload {
+        bytecode: compiled.bytecode.clone(),
+        source_map: compiled.source_map.clone(),
+        memory,
+        memory_used,
+        memory_peak,
+    })
+    .map_err(|e| RuntimeError::Snapshot(format!("failed to encode snapshot payload: {}", e)))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&payload_bytes);
+    let payload_sha256 = hasher.finalize().to_vec();
+
+    let header = SnapshotHeader {
+        magic: SNAPSHOT_MAGIC,
+        format_version: SNAPSHOT_FORMAT_VERSION,
+        engine_version: crate::VERSION.to_string(),
+        bytecode_sha256: hash_bytecode(&compiled.bytecode),
+        payload: payload_bytes,
+        payload_sha256,
+    };
+
+    rmp_serde::to_vec(&header)
+        .map_err(|e| RuntimeError::Snapshot(format!("failed to encode snapshot header: {}", e)))
+}
+
+/// Decode and validate a buffer previously produced by [`encode`], returning
+/// the reconstituted [`CompiledHandler`] plus its captured memory.
+///
+/// Rejects, rather than partially trusts, a buffer whose magic, format
+/// version, engine version, payload checksum, or bytecode hash don't match —
+/// the critical invariant this feature is built around: a stale or corrupt
+/// snapshot must come back as a typed [`RuntimeError::Snapshot`], never as
+/// bytecode paired with memory it wasn't actually captured from.
+pub fn decode(bytes: &[u8]) -> Result<(CompiledHandler, Vec<u8>, u64, u64)> {
+    let header: SnapshotHeader = rmp_serde::from_slice(bytes)
+        .map_err(|e| RuntimeError::Snapshot(format!("not a valid snapshot: {}", e)))?;
+
+    if header.magic != SNAPSHOT_MAGIC {
+        return Err(RuntimeError::Snapshot(
+            "snapshot has an unrecognized header".into(),
+        ));
+    }
+    if header.format_version != SNAPSHOT_FORMAT_VERSION {
+        return Err(RuntimeError::Snapshot(format!(
+            "snapshot format version {} is incompatible with this engine (expected {})",
+            header.format_version, SNAPSHOT_FORMAT_VERSION
+        )));
+    }
+    if header.engine_version != crate::VERSION {
+        return Err(RuntimeError::Snapshot(format!(
+            "snapshot was produced by engine version {}, but this engine is version {}",
+            header.engine_version,
+            crate::VERSION
+        )));
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(&header.payload);
+    if hasher.finalize().as_slice() != header.payload_sha256.as_slice() {
+        return Err(RuntimeError::Snapshot(
+            "snapshot payload checksum mismatch".into(),
+        ));
+    }
+
+    let payload: SnapshotPayload = rmp_serde::from_slice(&header.payload)
+        .map_err(|e| RuntimeError::Snapshot(format!("failed to decode snapshot payload: {}", e)))?;
+
+    if hash_bytecode(&payload.bytecode) != header.bytecode_sha256 {
+        return Err(RuntimeError::Snapshot(
+            "snapshot bytecode hash does not match its header".into(),
+        ));
+    }
+
+    let compiled = CompiledHandler {
+        bytecode: payload.bytecode,
+        source_map: payload.source_map,
+        cache_hit: true,
+        init_image: None,
+    };
+
+    Ok((compiled, payload.memory, payload.memory_used, payload.memory_peak))
+}
+
+fn hash_bytecode(bytecode: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(bytecode);
+    hasher.finalize().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_handler() -> CompiledHandler {
+        CompiledHandler {
+            bytecode: vec![1, 2, 3, 4, 5],
+            source_map: Some(SourceMap::from_source("$state.get('count')")),
+            cache_hit: false,
+            init_image: None,
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let compiled = sample_handler();
+        let bytes = encode(&compiled, vec![0u8; 16], 16, 32).unwrap();
+
+        let (decoded, memory, memory_used, memory_peak) = decode(&bytes).unwrap();
+        assert_eq!(decoded.bytecode, compiled.bytecode);
+        assert_eq!(memory, vec![0u8; 16]);
+        assert_eq!(memory_used, 16);
+        assert_eq!(memory_peak, 32);
+    }
+
+    #[test]
+    fn test_decode_rejects_garbage() {
+        let err = decode(b"not a snapshot").unwrap_err();
+        assert!(matches!(err, RuntimeError::Snapshot(_)));
+    }
+
+    #[test]
+    fn test_decode_rejects_format_version_mismatch() {
+        let bytes = encode(&sample_handler(), vec![0u8; 4], 4, 4).unwrap();
+        let mut header: SnapshotHeader = rmp_serde::from_slice(&bytes).unwrap();
+        header.format_version = SNAPSHOT_FORMAT_VERSION + 1;
+        let tampered = rmp_serde::to_vec(&header).unwrap();
+
+        let err = decode(&tampered).unwrap_err();
+        assert!(matches!(err, RuntimeError::Snapshot(_)));
+    }
+
+    #[test]
+    fn test_decode_rejects_payload_checksum_mismatch() {
+        let bytes = encode(&sample_handler(), vec![0u8; 4], 4, 4).unwrap();
+        let mut header: SnapshotHeader = rmp_serde::from_slice(&bytes).unwrap();
+        header.payload_sha256 = vec![0u8; 32];
+        let tampered = rmp_serde::to_vec(&header).unwrap();
+
+        let err = decode(&tampered).unwrap_err();
+        assert!(matches!(err, RuntimeError::Snapshot(_)));
+    }
+}
@@ -35,6 +35,10 @@ pub enum ErrorCode {
     ExtensionNotFound,
     /// Method not found
     MethodNotFound,
+    /// Handler exhausted its gas (instruction) budget
+    GasExhausted,
+    /// Execution was aborted via a cancellation handle before it finished
+    Cancelled,
 }
 
 impl std::fmt::Display for ErrorCode {
@@ -53,6 +57,8 @@ impl std::fmt::Display for ErrorCode {
             ErrorCode::InvalidArgument => write!(f, "INVALID_ARGUMENT"),
             ErrorCode::ExtensionNotFound => write!(f, "EXTENSION_NOT_FOUND"),
             ErrorCode::MethodNotFound => write!(f, "METHOD_NOT_FOUND"),
+            ErrorCode::GasExhausted => write!(f, "GAS_EXHAUSTED"),
+            ErrorCode::Cancelled => write!(f, "CANCELLED"),
         }
     }
 }
@@ -186,6 +192,35 @@ impl WasmError {
         )
     }
 
+    /// Create a gas-exhausted error
+    pub fn gas_exhausted(limit: u64, used: u64) -> Self {
+        Self::new(
+            ErrorCode::GasExhausted,
+            format!(
+                "Handler exceeded gas limit: {} gas used, {} gas allowed",
+                used, limit
+            ),
+        )
+    }
+
+    /// Create a resource-exhausted error for a specific
+    /// [`crate::context::ResourceLimitKind`] ceiling
+    pub fn resource_exhausted(kind: crate::context::ResourceLimitKind, limit: u32, used: u32) -> Self {
+        Self::resource_limit(kind.as_str(), limit, used)
+    }
+
+    /// Create a cancelled error, reported when an in-flight execution is
+    /// aborted via its [`crate::engine::cancellation::CancellationRegistry`]
+    /// handle rather than running to completion or timing out
+    pub fn cancelled() -> Self {
+        Self::new(ErrorCode::Cancelled, "Execution was cancelled")
+    }
+
+    /// Create an invalid argument error
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidArgument, message)
+    }
+
     /// Create an extension not found error
     pub fn extension_not_found(name: impl Into<String>) -> Self {
         Self::new(
@@ -232,6 +267,21 @@ impl WasmError {
         self.context = Some(context);
         self
     }
+
+    /// Resolve this error's `stack` (set via [`Self::with_stack`]) through
+    /// `map` and fill in `location` + `snippet` from it, rendering the code
+    /// frame from `source`. A no-op if `stack` hasn't been set or its top
+    /// frame carries no parseable offset, so this can always be chained
+    /// speculatively without an extra `is_some` check at the call site.
+    pub fn with_source_context(mut self, source: &str, map: &crate::engine::compiler::SourceMap) -> Self {
+        if let Some(stack) = &self.stack {
+            if let Some((location, snippet)) = crate::diagnostics::diagnose(stack, map, source) {
+                self.location = Some(location);
+                self.snippet = Some(snippet);
+            }
+        }
+        self
+    }
 }
 
 impl std::fmt::Display for WasmError {
@@ -285,9 +335,57 @@ pub enum RuntimeError {
     #[error("Suspension error: {0}")]
     Suspension(String),
 
+    /// Timed out waiting for a resource (e.g. a pooled instance permit)
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    /// Pool has tripped its restart circuit breaker and is refusing checkouts
+    #[error("Pool is degraded: {0}")]
+    Degraded(String),
+
+    /// Capability delegation chain violates the attenuation invariant
+    #[error("Delegation error: {0}")]
+    Delegation(String),
+
+    /// Capability routing between handlers failed: an unrouted `use`, a
+    /// dangling `offer`, or a reference to an unknown handler
+    #[error("Routing error: {0}")]
+    Routing(String),
+
     /// General error
     #[error("{0}")]
     General(String),
+
+    /// Handler was trapped after exhausting its gas (instruction) budget
+    #[error("Gas exhausted: {used} used of {limit} allowed")]
+    GasExhausted {
+        /// Configured gas limit
+        limit: u64,
+        /// Gas consumed before the budget was exhausted
+        used: u64,
+    },
+
+    /// A handler or host function panicked; the panic was caught so one
+    /// misbehaving handler can't take down the whole host process
+    #[error("Handler panicked: {0}")]
+    Panic(String),
+
+    /// A cross-process instance snapshot was malformed, or its header
+    /// didn't match the engine/bytecode that's about to restore it
+    #[error("Snapshot error: {0}")]
+    Snapshot(String),
+}
+
+/// Turn a caught panic payload into a human-readable message, the same way
+/// a panic hook would render it, for use in [`RuntimeError::Panic`].
+pub(crate) fn describe_panic(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "handler panicked with a non-string payload".to_string()
+    }
 }
 
 impl RuntimeError {
@@ -309,7 +407,20 @@ impl RuntimeError {
                 WasmError::new(ErrorCode::InternalError, msg.clone())
             }
             RuntimeError::Suspension(msg) => WasmError::new(ErrorCode::InternalError, msg.clone()),
+            RuntimeError::Delegation(msg) => {
+                WasmError::new(ErrorCode::PermissionDenied, msg.clone())
+            }
+            RuntimeError::Routing(msg) => {
+                WasmError::new(ErrorCode::PermissionDenied, msg.clone())
+            }
             RuntimeError::General(msg) => WasmError::new(ErrorCode::InternalError, msg.clone()),
+            RuntimeError::GasExhausted { limit, used } => WasmError::gas_exhausted(*limit, *used),
+            RuntimeError::Panic(msg) => {
+                WasmError::new(ErrorCode::ExecutionError, format!("Handler panicked: {}", msg))
+            }
+            RuntimeError::Snapshot(msg) => {
+                WasmError::new(ErrorCode::SerializationError, msg.clone())
+            }
         }
     }
 }
@@ -349,6 +460,10 @@ pub mod error_codes {
     pub const NOT_FOUND: i32 = -4;
     /// Internal error
     pub const INTERNAL_ERROR: i32 = -5;
+    /// Gas (instruction) budget exhausted
+    pub const GAS_EXHAUSTED: i32 = -6;
+    /// Compare-and-set precondition didn't match the live state value
+    pub const CONFLICT: i32 = -7;
 }
 
 #[cfg(test)]
@@ -377,6 +492,34 @@ mod tests {
         assert_eq!(loc.column, 5);
     }
 
+    #[test]
+    fn test_with_source_context_resolves_stack_through_map() {
+        use crate::engine::compiler::SourceMap;
+
+        let source = "line1\nline2\nline3";
+        let map = SourceMap::from_source(source);
+
+        let err = WasmError::execution_error("boom")
+            .with_stack("at handler (6)")
+            .with_source_context(source, &map);
+
+        let loc = err.location.expect("location should be filled in");
+        assert_eq!(loc.line, 2);
+        assert!(err.snippet.expect("snippet should be filled in").code.contains("line2"));
+    }
+
+    #[test]
+    fn test_with_source_context_without_stack_is_a_noop() {
+        use crate::engine::compiler::SourceMap;
+
+        let source = "line1\nline2";
+        let map = SourceMap::from_source(source);
+
+        let err = WasmError::execution_error("boom").with_source_context(source, &map);
+        assert!(err.location.is_none());
+        assert!(err.snippet.is_none());
+    }
+
     #[test]
     fn test_wasm_error_serialization() {
         let err = WasmError::permission_denied("state:write:secret", "write state.secret");
@@ -391,4 +534,50 @@ mod tests {
         let converted = runtime_err.to_wasm_error();
         assert_eq!(converted.code, ErrorCode::Timeout);
     }
+
+    #[test]
+    fn test_panic_conversion() {
+        let runtime_err = RuntimeError::Panic("index out of bounds".to_string());
+        let converted = runtime_err.to_wasm_error();
+        assert_eq!(converted.code, ErrorCode::ExecutionError);
+        assert!(converted.message.contains("index out of bounds"));
+    }
+
+    #[test]
+    fn test_describe_panic_from_str_payload() {
+        let payload: Box<dyn std::any::Any + Send> = Box::new("boom");
+        assert_eq!(describe_panic(payload), "boom");
+    }
+
+    #[test]
+    fn test_describe_panic_from_string_payload() {
+        let payload: Box<dyn std::any::Any + Send> = Box::new(String::from("boom"));
+        assert_eq!(describe_panic(payload), "boom");
+    }
+
+    #[test]
+    fn test_describe_panic_from_unknown_payload() {
+        let payload: Box<dyn std::any::Any + Send> = Box::new(42_i32);
+        assert_eq!(describe_panic(payload), "handler panicked with a non-string payload");
+    }
+
+    #[test]
+    fn test_resource_exhausted_error() {
+        let err = WasmError::resource_exhausted(crate::context::ResourceLimitKind::HostCalls, 100, 101);
+        assert_eq!(err.code, ErrorCode::ResourceLimit);
+        assert!(err.message.contains("host_calls"));
+        assert!(err.message.contains("101"));
+        assert!(err.message.contains("100"));
+    }
+
+    #[test]
+    fn test_gas_exhausted_conversion() {
+        let runtime_err = RuntimeError::GasExhausted {
+            limit: 1000,
+            used: 1000,
+        };
+        let converted = runtime_err.to_wasm_error();
+        assert_eq!(converted.code, ErrorCode::GasExhausted);
+        assert!(converted.message.contains("1000"));
+    }
 }
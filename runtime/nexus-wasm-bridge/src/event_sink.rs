@@ -0,0 +1,159 @@
+//! Pluggable synchronous delivery of emitted events.
+//!
+//! `emit_event` always buffers into the execution context's `events` vector
+//! for the caller to read once the handler returns, but a context may also
+//! hold an [`EventSink`] that is notified synchronously as each event is
+//! emitted, before execution finishes — useful for live streaming and
+//! telemetry of high-volume emitters. The same sink is also notified of view
+//! commands and state mutations as they're recorded, for callers (like
+//! `execute_handler_streaming`) that want to forward all three kinds of
+//! output without waiting for the handler to finish.
+
+use crate::context::{EmittedEvent, StateMutation, ViewCommand};
+use crate::metrics::MetricsCollector;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Receives a synchronous notification for every event, view command, and
+/// state mutation recorded by a handler
+pub trait EventSink: std::fmt::Debug + Send + Sync {
+    /// Called immediately as `event` is emitted by `handler_id` in `panel_id`
+    fn on_event(&self, panel_id: &str, handler_id: &str, event: &EmittedEvent);
+
+    /// Called immediately as `command` is recorded (default: no-op). Not
+    /// called for commands discarded by `abort_view_batch`.
+    fn on_view_command(&self, _panel_id: &str, _handler_id: &str, _command: &ViewCommand) {}
+
+    /// Called immediately as `mutation` is recorded (default: no-op)
+    fn on_mutation(&self, _panel_id: &str, _handler_id: &str, _mutation: &StateMutation) {}
+}
+
+/// An [`EventSink`] that buffers events up to a fixed capacity, dropping (and
+/// counting) anything emitted once the buffer is full instead of growing
+/// unbounded for a high-volume emitter.
+///
+/// When constructed with [`Self::with_metrics`], every event forwarded to
+/// this sink increments that event name's emission counter in the attached
+/// [`MetricsCollector`], and every dropped event increments
+/// `nexus_events_dropped_total`, so throughput and overflow are observable
+/// without polling the sink directly.
+#[derive(Debug)]
+pub struct BoundedEventSink {
+    capacity: usize,
+    buffer: parking_lot::Mutex<VecDeque<EmittedEvent>>,
+    dropped: AtomicU64,
+    metrics: Option<Arc<MetricsCollector>>,
+}
+
+impl BoundedEventSink {
+    /// Create a new sink that holds at most `capacity` buffered events
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            buffer: parking_lot::Mutex::new(VecDeque::new()),
+            dropped: AtomicU64::new(0),
+            metrics: None,
+        }
+    }
+
+    /// Attach a metrics collector to record emission and drop counters into
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Drain and return all currently buffered events
+    pub fn drain(&self) -> Vec<EmittedEvent> {
+        self.buffer.lock().drain(..).collect()
+    }
+
+    /// Number of currently buffered events
+    pub fn len(&self) -> usize {
+        self.buffer.lock().len()
+    }
+
+    /// Whether the buffer currently holds no events
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of events dropped because the buffer was at capacity
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl EventSink for BoundedEventSink {
+    fn on_event(&self, _panel_id: &str, _handler_id: &str, event: &EmittedEvent) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_event_emission(&event.name);
+        }
+
+        let mut buffer = self.buffer.lock();
+        if buffer.len() >= self.capacity {
+            drop(buffer);
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            if let Some(metrics) = &self.metrics {
+                metrics.record_event_dropped();
+            }
+            return;
+        }
+
+        buffer.push_back(event.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::RuntimeValue;
+
+    fn event(name: &str) -> EmittedEvent {
+        EmittedEvent::new(name, RuntimeValue::Null)
+    }
+
+    #[test]
+    fn test_bounded_sink_buffers_up_to_capacity() {
+        let sink = BoundedEventSink::new(2);
+
+        sink.on_event("panel-1", "handler-1", &event("a"));
+        sink.on_event("panel-1", "handler-1", &event("b"));
+
+        assert_eq!(sink.len(), 2);
+        assert_eq!(sink.dropped_count(), 0);
+    }
+
+    #[test]
+    fn test_bounded_sink_drops_past_capacity() {
+        let sink = BoundedEventSink::new(1);
+
+        sink.on_event("panel-1", "handler-1", &event("a"));
+        sink.on_event("panel-1", "handler-1", &event("b"));
+
+        assert_eq!(sink.len(), 1);
+        assert_eq!(sink.dropped_count(), 1);
+    }
+
+    #[test]
+    fn test_bounded_sink_drain_empties_buffer() {
+        let sink = BoundedEventSink::new(4);
+        sink.on_event("panel-1", "handler-1", &event("a"));
+
+        let drained = sink.drain();
+        assert_eq!(drained.len(), 1);
+        assert!(sink.is_empty());
+    }
+
+    #[test]
+    fn test_bounded_sink_with_metrics_records_emissions_and_drops() {
+        let metrics = Arc::new(MetricsCollector::new());
+        let sink = BoundedEventSink::new(1).with_metrics(Arc::clone(&metrics));
+
+        sink.on_event("panel-1", "handler-1", &event("toast"));
+        sink.on_event("panel-1", "handler-1", &event("toast"));
+
+        assert_eq!(metrics.event_emissions().get("toast"), Some(&2));
+        assert_eq!(metrics.events_dropped(), 1);
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! These functions allow handlers to emit events that propagate to the host system.
 
-use super::{HostResult, SharedContext};
+use super::{catch_panic, check_host_call, HostResult, SharedContext};
 use crate::context::{EmittedEvent, RuntimeValue};
 use crate::error::error_codes;
 
@@ -15,23 +15,29 @@ use crate::error::error_codes;
 ///
 /// # Returns
 /// * `Ok(())` - Success
-/// * `Err(code)` - If permission denied or error
+/// * `Err(code)` - If permission denied, the host-call or event ceiling was
+///   reached, or another error
+///
+/// If the context has an [`crate::event_sink::EventSink`] attached, it is
+/// notified synchronously in addition to the event being buffered.
 pub fn emit_event(ctx: &SharedContext, event_name: &str, payload: RuntimeValue) -> HostResult<()> {
-    let mut context = ctx.lock();
-
-    // Check capability
-    let required = format!("events:emit:{}", event_name);
-    if !context.has_capability(&required) && !context.has_capability("events:emit:*") {
-        return Err(error_codes::PERMISSION_DENIED);
-    }
-
-    // Record event
-    context.add_event(EmittedEvent {
-        name: event_name.to_string(),
-        payload,
-    });
+    catch_panic(|| {
+        let mut context = ctx.lock();
+        check_host_call(&mut context)?;
+
+        // Capability is checked inside `add_event` itself; a rejection
+        // could be a missing grant or the event ceiling, distinguished by
+        // which of the two error fields it set
+        if !context.add_event(EmittedEvent::new(event_name, payload)) {
+            return Err(if context.last_capability_error.is_some() {
+                error_codes::PERMISSION_DENIED
+            } else {
+                error_codes::RESOURCE_LIMIT
+            });
+        }
 
-    Ok(())
+        Ok(())
+    })
 }
 
 /// Emit a toast notification (convenience function)
@@ -151,4 +157,22 @@ mod tests {
         let context = ctx.lock();
         assert_eq!(context.events.len(), 3);
     }
+
+    #[test]
+    fn test_emit_event_rejects_once_event_ceiling_reached() {
+        let ctx = create_context_with_caps(vec![CapabilityToken::EventsEmitAll]);
+        ctx.lock().set_resource_limits(&crate::config::ResourceLimits {
+            max_events: 1,
+            ..crate::config::ResourceLimits::default()
+        });
+
+        assert!(emit_event(&ctx, "event1", RuntimeValue::Null).is_ok());
+        assert_eq!(
+            emit_event(&ctx, "event2", RuntimeValue::Null),
+            Err(error_codes::RESOURCE_LIMIT)
+        );
+
+        let context = ctx.lock();
+        assert_eq!(context.events.len(), 1);
+    }
 }
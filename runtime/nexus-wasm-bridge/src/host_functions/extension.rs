@@ -2,11 +2,100 @@
 //!
 //! These functions handle async extension calls using the suspend/resume mechanism.
 
-use super::{HostResult, SharedContext};
-use crate::context::{RuntimeValue, SuspensionDetails, SuspensionState};
-use crate::error::error_codes;
+use super::{catch_panic, check_host_call, HostResult, SharedContext};
+use crate::context::{
+    ExecutionContext, JoinMode, MethodSignature, RuntimeValue, SuspensionDetails, SuspensionState,
+};
+use crate::error::{error_codes, ErrorCode, WasmError};
 use uuid::Uuid;
 
+/// The result an extension hands back to [`ext_resume`] for a suspension it
+/// has finished: either the resolved value, or a `WasmError` describing why
+/// the call failed (extension/method no longer available, timed out, threw,
+/// ...).
+#[derive(Debug, Clone)]
+pub enum ExtensionOutcome {
+    /// The extension call completed successfully
+    Ok(RuntimeValue),
+    /// The extension call failed; the handler's `await` should reject with this
+    Err(WasmError),
+}
+
+/// Map a `WasmError`'s code to the `i32` the host call boundary speaks, so a
+/// rejected suspension surfaces something more specific than
+/// [`error_codes::INTERNAL_ERROR`] where a matching code exists. The full
+/// `WasmError` is still recorded on
+/// [`crate::context::ExecutionContext::last_extension_error`] for callers
+/// that need the message/stack rather than just the code.
+fn error_code_for(code: ErrorCode) -> i32 {
+    match code {
+        ErrorCode::PermissionDenied => error_codes::PERMISSION_DENIED,
+        ErrorCode::ResourceLimit => error_codes::RESOURCE_LIMIT,
+        ErrorCode::InvalidArgument => error_codes::INVALID_ARGUMENT,
+        ErrorCode::ExtensionNotFound | ErrorCode::MethodNotFound => error_codes::NOT_FOUND,
+        ErrorCode::GasExhausted => error_codes::GAS_EXHAUSTED,
+        _ => error_codes::INTERNAL_ERROR,
+    }
+}
+
+/// Check that `ext_name`/`method` is a registered extension method, that
+/// `args` satisfies its declared [`MethodSignature`], that the caller holds
+/// a capability covering it, and that `args` satisfies every
+/// [`ArgConstraint`](crate::capability::ArgConstraint) attached to a matching
+/// grant, without registering a suspension. Shared by [`ext_suspend`] and
+/// [`ext_suspend_many`] so the batch variant can validate every call before
+/// committing any of them.
+///
+/// Capability lookup goes from most to least specific: a method-scoped
+/// `ext:{ext_name}:{method}` grant, then an extension-wide `ext:{ext_name}`
+/// grant, then the `ext:*` wildcard. A rejection from a bad signature or a
+/// failed `ArgConstraint` records the offending [`WasmError`] on
+/// [`ExecutionContext::last_extension_error`] so the caller learns what
+/// tripped before just getting a bare code back.
+fn validate_ext_call(
+    context: &mut ExecutionContext,
+    ext_name: &str,
+    method: &str,
+    args: &[RuntimeValue],
+) -> HostResult<()> {
+    let signature = context
+        .extension_registry
+        .get(ext_name)
+        .ok_or(error_codes::NOT_FOUND)?
+        .get(method)
+        .ok_or(error_codes::NOT_FOUND)?
+        .clone();
+
+    let method_scoped = format!("ext:{}:{}", ext_name, method);
+    let ext_scoped = format!("ext:{}", ext_name);
+    if !context.has_capability(&method_scoped)
+        && !context.has_capability(&ext_scoped)
+        && !context.has_capability("ext:*")
+    {
+        return Err(error_codes::PERMISSION_DENIED);
+    }
+
+    if let Err(reason) = signature.validate(args) {
+        context.record_extension_error(WasmError::invalid_argument(format!(
+            "call {}.{}: {}",
+            ext_name, method, reason
+        )));
+        return Err(error_codes::INVALID_ARGUMENT);
+    }
+
+    for constraint in context.extension_method_constraints(ext_name, method) {
+        if let Some(violation) = constraint.check(args) {
+            context.record_extension_error(WasmError::permission_denied(
+                method_scoped,
+                format!("call {}.{} ({})", ext_name, method, violation),
+            ));
+            return Err(error_codes::PERMISSION_DENIED);
+        }
+    }
+
+    Ok(())
+}
+
 /// Suspend execution for an async extension call
 ///
 /// This function is called when a handler uses `await $ext.name.method()`.
@@ -27,42 +116,162 @@ pub fn ext_suspend(
     method: &str,
     args: Vec<RuntimeValue>,
 ) -> HostResult<SuspensionDetails> {
-    let mut context = ctx.lock();
-
-    // Check if extension is registered
-    if !context.extension_registry.contains_key(ext_name) {
-        return Err(error_codes::NOT_FOUND);
-    }
-
-    // Check if method exists on extension
-    let methods = context.extension_registry.get(ext_name).unwrap();
-    if !methods.iter().any(|m| m == method) {
-        return Err(error_codes::NOT_FOUND);
-    }
-
-    // Check capability
-    let required = format!("ext:{}", ext_name);
-    if !context.has_capability(&required) && !context.has_capability("ext:*") {
-        return Err(error_codes::PERMISSION_DENIED);
-    }
+    catch_panic(|| {
+        let mut context = ctx.lock();
+        check_host_call(&mut context)?;
+
+        validate_ext_call(&mut context, ext_name, method, &args)?;
+
+        // Generate suspension ID
+        let suspension_id = Uuid::new_v4().to_string();
+        let seq = context.next_seq();
+
+        // A lone suspend trivially satisfies both join conditions the same
+        // way, but set it explicitly so a later concurrent `ext_suspend` call
+        // (before this one resolves) doesn't inherit a stale `Any` from a
+        // previous batch.
+        context.set_join_mode(JoinMode::All);
+
+        // Store suspension state, preserving the remaining gas budget so resume
+        // continues from where execution left off rather than a fresh allowance.
+        // Inserted by id rather than replacing a single slot, so a handler that
+        // fans out several concurrent extension calls (`Promise.all`) can have
+        // more than one pending at once.
+        context.add_suspension(SuspensionState {
+            id: suspension_id.clone(),
+            seq,
+            extension_name: ext_name.to_string(),
+            method: method.to_string(),
+            args: args.clone(),
+            gas_remaining: context.gas_remaining,
+        });
+
+        // Return suspension details
+        Ok(SuspensionDetails {
+            suspension_id,
+            seq,
+            extension_name: ext_name.to_string(),
+            method: method.to_string(),
+            args,
+            gas_remaining: context.gas_remaining,
+        })
+    })
+}
 
-    // Generate suspension ID
-    let suspension_id = Uuid::new_v4().to_string();
+/// Suspend execution for several concurrent async extension calls at once,
+/// e.g. a handler awaiting `Promise.all([$ext.a.m1(), $ext.b.m2()])` (pass
+/// [`JoinMode::All`]) or `Promise.race`/`Promise.any` (pass [`JoinMode::Any`]).
+///
+/// Every call is validated before any of them is registered: if one
+/// extension/method is unknown or fails the capability check, none of the
+/// calls are suspended, so the handler gets a single error instead of an
+/// instance left half-suspended.
+///
+/// # Arguments
+/// * `ctx` - The execution context
+/// * `calls` - `(extension_name, method, args)` for each concurrent call
+/// * `join_mode` - Whether the handler resumes once every call settles, or
+///   as soon as the first one does
+///
+/// # Returns
+/// * `Ok(details)` - One [`SuspensionDetails`] per call, in the same order as `calls`
+/// * `Err(code)` - If any call fails validation; nothing is registered
+pub fn ext_suspend_many(
+    ctx: &SharedContext,
+    calls: Vec<(String, String, Vec<RuntimeValue>)>,
+    join_mode: JoinMode,
+) -> HostResult<Vec<SuspensionDetails>> {
+    catch_panic(|| {
+        let mut context = ctx.lock();
+        check_host_call(&mut context)?;
+
+        for (ext_name, method, args) in &calls {
+            validate_ext_call(&mut context, ext_name, method, args)?;
+        }
+
+        context.set_join_mode(join_mode);
+
+        let gas_remaining = context.gas_remaining;
+        let mut details = Vec::with_capacity(calls.len());
+        for (ext_name, method, args) in calls {
+            let suspension_id = Uuid::new_v4().to_string();
+            let seq = context.next_seq();
+            context.add_suspension(SuspensionState {
+                id: suspension_id.clone(),
+                seq,
+                extension_name: ext_name.clone(),
+                method: method.clone(),
+                args: args.clone(),
+                gas_remaining,
+            });
+            details.push(SuspensionDetails {
+                suspension_id,
+                seq,
+                extension_name: ext_name,
+                method,
+                args,
+                gas_remaining,
+            });
+        }
+
+        Ok(details)
+    })
+}
 
-    // Store suspension state
-    context.suspension = Some(SuspensionState {
-        id: suspension_id.clone(),
-        extension_name: ext_name.to_string(),
-        method: method.to_string(),
-        args: args.clone(),
-    });
+/// Deliver an extension call's result back into the context, resolving the
+/// pending suspension it answers.
+///
+/// This is the symmetric counterpart to [`ext_suspend`]/[`ext_suspend_many`]:
+/// the JS side calls this once the extension has settled, so the suspended
+/// `await` can either return a value or reject.
+///
+/// # Arguments
+/// * `ctx` - The execution context
+/// * `suspension_id` - The id returned in the original [`SuspensionDetails`]
+/// * `result` - The extension's outcome
+///
+/// # Returns
+/// * `Ok(value)` - The resolved value, to be handed back to the awaiting handler
+/// * `Err(code)` - [`error_codes::NOT_FOUND`] if `suspension_id` is unknown, or a
+///   code derived from the extension's `WasmError` (see [`error_code_for`]); the
+///   full `WasmError` is recorded on
+///   [`crate::context::ExecutionContext::last_extension_error`] either way
+pub fn ext_resume(
+    ctx: &SharedContext,
+    suspension_id: &str,
+    result: ExtensionOutcome,
+) -> HostResult<RuntimeValue> {
+    catch_panic(|| {
+        let mut context = ctx.lock();
+
+        if context.resolve_suspension(suspension_id).is_none() {
+            return Err(error_codes::NOT_FOUND);
+        }
+
+        match result {
+            ExtensionOutcome::Ok(value) => Ok(value),
+            ExtensionOutcome::Err(error) => {
+                let code = error_code_for(error.code);
+                context.record_extension_error(error);
+                Err(code)
+            }
+        }
+    })
+}
 
-    // Return suspension details
-    Ok(SuspensionDetails {
-        suspension_id,
-        extension_name: ext_name.to_string(),
-        method: method.to_string(),
-        args,
+/// List the ids of suspensions that have been handed out but not yet
+/// resolved by [`ext_resume`], so the orchestrator can detect a handler that
+/// suspended and was never resumed.
+///
+/// # Arguments
+/// * `ctx` - The execution context
+///
+/// # Returns
+/// * `Ok(ids)` - Outstanding suspension ids, in no particular order
+pub fn ext_pending(ctx: &SharedContext) -> HostResult<Vec<String>> {
+    catch_panic(|| {
+        let context = ctx.lock();
+        Ok(context.suspensions.keys().cloned().collect())
     })
 }
 
@@ -76,8 +285,10 @@ pub fn ext_suspend(
 /// * `Ok(true)` - Extension is available
 /// * `Ok(false)` - Extension is not available
 pub fn ext_exists(ctx: &SharedContext, ext_name: &str) -> HostResult<bool> {
-    let context = ctx.lock();
-    Ok(context.extension_registry.contains_key(ext_name))
+    catch_panic(|| {
+        let context = ctx.lock();
+        Ok(context.extension_registry.contains_key(ext_name))
+    })
 }
 
 /// Get available methods on an extension
@@ -90,12 +301,40 @@ pub fn ext_exists(ctx: &SharedContext, ext_name: &str) -> HostResult<bool> {
 /// * `Ok(methods)` - List of available methods
 /// * `Err(code)` - If extension not found
 pub fn ext_methods(ctx: &SharedContext, ext_name: &str) -> HostResult<Vec<String>> {
-    let context = ctx.lock();
+    catch_panic(|| {
+        let context = ctx.lock();
 
-    match context.extension_registry.get(ext_name) {
-        Some(methods) => Ok(methods.clone()),
-        None => Err(error_codes::NOT_FOUND),
-    }
+        match context.extension_registry.get(ext_name) {
+            Some(methods) => Ok(methods.keys().cloned().collect()),
+            None => Err(error_codes::NOT_FOUND),
+        }
+    })
+}
+
+/// Get a method's declared argument signature, for tooling that wants to
+/// introspect expected arguments ahead of a call (editors, handler linting)
+/// rather than discovering a mismatch from a rejected [`ext_suspend`].
+///
+/// # Arguments
+/// * `ctx` - The execution context
+/// * `ext_name` - The extension name
+/// * `method` - The method name
+///
+/// # Returns
+/// * `Ok(signature)` - The method's declared signature
+/// * `Err(code)` - [`error_codes::NOT_FOUND`] if the extension or method isn't registered
+pub fn ext_signature(
+    ctx: &SharedContext,
+    ext_name: &str,
+    method: &str,
+) -> HostResult<MethodSignature> {
+    catch_panic(|| {
+        let context = ctx.lock();
+        context
+            .extension_signature(ext_name, method)
+            .cloned()
+            .ok_or(error_codes::NOT_FOUND)
+    })
 }
 
 /// Get all registered extensions
@@ -106,26 +345,40 @@ pub fn ext_methods(ctx: &SharedContext, ext_name: &str) -> HostResult<Vec<String
 /// # Returns
 /// * `Ok(extensions)` - List of extension names
 pub fn ext_list(ctx: &SharedContext) -> HostResult<Vec<String>> {
-    let context = ctx.lock();
-    Ok(context.extension_registry.keys().cloned().collect())
+    catch_panic(|| {
+        let context = ctx.lock();
+        Ok(context.extension_registry.keys().cloned().collect())
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::capability::CapabilityToken;
-    use crate::context::{ExecutionContext, WasmContext};
+    use crate::context::{ExecutionContext, ParamSpec, ValueKind, WasmContext};
     use parking_lot::Mutex;
     use std::collections::HashMap;
     use std::sync::Arc;
 
+    /// A registry entry accepting 0 or 1 argument of any kind, enough for
+    /// the suspend/resume/capability tests in this module that aren't
+    /// specifically exercising signature validation (see `ext_signature`
+    /// tests below for that).
+    fn permissive_signature() -> MethodSignature {
+        MethodSignature::new(vec![ParamSpec::new("arg", ValueKind::Any).optional()])
+    }
+
+    fn registry(methods: &[&str]) -> HashMap<String, MethodSignature> {
+        methods
+            .iter()
+            .map(|m| (m.to_string(), permissive_signature()))
+            .collect()
+    }
+
     fn create_context_with_extensions() -> SharedContext {
         let mut extensions = HashMap::new();
-        extensions.insert(
-            "http".to_string(),
-            vec!["get".to_string(), "post".to_string()],
-        );
-        extensions.insert("storage".to_string(), vec!["read".to_string(), "write".to_string()]);
+        extensions.insert("http".to_string(), registry(&["get", "post"]));
+        extensions.insert("storage".to_string(), registry(&["read", "write"]));
 
         let wasm_ctx = WasmContext::new("test-panel", "test-handler")
             .with_extensions(extensions)
@@ -171,7 +424,7 @@ mod tests {
     #[test]
     fn test_ext_suspend_without_permission() {
         let mut extensions = HashMap::new();
-        extensions.insert("http".to_string(), vec!["get".to_string()]);
+        extensions.insert("http".to_string(), registry(&["get"]));
 
         let wasm_ctx = WasmContext::new("test-panel", "test-handler")
             .with_extensions(extensions)
@@ -224,9 +477,298 @@ mod tests {
         ext_suspend(&ctx, "http", "get", vec![RuntimeValue::String("url".to_string())]).unwrap();
 
         let context = ctx.lock();
-        assert!(context.suspension.is_some());
-        let suspension = context.suspension.as_ref().unwrap();
+        assert!(context.has_pending_suspensions());
+        let suspension = context.suspensions.values().next().unwrap();
         assert_eq!(suspension.extension_name, "http");
         assert_eq!(suspension.method, "get");
     }
+
+    #[test]
+    fn test_concurrent_ext_suspend_calls_are_all_tracked() {
+        let ctx = create_context_with_extensions();
+
+        ext_suspend(&ctx, "http", "get", vec![]).unwrap();
+        ext_suspend(&ctx, "storage", "read", vec![]).unwrap();
+
+        let context = ctx.lock();
+        assert_eq!(context.suspensions.len(), 2);
+    }
+
+    #[test]
+    fn test_ext_suspend_many_registers_all_calls() {
+        let ctx = create_context_with_extensions();
+
+        let details = ext_suspend_many(
+            &ctx,
+            vec![
+                ("http".to_string(), "get".to_string(), vec![]),
+                ("storage".to_string(), "read".to_string(), vec![]),
+            ],
+            JoinMode::All,
+        )
+        .unwrap();
+
+        assert_eq!(details.len(), 2);
+        assert_ne!(details[0].suspension_id, details[1].suspension_id);
+        assert_ne!(details[0].seq, details[1].seq);
+
+        let context = ctx.lock();
+        assert_eq!(context.suspensions.len(), 2);
+        assert_eq!(context.join_mode, JoinMode::All);
+    }
+
+    #[test]
+    fn test_ext_suspend_many_is_all_or_nothing_on_validation_failure() {
+        let ctx = create_context_with_extensions();
+
+        let result = ext_suspend_many(
+            &ctx,
+            vec![
+                ("http".to_string(), "get".to_string(), vec![]),
+                ("unknown".to_string(), "method".to_string(), vec![]),
+            ],
+            JoinMode::All,
+        );
+
+        assert_eq!(result, Err(error_codes::NOT_FOUND));
+
+        // The valid first call must not have been registered either
+        let context = ctx.lock();
+        assert!(!context.has_pending_suspensions());
+    }
+
+    #[test]
+    fn test_ext_resume_delivers_value_and_clears_suspension() {
+        let ctx = create_context_with_extensions();
+
+        let details = ext_suspend(&ctx, "http", "get", vec![]).unwrap();
+        let result = ext_resume(
+            &ctx,
+            &details.suspension_id,
+            ExtensionOutcome::Ok(RuntimeValue::String("pong".to_string())),
+        );
+
+        assert_eq!(result, Ok(RuntimeValue::String("pong".to_string())));
+
+        let context = ctx.lock();
+        assert!(!context.has_pending_suspensions());
+    }
+
+    #[test]
+    fn test_ext_resume_unknown_suspension_id() {
+        let ctx = create_context_with_extensions();
+
+        let result = ext_resume(&ctx, "not-a-real-id", ExtensionOutcome::Ok(RuntimeValue::Null));
+        assert_eq!(result, Err(error_codes::NOT_FOUND));
+    }
+
+    #[test]
+    fn test_ext_resume_surfaces_extension_error() {
+        let ctx = create_context_with_extensions();
+
+        let details = ext_suspend(&ctx, "http", "get", vec![]).unwrap();
+        let error = WasmError::execution_error("connection reset");
+        let result = ext_resume(&ctx, &details.suspension_id, ExtensionOutcome::Err(error));
+
+        assert_eq!(result, Err(error_codes::INTERNAL_ERROR));
+
+        let context = ctx.lock();
+        assert_eq!(
+            context.last_extension_error.as_ref().unwrap().message,
+            "connection reset"
+        );
+    }
+
+    #[test]
+    fn test_ext_resume_maps_known_error_codes() {
+        let ctx = create_context_with_extensions();
+
+        let details = ext_suspend(&ctx, "http", "get", vec![]).unwrap();
+        let error = WasmError::method_not_found("http", "get");
+        let result = ext_resume(&ctx, &details.suspension_id, ExtensionOutcome::Err(error));
+
+        assert_eq!(result, Err(error_codes::NOT_FOUND));
+    }
+
+    #[test]
+    fn test_ext_pending_lists_outstanding_suspensions() {
+        let ctx = create_context_with_extensions();
+
+        let first = ext_suspend(&ctx, "http", "get", vec![]).unwrap();
+        let second = ext_suspend(&ctx, "storage", "read", vec![]).unwrap();
+
+        let mut pending = ext_pending(&ctx).unwrap();
+        pending.sort();
+        let mut expected = vec![first.suspension_id, second.suspension_id];
+        expected.sort();
+        assert_eq!(pending, expected);
+
+        ext_resume(&ctx, &expected[0], ExtensionOutcome::Ok(RuntimeValue::Null)).unwrap();
+        assert_eq!(ext_pending(&ctx).unwrap(), vec![expected[1].clone()]);
+    }
+
+    fn context_with_capabilities(capabilities: Vec<CapabilityToken>) -> SharedContext {
+        let mut extensions = HashMap::new();
+        extensions.insert("http".to_string(), registry(&["get", "post"]));
+        extensions.insert("storage".to_string(), registry(&["read"]));
+
+        let wasm_ctx = WasmContext::new("test-panel", "test-handler")
+            .with_extensions(extensions)
+            .with_capabilities(capabilities);
+
+        Arc::new(Mutex::new(ExecutionContext::from_wasm_context(wasm_ctx)))
+    }
+
+    #[test]
+    fn test_ext_suspend_allowed_by_method_scoped_capability() {
+        let ctx = context_with_capabilities(vec![CapabilityToken::ExtensionMethod {
+            ext: "http".to_string(),
+            method: "get".to_string(),
+            constraints: Vec::new(),
+        }]);
+
+        assert!(ext_suspend(&ctx, "http", "get", vec![]).is_ok());
+    }
+
+    #[test]
+    fn test_ext_suspend_method_scoped_capability_does_not_cover_other_methods() {
+        let ctx = context_with_capabilities(vec![CapabilityToken::ExtensionMethod {
+            ext: "http".to_string(),
+            method: "get".to_string(),
+            constraints: Vec::new(),
+        }]);
+
+        let result = ext_suspend(&ctx, "http", "post", vec![]);
+        assert_eq!(result, Err(error_codes::PERMISSION_DENIED));
+    }
+
+    #[test]
+    fn test_ext_suspend_enforces_host_allowlist_constraint() {
+        use crate::capability::ArgConstraint;
+
+        let ctx = context_with_capabilities(vec![CapabilityToken::ExtensionMethod {
+            ext: "http".to_string(),
+            method: "get".to_string(),
+            constraints: vec![ArgConstraint::HostAllowlist(vec!["api.example.com".to_string()])],
+        }]);
+
+        let allowed = ext_suspend(
+            &ctx,
+            "http",
+            "get",
+            vec![RuntimeValue::String("https://api.example.com/widgets".to_string())],
+        );
+        assert!(allowed.is_ok());
+
+        let denied = ext_suspend(
+            &ctx,
+            "http",
+            "get",
+            vec![RuntimeValue::String("https://evil.example.net/widgets".to_string())],
+        );
+        assert_eq!(denied, Err(error_codes::PERMISSION_DENIED));
+
+        let context = ctx.lock();
+        let recorded = context.last_extension_error.as_ref().unwrap();
+        assert!(recorded.message.contains("evil.example.net"));
+    }
+
+    #[test]
+    fn test_ext_suspend_enforces_key_prefix_constraint() {
+        use crate::capability::ArgConstraint;
+
+        let ctx = context_with_capabilities(vec![CapabilityToken::ExtensionMethod {
+            ext: "storage".to_string(),
+            method: "read".to_string(),
+            constraints: vec![ArgConstraint::KeyPrefix("tenant-42.".to_string())],
+        }]);
+
+        assert!(ext_suspend(
+            &ctx,
+            "storage",
+            "read",
+            vec![RuntimeValue::String("tenant-42.profile".to_string())]
+        )
+        .is_ok());
+
+        let denied = ext_suspend(
+            &ctx,
+            "storage",
+            "read",
+            vec![RuntimeValue::String("tenant-7.profile".to_string())],
+        );
+        assert_eq!(denied, Err(error_codes::PERMISSION_DENIED));
+    }
+
+    fn context_with_typed_signature() -> SharedContext {
+        let mut methods = HashMap::new();
+        methods.insert(
+            "get".to_string(),
+            MethodSignature::new(vec![
+                ParamSpec::new("url", ValueKind::String),
+                ParamSpec::new("options", ValueKind::Object).optional(),
+            ]),
+        );
+        let mut extensions = HashMap::new();
+        extensions.insert("http".to_string(), methods);
+
+        let wasm_ctx = WasmContext::new("test-panel", "test-handler")
+            .with_extensions(extensions)
+            .with_capabilities(vec![CapabilityToken::ExtensionAll]);
+
+        Arc::new(Mutex::new(ExecutionContext::from_wasm_context(wasm_ctx)))
+    }
+
+    #[test]
+    fn test_ext_suspend_rejects_wrong_argument_kind() {
+        let ctx = context_with_typed_signature();
+
+        let result = ext_suspend(&ctx, "http", "get", vec![RuntimeValue::Number(1.0)]);
+        assert_eq!(result, Err(error_codes::INVALID_ARGUMENT));
+
+        let context = ctx.lock();
+        let recorded = context.last_extension_error.as_ref().unwrap();
+        assert_eq!(recorded.code, ErrorCode::InvalidArgument);
+        assert!(recorded.message.contains("url"));
+    }
+
+    #[test]
+    fn test_ext_suspend_rejects_missing_required_argument() {
+        let ctx = context_with_typed_signature();
+
+        let result = ext_suspend(&ctx, "http", "get", vec![]);
+        assert_eq!(result, Err(error_codes::INVALID_ARGUMENT));
+    }
+
+    #[test]
+    fn test_ext_suspend_allows_trailing_optional_argument_to_be_omitted() {
+        let ctx = context_with_typed_signature();
+
+        let result = ext_suspend(
+            &ctx,
+            "http",
+            "get",
+            vec![RuntimeValue::String("https://api.example.com".to_string())],
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_ext_signature_returns_declared_signature() {
+        let ctx = context_with_typed_signature();
+
+        let signature = ext_signature(&ctx, "http", "get").unwrap();
+        assert_eq!(signature.params.len(), 2);
+        assert_eq!(signature.params[0].name, "url");
+        assert!(!signature.params[0].optional);
+        assert!(signature.params[1].optional);
+    }
+
+    #[test]
+    fn test_ext_signature_unknown_method() {
+        let ctx = context_with_typed_signature();
+
+        let result = ext_signature(&ctx, "http", "post");
+        assert_eq!(result, Err(error_codes::NOT_FOUND));
+    }
 }
@@ -4,8 +4,9 @@
 //! Logging is always allowed (no capability required) but output is captured
 //! in the execution context.
 
-use super::{HostResult, SharedContext};
-use crate::context::LogLevel;
+use super::{catch_panic, HostResult, SharedContext};
+use crate::context::{LogLevel, RuntimeValue};
+use std::collections::HashMap;
 
 /// Log a message
 ///
@@ -19,43 +20,112 @@ use crate::context::LogLevel;
 /// # Returns
 /// * `Ok(())` - Always succeeds
 pub fn log(ctx: &SharedContext, level: i32, message: &str) -> HostResult<()> {
-    let mut context = ctx.lock();
-    
-    // Convert level to enum
-    let log_level = LogLevel::from(level);
-    
-    // Record the log message
-    context.add_log(log_level, message.to_string());
-    
-    // Also log to tracing for debugging
-    match log_level {
-        LogLevel::Debug => tracing::debug!(
-            panel_id = %context.panel_id,
-            handler = %context.handler_name,
-            "{}",
-            message
-        ),
-        LogLevel::Info => tracing::info!(
-            panel_id = %context.panel_id,
-            handler = %context.handler_name,
-            "{}",
-            message
-        ),
-        LogLevel::Warn => tracing::warn!(
-            panel_id = %context.panel_id,
-            handler = %context.handler_name,
-            "{}",
-            message
-        ),
-        LogLevel::Error => tracing::error!(
-            panel_id = %context.panel_id,
-            handler = %context.handler_name,
-            "{}",
-            message
-        ),
-    }
-    
-    Ok(())
+    catch_panic(|| {
+        let mut context = ctx.lock();
+
+        // Convert level to enum
+        let log_level = LogLevel::from(level);
+
+        // Record the log message
+        context.add_log(log_level, message.to_string());
+
+        // Also log to tracing for debugging
+        match log_level {
+            LogLevel::Debug => tracing::debug!(
+                panel_id = %context.panel_id,
+                handler = %context.handler_name,
+                "{}",
+                message
+            ),
+            LogLevel::Info => tracing::info!(
+                panel_id = %context.panel_id,
+                handler = %context.handler_name,
+                "{}",
+                message
+            ),
+            LogLevel::Warn => tracing::warn!(
+                panel_id = %context.panel_id,
+                handler = %context.handler_name,
+                "{}",
+                message
+            ),
+            LogLevel::Error => tracing::error!(
+                panel_id = %context.panel_id,
+                handler = %context.handler_name,
+                "{}",
+                message
+            ),
+        }
+
+        Ok(())
+    })
+}
+
+/// Log a message with structured key-value context
+///
+/// Like [`log`], but attaches `fields` to the captured
+/// [`crate::context::LogMessage`] and forwards them into the underlying
+/// `tracing` event alongside `panel_id`/`handler`, giving operators
+/// queryable, typed attributes (e.g. `user_id`, `latency_ms`) instead of a
+/// pre-interpolated message string.
+///
+/// # Arguments
+/// * `ctx` - The execution context
+/// * `level` - The log level (0=debug, 1=info, 2=warn, 3=error)
+/// * `message` - The message to log
+/// * `fields` - Structured key-value context to attach
+///
+/// # Returns
+/// * `Ok(())` - Always succeeds
+pub fn log_structured(
+    ctx: &SharedContext,
+    level: i32,
+    message: &str,
+    fields: HashMap<String, RuntimeValue>,
+) -> HostResult<()> {
+    catch_panic(|| {
+        let mut context = ctx.lock();
+
+        // Convert level to enum
+        let log_level = LogLevel::from(level);
+
+        // Record the log message, fields and all
+        context.add_log_with_fields(log_level, message.to_string(), Some(fields.clone()));
+
+        // Also log to tracing for debugging, carrying the fields along
+        match log_level {
+            LogLevel::Debug => tracing::debug!(
+                panel_id = %context.panel_id,
+                handler = %context.handler_name,
+                fields = ?fields,
+                "{}",
+                message
+            ),
+            LogLevel::Info => tracing::info!(
+                panel_id = %context.panel_id,
+                handler = %context.handler_name,
+                fields = ?fields,
+                "{}",
+                message
+            ),
+            LogLevel::Warn => tracing::warn!(
+                panel_id = %context.panel_id,
+                handler = %context.handler_name,
+                fields = ?fields,
+                "{}",
+                message
+            ),
+            LogLevel::Error => tracing::error!(
+                panel_id = %context.panel_id,
+                handler = %context.handler_name,
+                fields = ?fields,
+                "{}",
+                message
+            ),
+        }
+
+        Ok(())
+    })
 }
 
 /// Log at debug level
@@ -120,6 +190,35 @@ mod tests {
         assert_eq!(context.log_messages[0].level, LogLevel::Info);
     }
 
+    #[test]
+    fn test_log_structured_attaches_fields() {
+        let ctx = create_context();
+
+        let mut fields = HashMap::new();
+        fields.insert("user_id".to_string(), RuntimeValue::String("u-1".to_string()));
+        fields.insert("latency_ms".to_string(), RuntimeValue::Number(12.5));
+
+        let result = log_structured(&ctx, 1, "request handled", fields);
+        assert!(result.is_ok());
+
+        let context = ctx.lock();
+        assert_eq!(context.log_messages.len(), 1);
+        assert_eq!(context.log_messages[0].message, "request handled");
+        let recorded = context.log_messages[0].fields.as_ref().unwrap();
+        assert_eq!(recorded.get("user_id"), Some(&RuntimeValue::String("u-1".to_string())));
+        assert_eq!(recorded.get("latency_ms"), Some(&RuntimeValue::Number(12.5)));
+    }
+
+    #[test]
+    fn test_log_has_no_fields() {
+        let ctx = create_context();
+
+        log(&ctx, 1, "plain message").unwrap();
+
+        let context = ctx.lock();
+        assert!(context.log_messages[0].fields.is_none());
+    }
+
     #[test]
     fn test_log_levels() {
         let ctx = create_context();
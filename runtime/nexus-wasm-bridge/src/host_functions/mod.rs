@@ -6,10 +6,11 @@
 pub mod events;
 pub mod extension;
 pub mod logging;
+pub mod op_driver;
 pub mod state;
 pub mod view;
 
-use crate::context::ExecutionContext;
+use crate::context::{ExecutionContext, ResourceLimitKind};
 use crate::error::error_codes;
 use parking_lot::Mutex;
 use std::sync::Arc;
@@ -45,10 +46,80 @@ impl HostFunctions {
         }
     }
 
+    /// Charge `cost` units of gas for the basic block about to execute. This
+    /// is the metering host call a compiled module invokes on entry to each
+    /// block; once the budget reaches zero it traps rather than decrementing
+    /// further.
+    pub fn charge_gas(&self, cost: u64) -> Result<(), i32> {
+        let mut ctx = self.context.lock();
+        if ctx.charge_gas(cost) {
+            Ok(())
+        } else {
+            Err(error_codes::GAS_EXHAUSTED)
+        }
+    }
+
     /// Get the execution context
     pub fn context(&self) -> SharedContext {
         Arc::clone(&self.context)
     }
+
+    /// Run a named host call, accounting it against the host-call budget
+    /// and isolating any panic so it can't unwind through the `SharedContext`
+    /// lock and take down the host thread.
+    ///
+    /// This is the richer counterpart to the free [`catch_panic`] helper
+    /// that `state`/`events`/`view`/`extension` wrap their bodies in
+    /// directly: in addition to catching the panic, it runs the host-call
+    /// limit check and records the call site and panic payload onto
+    /// [`crate::context::ExecutionContext::last_panic`], so a crashing host
+    /// function surfaces more than a bare error code once the final
+    /// `WasmError` is built.
+    #[track_caller]
+    pub fn call_guarded<T>(
+        &self,
+        name: &str,
+        f: impl FnOnce() -> HostResult<T> + std::panic::UnwindSafe,
+    ) -> HostResult<T> {
+        self.check_host_call_limit()?;
+
+        let location = std::panic::Location::caller();
+        match std::panic::catch_unwind(f) {
+            Ok(result) => result,
+            Err(payload) => {
+                let message = crate::error::describe_panic(payload);
+                self.context.lock().record_panic(name, message, location.to_string());
+                Err(error_codes::INTERNAL_ERROR)
+            }
+        }
+    }
+}
+
+/// Count a host function call against the execution's
+/// `ResourceLimits::max_host_calls` ceiling (set on `context` via
+/// [`ExecutionContext::set_resource_limits`]), returning
+/// [`error_codes::RESOURCE_LIMIT`] once it's crossed. Every `pub fn` in
+/// `state`/`events`/`view` that a compiled handler can call directly, and
+/// `ext_suspend`/`ext_suspend_many` in `extension`, runs this first, before
+/// its capability check, so a handler stuck in a call-heavy loop is cut off
+/// rather than left to run unmetered. The `logging` functions and the other
+/// `extension` functions (`ext_exists`, `ext_resume`, ...) are not counted.
+pub(crate) fn check_host_call(context: &mut ExecutionContext) -> HostResult<()> {
+    context.increment_host_calls();
+    if context.resource_exhausted == Some(ResourceLimitKind::HostCalls) {
+        Err(error_codes::RESOURCE_LIMIT)
+    } else {
+        Ok(())
+    }
+}
+
+/// Run a host function's body, catching a panic instead of letting it
+/// unwind through the `SharedContext` lock and abort the host thread. Every
+/// `pub fn` in this module that a compiled handler can call directly wraps
+/// its body in this, mirroring the same isolation applied around the inner
+/// execution in `WasmInstance::execute`/`resume`.
+pub(crate) fn catch_panic<T>(f: impl FnOnce() -> HostResult<T> + std::panic::UnwindSafe) -> HostResult<T> {
+    std::panic::catch_unwind(f).unwrap_or(Err(error_codes::INTERNAL_ERROR))
 }
 
 /// Trait for host function implementations
@@ -89,4 +160,72 @@ mod tests {
         assert!(host.check_host_call_limit().is_ok()); // 2
         assert!(host.check_host_call_limit().is_err()); // 3 - exceeds limit
     }
+
+    #[test]
+    fn test_charge_gas_traps_once_budget_reaches_zero() {
+        let ctx = create_test_context();
+        ctx.lock().set_gas_remaining(100);
+        let host = HostFunctions::new(ctx, 100);
+
+        assert!(host.charge_gas(60).is_ok());
+        assert_eq!(
+            host.charge_gas(60),
+            Err(crate::error::error_codes::GAS_EXHAUSTED)
+        );
+    }
+
+    #[test]
+    fn test_charge_gas_unlimited_by_default() {
+        let ctx = create_test_context();
+        let host = HostFunctions::new(ctx, 100);
+
+        assert!(host.charge_gas(u64::MAX / 2).is_ok());
+    }
+
+    #[test]
+    fn test_catch_panic_converts_to_internal_error() {
+        let result: HostResult<()> = catch_panic(|| panic!("boom"));
+        assert_eq!(result, Err(error_codes::INTERNAL_ERROR));
+    }
+
+    #[test]
+    fn test_catch_panic_passes_through_ok() {
+        let result = catch_panic(|| Ok(42));
+        assert_eq!(result, Ok(42));
+    }
+
+    #[test]
+    fn test_call_guarded_passes_through_ok() {
+        let ctx = create_test_context();
+        let host = HostFunctions::new(ctx, 100);
+
+        let result = host.call_guarded("state_get", || Ok(42));
+        assert_eq!(result, Ok(42));
+    }
+
+    #[test]
+    fn test_call_guarded_converts_panic_and_records_details() {
+        let ctx = create_test_context();
+        let host = HostFunctions::new(ctx.clone(), 100);
+
+        let result: HostResult<()> = host.call_guarded("state_set", || panic!("boom"));
+        assert_eq!(result, Err(error_codes::INTERNAL_ERROR));
+
+        let context = ctx.lock();
+        let panic_details = context.last_panic.as_ref().unwrap();
+        assert_eq!(panic_details.host_function, "state_set");
+        assert_eq!(panic_details.message, "boom");
+    }
+
+    #[test]
+    fn test_call_guarded_respects_host_call_limit() {
+        let ctx = create_test_context();
+        let host = HostFunctions::new(ctx, 1);
+
+        assert!(host.call_guarded("state_get", || Ok(())).is_ok());
+        assert_eq!(
+            host.call_guarded("state_get", || Ok(())),
+            Err(error_codes::RESOURCE_LIMIT)
+        );
+    }
 }
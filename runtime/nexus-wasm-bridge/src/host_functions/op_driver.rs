@@ -0,0 +1,221 @@
+//! Op-driver fast path for extension calls.
+//!
+//! [`ext_suspend`](super::extension::ext_suspend) always registers a
+//! [`SuspensionState`] and hands it back to the caller for a later
+//! [`ext_resume`](super::extension::ext_resume) round-trip — necessary for
+//! ops that genuinely cross a process boundary, but wasteful for the common
+//! case where the host side (a JS function registered up front) can answer
+//! within the same tick. A [`HostOpRegistry`] lets a runtime register such
+//! host-side answerers by `extension.method`, and [`drive_registered_ops`]
+//! resolves every currently pending suspension that has one, concurrently,
+//! via a local `FuturesUnordered` instead of one suspend/resume FFI round-trip
+//! per call. Suspensions with no matching registered op are left untouched
+//! for the caller to hand off to the regular suspend/resume path.
+
+use super::extension::{ext_resume, ExtensionOutcome};
+use super::SharedContext;
+use crate::context::{RuntimeValue, SuspensionState};
+use crate::error::WasmError;
+use futures::future::BoxFuture;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A host-side answerer for one `extension.method` pair, registered up front
+/// via [`HostOpRegistry::register`]. Takes the call's arguments and returns a
+/// future resolving to the extension's outcome, matching the same
+/// success/failure split `ext_resume` expects from a suspend/resume
+/// round-trip.
+pub type HostOpFn = dyn Fn(Vec<RuntimeValue>) -> BoxFuture<'static, Result<RuntimeValue, WasmError>>
+    + Send
+    + Sync;
+
+/// Key a registered op by its `extension.method` pair, matching a pending
+/// [`SuspensionState`]'s `extension_name`/`method`.
+fn op_key(extension_name: &str, method: &str) -> String {
+    format!("{}.{}", extension_name, method)
+}
+
+/// A set of host-side answerers, registered up front, that [`WasmRuntime`]
+/// consults before leaving a suspension for the caller's manual
+/// `resume_handler`. Built once by a caller (e.g. the N-API binding wrapping
+/// a `register_host_ops({...})` call from Node) and reused across many
+/// executions.
+///
+/// [`WasmRuntime`]: crate::engine::WasmRuntime
+#[derive(Default)]
+pub struct HostOpRegistry {
+    ops: HashMap<String, Arc<HostOpFn>>,
+}
+
+impl HostOpRegistry {
+    /// Create an empty registry; every suspension falls back to the manual
+    /// suspend/resume path until ops are registered on it
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) the answerer for `extension_name.method`
+    pub fn register(&mut self, extension_name: &str, method: &str, op: Arc<HostOpFn>) {
+        self.ops.insert(op_key(extension_name, method), op);
+    }
+
+    /// Merge `ops` (already keyed as `"extension.method"`, e.g. from
+    /// [`WasmRuntime::register_host_ops`]) into this registry, replacing any
+    /// existing answerer for a key that appears in both.
+    ///
+    /// [`WasmRuntime::register_host_ops`]: crate::engine::WasmRuntime::register_host_ops
+    pub fn merge(&mut self, ops: HashMap<String, Arc<HostOpFn>>) {
+        self.ops.extend(ops);
+    }
+
+    /// Look up the registered answerer for `extension_name.method`, if any
+    pub fn get(&self, extension_name: &str, method: &str) -> Option<Arc<HostOpFn>> {
+        self.ops.get(&op_key(extension_name, method)).cloned()
+    }
+
+    /// Whether no ops have been registered at all
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+/// Resolve every currently pending suspension on `ctx` that has a matching
+/// registered op in `registry`, concurrently, resuming each inline as its op
+/// future completes rather than waiting for all of them.
+///
+/// Returns the ids of suspensions this pass resolved. Suspensions with no
+/// matching op are left pending on `ctx` untouched, so the caller can still
+/// hand them to the regular suspend/resume path; callers loop this (as
+/// [`WasmRuntime::execute_handler`] does) since resolving one suspension may
+/// unblock the handler into registering more before it suspends again.
+///
+/// [`WasmRuntime::execute_handler`]: crate::engine::WasmRuntime::execute_handler
+pub async fn drive_registered_ops(ctx: &SharedContext, registry: &HostOpRegistry) -> Vec<String> {
+    if registry.is_empty() {
+        return Vec::new();
+    }
+
+    let pending: Vec<SuspensionState> = ctx.lock().suspensions.values().cloned().collect();
+
+    let mut driven = FuturesUnordered::new();
+    for suspension in pending {
+        if let Some(op) = registry.get(&suspension.extension_name, &suspension.method) {
+            let args = suspension.args.clone();
+            let id = suspension.id.clone();
+            driven.push(async move { (id, op(args).await) });
+        }
+    }
+
+    let mut resolved = Vec::new();
+    while let Some((suspension_id, outcome)) = driven.next().await {
+        let extension_outcome = match outcome {
+            Ok(value) => ExtensionOutcome::Ok(value),
+            Err(error) => ExtensionOutcome::Err(error),
+        };
+        if ext_resume(ctx, &suspension_id, extension_outcome).is_ok() {
+            resolved.push(suspension_id);
+        }
+    }
+
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capability::CapabilityToken;
+    use crate::context::{ExecutionContext, MethodSignature, ParamSpec, ValueKind, WasmContext};
+    use parking_lot::Mutex;
+    use std::collections::HashMap as StdHashMap;
+
+    fn permissive_signature() -> MethodSignature {
+        MethodSignature::new(vec![ParamSpec::new("arg", ValueKind::Any).optional()])
+    }
+
+    fn create_context_with_extensions() -> SharedContext {
+        let mut extensions = StdHashMap::new();
+        let mut http = StdHashMap::new();
+        http.insert("get".to_string(), permissive_signature());
+        extensions.insert("http".to_string(), http);
+
+        let wasm_ctx = WasmContext::new("test-panel", "test-handler")
+            .with_extensions(extensions)
+            .with_capabilities(vec![CapabilityToken::ExtensionAll]);
+
+        Arc::new(Mutex::new(ExecutionContext::from_wasm_context(wasm_ctx)))
+    }
+
+    fn ready_op(value: RuntimeValue) -> Arc<HostOpFn> {
+        Arc::new(move |_args| {
+            let value = value.clone();
+            Box::pin(async move { Ok(value) })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_drive_registered_ops_resolves_matching_suspension() {
+        let ctx = create_context_with_extensions();
+        let details =
+            super::super::extension::ext_suspend(&ctx, "http", "get", vec![]).unwrap();
+
+        let mut registry = HostOpRegistry::new();
+        registry.register("http", "get", ready_op(RuntimeValue::Bool(true)));
+
+        let resolved = drive_registered_ops(&ctx, &registry).await;
+
+        assert_eq!(resolved, vec![details.suspension_id]);
+        assert!(ctx.lock().suspensions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_drive_registered_ops_leaves_unmatched_suspension_pending() {
+        let ctx = create_context_with_extensions();
+        super::super::extension::ext_suspend(&ctx, "http", "get", vec![]).unwrap();
+
+        // No op registered at all, so nothing should be touched.
+        let registry = HostOpRegistry::new();
+        let resolved = drive_registered_ops(&ctx, &registry).await;
+
+        assert!(resolved.is_empty());
+        assert_eq!(ctx.lock().suspensions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_drive_registered_ops_resolves_only_the_covered_call() {
+        let ctx = create_context_with_extensions();
+        let covered = super::super::extension::ext_suspend(&ctx, "http", "get", vec![]).unwrap();
+
+        let mut registry = HostOpRegistry::new();
+        registry.register("http", "get", ready_op(RuntimeValue::Null));
+
+        // Manually register a second suspension for an extension/method the
+        // registry doesn't cover.
+        ctx.lock().add_suspension(SuspensionState {
+            id: "uncovered".to_string(),
+            seq: 0,
+            extension_name: "storage".to_string(),
+            method: "read".to_string(),
+            args: vec![],
+            gas_remaining: 0,
+        });
+
+        let resolved = drive_registered_ops(&ctx, &registry).await;
+
+        assert_eq!(resolved, vec![covered.suspension_id]);
+        assert_eq!(ctx.lock().suspensions.len(), 1);
+        assert!(ctx.lock().suspensions.contains_key("uncovered"));
+    }
+
+    #[tokio::test]
+    async fn test_drive_registered_ops_with_empty_registry_is_a_noop() {
+        let ctx = create_context_with_extensions();
+        super::super::extension::ext_suspend(&ctx, "http", "get", vec![]).unwrap();
+
+        let registry = HostOpRegistry::new();
+        let resolved = drive_registered_ops(&ctx, &registry).await;
+
+        assert!(resolved.is_empty());
+        assert_eq!(ctx.lock().suspensions.len(), 1);
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! These functions provide read/write access to the panel's reactive state.
 
-use super::{HostResult, SharedContext};
+use super::{catch_panic, check_host_call, HostResult, SharedContext};
 use crate::context::{MutationOperation, RuntimeValue, StateMutation};
 use crate::error::error_codes;
 
@@ -17,16 +17,19 @@ use crate::error::error_codes;
 /// * `Ok(None)` - If the key doesn't exist
 /// * `Err(code)` - If permission denied or error
 pub fn state_get(ctx: &SharedContext, key: &str) -> HostResult<Option<RuntimeValue>> {
-    let context = ctx.lock();
-
-    // Check capability
-    let required = format!("state:read:{}", key);
-    if !context.has_capability(&required) && !context.has_capability("state:read:*") {
-        return Err(error_codes::PERMISSION_DENIED);
-    }
-
-    // Get from snapshot
-    Ok(context.state_snapshot.get(key).cloned())
+    catch_panic(|| {
+        let mut context = ctx.lock();
+        check_host_call(&mut context)?;
+
+        // Check capability
+        let required = format!("state:read:{}", key);
+        if !context.has_capability(&required) && !context.has_capability("state:read:*") {
+            return Err(error_codes::PERMISSION_DENIED);
+        }
+
+        // Get from snapshot
+        Ok(context.state_snapshot.get(key).cloned())
+    })
 }
 
 /// Set a state value
@@ -38,24 +41,26 @@ pub fn state_get(ctx: &SharedContext, key: &str) -> HostResult<Option<RuntimeVal
 ///
 /// # Returns
 /// * `Ok(())` - Success
-/// * `Err(code)` - If permission denied or error
+/// * `Err(code)` - If permission denied, the host-call or state-mutation
+///   ceiling was reached, or another error
 pub fn state_set(ctx: &SharedContext, key: &str, value: RuntimeValue) -> HostResult<()> {
-    let mut context = ctx.lock();
-
-    // Check capability
-    let required = format!("state:write:{}", key);
-    if !context.has_capability(&required) && !context.has_capability("state:write:*") {
-        return Err(error_codes::PERMISSION_DENIED);
-    }
-
-    // Record mutation
-    context.add_mutation(StateMutation {
-        key: key.to_string(),
-        value,
-        operation: MutationOperation::Set,
-    });
-
-    Ok(())
+    catch_panic(|| {
+        let mut context = ctx.lock();
+        check_host_call(&mut context)?;
+
+        // Check capability
+        let required = format!("state:write:{}", key);
+        if !context.has_capability(&required) && !context.has_capability("state:write:*") {
+            return Err(error_codes::PERMISSION_DENIED);
+        }
+
+        // Record mutation
+        if !context.add_mutation(StateMutation::set(key, value)) {
+            return Err(error_codes::RESOURCE_LIMIT);
+        }
+
+        Ok(())
+    })
 }
 
 /// Delete a state value
@@ -66,24 +71,80 @@ pub fn state_set(ctx: &SharedContext, key: &str, value: RuntimeValue) -> HostRes
 ///
 /// # Returns
 /// * `Ok(())` - Success
-/// * `Err(code)` - If permission denied or error
+/// * `Err(code)` - If permission denied, the host-call or state-mutation
+///   ceiling was reached, or another error
 pub fn state_delete(ctx: &SharedContext, key: &str) -> HostResult<()> {
-    let mut context = ctx.lock();
-
-    // Check capability
-    let required = format!("state:write:{}", key);
-    if !context.has_capability(&required) && !context.has_capability("state:write:*") {
-        return Err(error_codes::PERMISSION_DENIED);
-    }
-
-    // Record deletion mutation
-    context.add_mutation(StateMutation {
-        key: key.to_string(),
-        value: RuntimeValue::Null,
-        operation: MutationOperation::Delete,
-    });
+    catch_panic(|| {
+        let mut context = ctx.lock();
+        check_host_call(&mut context)?;
+
+        // Check capability
+        let required = format!("state:write:{}", key);
+        if !context.has_capability(&required) && !context.has_capability("state:write:*") {
+            return Err(error_codes::PERMISSION_DENIED);
+        }
+
+        // Record deletion mutation
+        if !context.add_mutation(StateMutation::delete(key)) {
+            return Err(error_codes::RESOURCE_LIMIT);
+        }
+
+        Ok(())
+    })
+}
 
-    Ok(())
+/// Set a state value only if it still matches an expected value
+///
+/// This is a best-effort optimistic-concurrency check against the handler's
+/// own `state_snapshot` (the view of state as of when the handler started),
+/// so a stale read inside the *same* handler invocation is rejected
+/// immediately instead of silently recording a clobbering mutation. The
+/// authoritative check against the truly live value happens when the
+/// mutation is applied outside this crate; that applier enforces the same
+/// precondition and rejects the commit with
+/// [`crate::error::error_codes::CONFLICT`] if it no longer holds.
+///
+/// # Arguments
+/// * `ctx` - The execution context
+/// * `key` - The state key to write
+/// * `expected` - The value the key must currently hold (`None` meaning the
+///   key must not exist yet)
+/// * `value` - The value to set if `expected` still matches
+///
+/// # Returns
+/// * `Ok(())` - Success
+/// * `Err(code)` - If permission denied, or [`error_codes::CONFLICT`] if
+///   `expected` no longer matches
+pub fn state_compare_and_set(
+    ctx: &SharedContext,
+    key: &str,
+    expected: Option<RuntimeValue>,
+    value: RuntimeValue,
+) -> HostResult<()> {
+    catch_panic(|| {
+        let mut context = ctx.lock();
+        check_host_call(&mut context)?;
+
+        // Check capability
+        let required = format!("state:write:{}", key);
+        if !context.has_capability(&required) && !context.has_capability("state:write:*") {
+            return Err(error_codes::PERMISSION_DENIED);
+        }
+
+        // Reject early if this handler's own view of state already
+        // disagrees with `expected`; the mutation applier re-checks against
+        // the truly live value when the mutation is committed.
+        if context.state_snapshot.get(key) != expected.as_ref() {
+            return Err(error_codes::CONFLICT);
+        }
+
+        // Record mutation
+        if !context.add_mutation(StateMutation::compare_and_set(key, expected, value)) {
+            return Err(error_codes::RESOURCE_LIMIT);
+        }
+
+        Ok(())
+    })
 }
 
 /// Check if a state key exists
@@ -97,15 +158,18 @@ pub fn state_delete(ctx: &SharedContext, key: &str) -> HostResult<()> {
 /// * `Ok(false)` - Key doesn't exist
 /// * `Err(code)` - If permission denied
 pub fn state_has(ctx: &SharedContext, key: &str) -> HostResult<bool> {
-    let context = ctx.lock();
-
-    // Check capability (reading requires read permission)
-    let required = format!("state:read:{}", key);
-    if !context.has_capability(&required) && !context.has_capability("state:read:*") {
-        return Err(error_codes::PERMISSION_DENIED);
-    }
-
-    Ok(context.state_snapshot.contains_key(key))
+    catch_panic(|| {
+        let mut context = ctx.lock();
+        check_host_call(&mut context)?;
+
+        // Check capability (reading requires read permission)
+        let required = format!("state:read:{}", key);
+        if !context.has_capability(&required) && !context.has_capability("state:read:*") {
+            return Err(error_codes::PERMISSION_DENIED);
+        }
+
+        Ok(context.state_snapshot.contains_key(key))
+    })
 }
 
 /// Get all state keys
@@ -117,14 +181,17 @@ pub fn state_has(ctx: &SharedContext, key: &str) -> HostResult<bool> {
 /// * `Ok(keys)` - List of all state keys
 /// * `Err(code)` - If permission denied
 pub fn state_keys(ctx: &SharedContext) -> HostResult<Vec<String>> {
-    let context = ctx.lock();
+    catch_panic(|| {
+        let mut context = ctx.lock();
+        check_host_call(&mut context)?;
 
-    // Requires state:read:* capability
-    if !context.has_capability("state:read:*") {
-        return Err(error_codes::PERMISSION_DENIED);
-    }
+        // Requires state:read:* capability
+        if !context.has_capability("state:read:*") {
+            return Err(error_codes::PERMISSION_DENIED);
+        }
 
-    Ok(context.state_snapshot.keys().cloned().collect())
+        Ok(context.state_snapshot.keys().cloned().collect())
+    })
 }
 
 #[cfg(test)]
@@ -210,6 +277,71 @@ mod tests {
         assert_eq!(context.state_mutations[0].operation, MutationOperation::Delete);
     }
 
+    #[test]
+    fn test_state_compare_and_set_succeeds_when_expected_matches() {
+        let ctx = create_context_with_caps(vec![CapabilityToken::StateWriteAll]);
+
+        let result = state_compare_and_set(
+            &ctx,
+            "count",
+            Some(RuntimeValue::Number(42.0)),
+            RuntimeValue::Number(100.0),
+        );
+        assert!(result.is_ok());
+
+        let context = ctx.lock();
+        assert_eq!(context.state_mutations.len(), 1);
+        assert_eq!(
+            context.state_mutations[0].operation,
+            MutationOperation::CompareAndSet {
+                expected: Some(RuntimeValue::Number(42.0))
+            }
+        );
+    }
+
+    #[test]
+    fn test_state_compare_and_set_rejects_on_mismatch() {
+        let ctx = create_context_with_caps(vec![CapabilityToken::StateWriteAll]);
+
+        let result = state_compare_and_set(
+            &ctx,
+            "count",
+            Some(RuntimeValue::Number(1.0)),
+            RuntimeValue::Number(100.0),
+        );
+        assert_eq!(result, Err(error_codes::CONFLICT));
+
+        // No mutation should have been recorded
+        let context = ctx.lock();
+        assert_eq!(context.state_mutations.len(), 0);
+    }
+
+    #[test]
+    fn test_state_compare_and_set_none_requires_key_absent() {
+        let ctx = create_context_with_caps(vec![CapabilityToken::StateWriteAll]);
+
+        // 'count' already exists, so expecting it absent is a conflict
+        let result = state_compare_and_set(&ctx, "count", None, RuntimeValue::Number(1.0));
+        assert_eq!(result, Err(error_codes::CONFLICT));
+
+        // A genuinely absent key succeeds
+        let result = state_compare_and_set(&ctx, "missing", None, RuntimeValue::Number(1.0));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_state_compare_and_set_without_permission() {
+        let ctx = create_context_with_caps(vec![CapabilityToken::StateReadAll]); // Only read
+
+        let result = state_compare_and_set(
+            &ctx,
+            "count",
+            Some(RuntimeValue::Number(42.0)),
+            RuntimeValue::Number(100.0),
+        );
+        assert_eq!(result, Err(error_codes::PERMISSION_DENIED));
+    }
+
     #[test]
     fn test_state_has() {
         let ctx = create_context_with_caps(vec![CapabilityToken::StateReadAll]);
@@ -234,4 +366,23 @@ mod tests {
         // Specific capability doesn't grant keys access
         assert_eq!(state_keys(&ctx), Err(error_codes::PERMISSION_DENIED));
     }
+
+    #[test]
+    fn test_state_set_rejects_once_mutation_ceiling_reached() {
+        let ctx = create_context_with_caps(vec![CapabilityToken::StateWriteAll]);
+        ctx.lock().set_resource_limits(&crate::config::ResourceLimits {
+            max_state_mutations: 1,
+            ..crate::config::ResourceLimits::default()
+        });
+
+        assert!(state_set(&ctx, "count", RuntimeValue::Number(1.0)).is_ok());
+        assert_eq!(
+            state_set(&ctx, "count", RuntimeValue::Number(2.0)),
+            Err(error_codes::RESOURCE_LIMIT)
+        );
+
+        // The rejected mutation must not have been recorded
+        let context = ctx.lock();
+        assert_eq!(context.state_mutations.len(), 1);
+    }
 }
@@ -2,7 +2,7 @@
 //!
 //! These functions allow handlers to manipulate the UI imperatively.
 
-use super::{HostResult, SharedContext};
+use super::{catch_panic, check_host_call, HostResult, SharedContext};
 use crate::context::{RuntimeValue, ViewCommand, ViewCommandType};
 use crate::error::error_codes;
 use std::collections::HashMap;
@@ -17,22 +17,18 @@ use std::collections::HashMap;
 /// * `Ok(())` - Success
 /// * `Err(code)` - If permission denied or error
 pub fn view_command(ctx: &SharedContext, command: ViewCommand) -> HostResult<()> {
-    let mut context = ctx.lock();
-
-    // Check capability
-    let required = match &command.component_id {
-        Some(id) => format!("view:update:{}", id),
-        None => "view:update:*".to_string(),
-    };
-
-    if !context.has_capability(&required) && !context.has_capability("view:update:*") {
-        return Err(error_codes::PERMISSION_DENIED);
-    }
-
-    // Record command
-    context.add_view_command(command);
-
-    Ok(())
+    catch_panic(|| {
+        let mut context = ctx.lock();
+        check_host_call(&mut context)?;
+
+        // Capability is checked inside `add_view_command` itself, so a
+        // rejection there is the only source of truth
+        if !context.add_view_command(command) {
+            return Err(error_codes::PERMISSION_DENIED);
+        }
+
+        Ok(())
+    })
 }
 
 /// Set a filter on a component
@@ -135,6 +131,54 @@ pub fn view_custom(
     view_command(ctx, command)
 }
 
+/// Begin buffering view commands instead of recording them immediately.
+///
+/// # Arguments
+/// * `ctx` - The execution context
+pub fn view_begin_batch(ctx: &SharedContext) {
+    ctx.lock().begin_view_batch();
+}
+
+/// Coalesce and commit the currently buffered batch of view commands.
+///
+/// Commands sharing a target (`component_id` and command type) are
+/// collapsed to the last one received, keeping the position of the
+/// target's first occurrence; `Custom` commands are never coalesced.
+/// A no-op if no batch is open.
+///
+/// # Arguments
+/// * `ctx` - The execution context
+pub fn view_commit_batch(ctx: &SharedContext) {
+    ctx.lock().commit_view_batch();
+}
+
+/// Send a batch of view commands atomically: each command is capability
+/// checked as it is enqueued, and if any is denied the whole batch is
+/// discarded (nothing from it is recorded), otherwise the batch is
+/// coalesced and committed as a unit.
+///
+/// # Arguments
+/// * `ctx` - The execution context
+/// * `commands` - The view commands to execute as a batch
+///
+/// # Returns
+/// * `Ok(())` - Success; the coalesced batch was recorded
+/// * `Err(code)` - If permission was denied for any command; nothing recorded
+pub fn view_batch(ctx: &SharedContext, commands: Vec<ViewCommand>) -> HostResult<()> {
+    view_begin_batch(ctx);
+
+    for command in commands {
+        if let Err(code) = view_command(ctx, command) {
+            ctx.lock().abort_view_batch();
+            return Err(code);
+        }
+    }
+
+    view_commit_batch(ctx);
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,4 +291,53 @@ mod tests {
         let context = ctx.lock();
         assert_eq!(context.view_commands.len(), 3);
     }
+
+    #[test]
+    fn test_view_batch_coalesces_and_commits() {
+        let ctx = create_context_with_caps(vec![CapabilityToken::ViewUpdateAll]);
+
+        let commands = vec![
+            ViewCommand::set_filter("logs", RuntimeValue::String("a".to_string())),
+            ViewCommand::set_filter("logs", RuntimeValue::String("b".to_string())),
+            ViewCommand::focus("input"),
+        ];
+
+        let result = view_batch(&ctx, commands);
+        assert!(result.is_ok());
+
+        let context = ctx.lock();
+        assert_eq!(context.view_commands.len(), 2);
+        assert_eq!(
+            context.view_commands[0].args.get("value"),
+            Some(&RuntimeValue::String("b".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_view_batch_denied_command_records_nothing() {
+        let ctx = create_context_with_caps(vec![CapabilityToken::ViewUpdate("logs".to_string())]);
+
+        let commands = vec![
+            ViewCommand::set_filter("logs", RuntimeValue::Null),
+            ViewCommand::focus("other"), // not granted
+        ];
+
+        let result = view_batch(&ctx, commands);
+        assert_eq!(result, Err(error_codes::PERMISSION_DENIED));
+
+        let context = ctx.lock();
+        assert!(context.view_commands.is_empty());
+    }
+
+    #[test]
+    fn test_view_begin_and_commit_batch_directly() {
+        let ctx = create_context_with_caps(vec![CapabilityToken::ViewUpdateAll]);
+
+        view_begin_batch(&ctx);
+        view_focus(&ctx, "input").unwrap();
+        view_commit_batch(&ctx);
+
+        let context = ctx.lock();
+        assert_eq!(context.view_commands.len(), 1);
+    }
 }
@@ -32,18 +32,25 @@
 pub mod capability;
 pub mod config;
 pub mod context;
+pub mod conversion;
+pub mod diagnostics;
 pub mod engine;
 pub mod error;
+pub mod event_sink;
 pub mod host_functions;
 pub mod metrics;
 pub mod napi;
 
 // Re-export commonly used types
 pub use capability::{Capability, CapabilityToken};
-pub use config::RuntimeConfig;
-pub use context::{ExecutionContext, SuspensionDetails, WasmContext, WasmResult};
+pub use config::{ResourceLimits, RuntimeConfig};
+pub use context::{
+    ExecutionContext, JoinMode, ResourceLimitKind, SuspensionDetails, WasmContext, WasmResult,
+};
+pub use conversion::Conversion;
 pub use engine::{WasmInstance, WasmRuntime};
 pub use error::{ErrorCode, WasmError};
+pub use event_sink::{BoundedEventSink, EventSink};
 pub use metrics::ExecutionMetrics;
 
 /// The embedded QuickJS wrapper script that injects $state, $args, etc.
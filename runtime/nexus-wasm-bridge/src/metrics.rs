@@ -3,6 +3,7 @@
 //! This module provides types for collecting and reporting metrics about
 //! handler execution, including timing, memory usage, and host function calls.
 
+use crate::context::ResourceLimitKind;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -34,6 +35,22 @@ pub struct ExecutionMetrics {
 
     /// Whether compilation cache was hit
     pub cache_hit: bool,
+
+    /// Per-phase timing breakdown in microseconds, keyed by [`Phase`] label
+    pub phase_durations: HashMap<String, u64>,
+
+    /// Compute units (fuel) consumed by this execution
+    pub compute_units_consumed: u64,
+
+    /// Gas (instruction budget) consumed by this execution, enforced
+    /// mid-execution via `ResourceLimits::gas_limit` (distinct from the
+    /// purely observational `compute_units_consumed`)
+    pub gas_used: u64,
+
+    /// Which [`ResourceLimits`](crate::config::ResourceLimits) ceiling (other
+    /// than gas) tripped during this execution, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_limit_exceeded: Option<ResourceLimitKind>,
 }
 
 impl Default for ExecutionMetrics {
@@ -46,6 +63,10 @@ impl Default for ExecutionMetrics {
             instruction_count: 0,
             compilation_time_us: None,
             cache_hit: false,
+            phase_durations: HashMap::new(),
+            compute_units_consumed: 0,
+            gas_used: 0,
+            resource_limit_exceeded: None,
         }
     }
 }
@@ -87,6 +108,30 @@ impl ExecutionMetrics {
         self
     }
 
+    /// Record per-phase timing breakdown
+    pub fn with_phase_durations(mut self, durations: HashMap<String, u64>) -> Self {
+        self.phase_durations = durations;
+        self
+    }
+
+    /// Record compute units (fuel) consumed
+    pub fn with_compute_units(mut self, units: u64) -> Self {
+        self.compute_units_consumed = units;
+        self
+    }
+
+    /// Record gas (instruction budget) consumed
+    pub fn with_gas_used(mut self, gas: u64) -> Self {
+        self.gas_used = gas;
+        self
+    }
+
+    /// Record which resource-limit ceiling was exceeded
+    pub fn with_resource_limit_exceeded(mut self, kind: ResourceLimitKind) -> Self {
+        self.resource_limit_exceeded = Some(kind);
+        self
+    }
+
     /// Increment host call count
     pub fn increment_host_call(&mut self, function_name: &str) {
         *self.host_calls.entry(function_name.to_string()).or_insert(0) += 1;
@@ -114,11 +159,25 @@ pub struct RuntimeStats {
     /// Cache hit rate (0-1)
     pub cache_hit_rate: f64,
 
+    /// Fraction of idle instance releases reused in place instead of a full
+    /// teardown/re-instantiation (0-1); see
+    /// `crate::config::RuntimeConfig::fast_instance_reuse`
+    pub fast_reuse_hit_rate: f64,
+
     /// Average execution time in microseconds
     pub avg_execution_time_us: f64,
 
     /// Total memory used by all instances
     pub total_memory_bytes: u64,
+
+    /// Idle/suspended instances currently mid low-memory episode (see
+    /// `crate::config::LowMemoryHook`); checked-out instances aren't
+    /// visible to this snapshot
+    pub low_memory_pending_instances: usize,
+
+    /// Linear memory ABI instances were built with, per
+    /// `crate::config::RuntimeConfig::memory_model`
+    pub memory_model: crate::config::MemoryModel,
 }
 
 impl Default for RuntimeStats {
@@ -128,13 +187,115 @@ impl Default for RuntimeStats {
             active_instances: 0,
             available_instances: 0,
             cache_hit_rate: 0.0,
+            fast_reuse_hit_rate: 0.0,
             avg_execution_time_us: 0.0,
             total_memory_bytes: 0,
+            low_memory_pending_instances: 0,
+            memory_model: crate::config::MemoryModel::default(),
+        }
+    }
+}
+
+/// Upper bounds (in microseconds) for the execution-latency histogram
+/// buckets, exponentially spaced. An implicit final bucket above the last
+/// bound here covers everything up to `+Inf`.
+const LATENCY_BUCKET_BOUNDS_US: &[u64] = &[
+    100, 500, 1_000, 5_000, 10_000, 50_000, 100_000, 500_000, 1_000_000,
+];
+
+/// Maximum number of distinct `(panel_id, handler_id)` entries kept by
+/// [`MetricsCollector`] before the least-recently-used one is evicted, so an
+/// unbounded stream of distinct handler identities can't grow label
+/// cardinality without bound.
+const MAX_HANDLER_ENTRIES: usize = 512;
+
+/// Key identifying a single handler for per-handler metric aggregation
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HandlerKey {
+    /// Panel ID the handler belongs to
+    pub panel_id: String,
+    /// Handler name
+    pub handler_id: String,
+}
+
+impl HandlerKey {
+    /// Create a new handler key
+    pub fn new(panel_id: impl Into<String>, handler_id: impl Into<String>) -> Self {
+        Self {
+            panel_id: panel_id.into(),
+            handler_id: handler_id.into(),
+        }
+    }
+}
+
+/// Aggregated timing and outcome stats for a single handler
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HandlerTiming {
+    /// Total executions recorded
+    pub executions: u64,
+    /// Successful executions
+    pub successes: u64,
+    /// Failed executions
+    pub failures: u64,
+    /// Sum of all execution durations in microseconds
+    pub total_duration_us: u64,
+    /// Slowest single execution in microseconds
+    pub peak_duration_us: u64,
+    /// Sum of memory used across executions in bytes
+    pub total_memory_bytes: u64,
+    /// Host function call counts
+    pub host_calls: HashMap<String, u64>,
+}
+
+impl HandlerTiming {
+    /// Build a timing entry for a single execution
+    fn from_execution(metrics: &ExecutionMetrics, success: bool) -> Self {
+        let mut host_calls = HashMap::new();
+        for (name, count) in &metrics.host_calls {
+            host_calls.insert(name.clone(), *count as u64);
+        }
+
+        Self {
+            executions: 1,
+            successes: success as u64,
+            failures: (!success) as u64,
+            total_duration_us: metrics.duration_us,
+            peak_duration_us: metrics.duration_us,
+            total_memory_bytes: metrics.memory_used_bytes,
+            host_calls,
+        }
+    }
+
+    /// Fold `other` into this entry using saturating arithmetic
+    pub fn accumulate(&mut self, other: &HandlerTiming) {
+        self.executions = self.executions.saturating_add(other.executions);
+        self.successes = self.successes.saturating_add(other.successes);
+        self.failures = self.failures.saturating_add(other.failures);
+        self.total_duration_us = self.total_duration_us.saturating_add(other.total_duration_us);
+        self.peak_duration_us = self.peak_duration_us.max(other.peak_duration_us);
+        self.total_memory_bytes = self
+            .total_memory_bytes
+            .saturating_add(other.total_memory_bytes);
+
+        for (name, count) in &other.host_calls {
+            let entry = self.host_calls.entry(name.clone()).or_insert(0);
+            *entry = entry.saturating_add(*count);
+        }
+    }
+
+    /// Average execution duration in microseconds
+    pub fn avg_duration_us(&self) -> f64 {
+        if self.executions == 0 {
+            0.0
+        } else {
+            self.total_duration_us as f64 / self.executions as f64
         }
     }
 }
 
 /// Metrics collector for aggregating runtime metrics
+#[derive(Debug)]
 pub struct MetricsCollector {
     total_executions: AtomicU64,
     successful_executions: AtomicU64,
@@ -146,6 +307,37 @@ pub struct MetricsCollector {
     peak_memory: AtomicU64,
     host_calls: parking_lot::Mutex<HashMap<String, u64>>,
     error_counts: parking_lot::Mutex<HashMap<String, u64>>,
+    phase_durations: parking_lot::Mutex<HashMap<String, u64>>,
+    /// Per-handler stats, paired with a last-access tick for LRU eviction
+    handler_stats: parking_lot::Mutex<HashMap<HandlerKey, (HandlerTiming, u64)>>,
+    handler_access_tick: AtomicU64,
+    /// Compute units (fuel) charged across all successful and coalesced executions
+    accumulated_compute_units: AtomicU64,
+    /// Number of executions that have contributed to `accumulated_compute_units`
+    compute_unit_executions: AtomicU64,
+    /// Compute units charged specifically to coalesced errored executions
+    total_errored_units: AtomicU64,
+    /// Units consumed by errored runs, buffered until [`Self::coalesce_error_timings`]
+    /// flushes them so a handler that traps early is still charged fairly
+    errored_units: parking_lot::Mutex<Vec<u64>>,
+    /// Compiled-module cache evictions, mirrored from the compiler's live stats
+    cache_evictions: AtomicU64,
+    /// Compiled-module cache resident bytes, mirrored from the compiler's live stats
+    cache_resident_bytes: AtomicU64,
+    /// Per-bucket execution-duration counts, indexed in parallel with
+    /// [`LATENCY_BUCKET_BOUNDS_US`] plus one trailing `+Inf` bucket
+    latency_buckets: Vec<AtomicU64>,
+    /// Sum of all recorded execution durations in microseconds
+    latency_sum_us: AtomicU64,
+    /// Count of executions recorded into the latency histogram
+    latency_count: AtomicU64,
+    /// Emission counts per event name, reported by [`crate::event_sink::EventSink`]s
+    event_emissions: parking_lot::Mutex<HashMap<String, u64>>,
+    /// Events dropped by a bounded event sink due to buffer overflow
+    events_dropped: AtomicU64,
+    /// Executions terminated by a `ResourceLimits` ceiling, keyed by
+    /// [`ResourceLimitKind::as_str`]
+    resource_limit_violations: parking_lot::Mutex<HashMap<String, u64>>,
 }
 
 impl MetricsCollector {
@@ -162,11 +354,60 @@ impl MetricsCollector {
             peak_memory: AtomicU64::new(0),
             host_calls: parking_lot::Mutex::new(HashMap::new()),
             error_counts: parking_lot::Mutex::new(HashMap::new()),
+            phase_durations: parking_lot::Mutex::new(HashMap::new()),
+            handler_stats: parking_lot::Mutex::new(HashMap::new()),
+            handler_access_tick: AtomicU64::new(0),
+            accumulated_compute_units: AtomicU64::new(0),
+            compute_unit_executions: AtomicU64::new(0),
+            total_errored_units: AtomicU64::new(0),
+            errored_units: parking_lot::Mutex::new(Vec::new()),
+            cache_evictions: AtomicU64::new(0),
+            cache_resident_bytes: AtomicU64::new(0),
+            latency_buckets: (0..=LATENCY_BUCKET_BOUNDS_US.len())
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            latency_sum_us: AtomicU64::new(0),
+            latency_count: AtomicU64::new(0),
+            event_emissions: parking_lot::Mutex::new(HashMap::new()),
+            events_dropped: AtomicU64::new(0),
+            resource_limit_violations: parking_lot::Mutex::new(HashMap::new()),
         }
     }
 
-    /// Record an execution
-    pub fn record_execution(&self, metrics: &ExecutionMetrics, success: bool) {
+    /// Record that an event named `event_name` was emitted
+    pub fn record_event_emission(&self, event_name: &str) {
+        let mut emissions = self.event_emissions.lock();
+        *emissions.entry(event_name.to_string()).or_insert(0) += 1;
+    }
+
+    /// Record that an event was dropped by a bounded event sink
+    pub fn record_event_dropped(&self) {
+        self.events_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get emission counts per event name
+    pub fn event_emissions(&self) -> HashMap<String, u64> {
+        self.event_emissions.lock().clone()
+    }
+
+    /// Get the total number of events dropped by a bounded event sink
+    pub fn events_dropped(&self) -> u64 {
+        self.events_dropped.load(Ordering::Relaxed)
+    }
+
+    /// Get execution counts terminated by each `ResourceLimits` ceiling,
+    /// keyed by [`ResourceLimitKind::as_str`]
+    pub fn resource_limit_violations(&self) -> HashMap<String, u64> {
+        self.resource_limit_violations.lock().clone()
+    }
+
+    /// Record an execution for a specific handler
+    pub fn record_execution(&self, handler: HandlerKey, metrics: &ExecutionMetrics, success: bool) {
+        self.record_global(metrics, success);
+        self.record_handler(handler, metrics, success);
+    }
+
+    fn record_global(&self, metrics: &ExecutionMetrics, success: bool) {
         self.total_executions.fetch_add(1, Ordering::Relaxed);
         
         if success {
@@ -177,6 +418,7 @@ impl MetricsCollector {
 
         self.total_execution_time_us
             .fetch_add(metrics.duration_us, Ordering::Relaxed);
+        self.record_latency(metrics.duration_us);
 
         if metrics.cache_hit {
             self.cache_hits.fetch_add(1, Ordering::Relaxed);
@@ -207,6 +449,118 @@ impl MetricsCollector {
         for (name, count) in &metrics.host_calls {
             *host_calls.entry(name.clone()).or_insert(0) += *count as u64;
         }
+        drop(host_calls);
+
+        // Update per-phase timing totals
+        let mut phase_durations = self.phase_durations.lock();
+        for (phase, duration_us) in &metrics.phase_durations {
+            *phase_durations.entry(phase.clone()).or_insert(0) += *duration_us;
+        }
+        drop(phase_durations);
+
+        // Charge compute units: successful runs are charged immediately, while
+        // errored runs are buffered so they can be coalesced against an
+        // estimated program cost later (see `coalesce_error_timings`)
+        if success {
+            self.accumulated_compute_units
+                .fetch_add(metrics.compute_units_consumed, Ordering::Relaxed);
+            self.compute_unit_executions.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.errored_units.lock().push(metrics.compute_units_consumed);
+        }
+
+        if let Some(kind) = metrics.resource_limit_exceeded {
+            let mut violations = self.resource_limit_violations.lock();
+            *violations.entry(kind.as_str().to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// Flush buffered errored-execution compute units into the accumulator.
+    ///
+    /// Each buffered run is charged `max(estimated_cost, consumed)`, so a
+    /// handler that traps before consuming its estimated share of fuel is
+    /// still billed fairly instead of being undercounted.
+    pub fn coalesce_error_timings(&self, estimated_cost: u64) {
+        let buffered: Vec<u64> = std::mem::take(&mut *self.errored_units.lock());
+
+        for consumed in buffered {
+            let charged = estimated_cost.max(consumed);
+            self.accumulated_compute_units
+                .fetch_add(charged, Ordering::Relaxed);
+            self.compute_unit_executions.fetch_add(1, Ordering::Relaxed);
+            self.total_errored_units.fetch_add(charged, Ordering::Relaxed);
+        }
+    }
+
+    fn record_handler(&self, handler: HandlerKey, metrics: &ExecutionMetrics, success: bool) {
+        let tick = self.handler_access_tick.fetch_add(1, Ordering::Relaxed);
+        let entry = HandlerTiming::from_execution(metrics, success);
+
+        let mut handler_stats = self.handler_stats.lock();
+        match handler_stats.get_mut(&handler) {
+            Some((existing, last_used)) => {
+                existing.accumulate(&entry);
+                *last_used = tick;
+            }
+            None => {
+                if handler_stats.len() >= MAX_HANDLER_ENTRIES {
+                    if let Some(lru_key) = handler_stats
+                        .iter()
+                        .min_by_key(|(_, (_, last_used))| *last_used)
+                        .map(|(key, _)| key.clone())
+                    {
+                        handler_stats.remove(&lru_key);
+                    }
+                }
+                handler_stats.insert(handler, (entry, tick));
+            }
+        }
+    }
+
+    /// Bucket `duration_us` into the latency histogram and accumulate the
+    /// running sum/count used for both Prometheus export and `quantile`
+    fn record_latency(&self, duration_us: u64) {
+        let bucket = LATENCY_BUCKET_BOUNDS_US
+            .iter()
+            .position(|&bound| duration_us <= bound)
+            .unwrap_or(LATENCY_BUCKET_BOUNDS_US.len());
+
+        self.latency_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.latency_sum_us.fetch_add(duration_us, Ordering::Relaxed);
+        self.latency_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Estimate the `q`-th quantile (e.g. `0.95` for p95) execution duration
+    /// in microseconds, linearly interpolating within the bucket it falls in
+    pub fn quantile(&self, q: f64) -> f64 {
+        let count = self.latency_count.load(Ordering::Relaxed);
+        if count == 0 {
+            return 0.0;
+        }
+
+        let target = q.clamp(0.0, 1.0) * count as f64;
+        let mut cumulative = 0u64;
+        let mut prev_bound = 0u64;
+
+        for (i, &bound) in LATENCY_BUCKET_BOUNDS_US.iter().enumerate() {
+            let bucket_count = self.latency_buckets[i].load(Ordering::Relaxed);
+            let next_cumulative = cumulative + bucket_count;
+
+            if next_cumulative as f64 >= target {
+                if bucket_count == 0 {
+                    return bound as f64;
+                }
+                let fraction = (target - cumulative as f64) / bucket_count as f64;
+                return prev_bound as f64 + fraction * (bound - prev_bound) as f64;
+            }
+
+            cumulative = next_cumulative;
+            prev_bound = bound;
+        }
+
+        // Target falls in the +Inf bucket; there's no upper bound to
+        // interpolate against, so approximate with the last finite boundary.
+        prev_bound as f64
     }
 
     /// Record an error
@@ -267,6 +621,60 @@ impl MetricsCollector {
         self.error_counts.lock().clone()
     }
 
+    /// Get per-phase timing totals in microseconds, keyed by [`Phase`] label
+    pub fn phase_durations(&self) -> HashMap<String, u64> {
+        self.phase_durations.lock().clone()
+    }
+
+    /// Get per-handler aggregated stats, keyed by `(panel_id, handler_id)`
+    pub fn handler_stats(&self) -> HashMap<HandlerKey, HandlerTiming> {
+        self.handler_stats
+            .lock()
+            .iter()
+            .map(|(key, (timing, _))| (key.clone(), timing.clone()))
+            .collect()
+    }
+
+    /// Get total compute units (fuel) charged so far, including coalesced
+    /// errored executions
+    pub fn accumulated_compute_units(&self) -> u64 {
+        self.accumulated_compute_units.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of executions that have contributed to
+    /// `accumulated_compute_units`, including coalesced errored executions
+    pub fn compute_unit_executions(&self) -> u64 {
+        self.compute_unit_executions.load(Ordering::Relaxed)
+    }
+
+    /// Get total compute units charged specifically to coalesced errored
+    /// executions
+    pub fn total_errored_units(&self) -> u64 {
+        self.total_errored_units.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of errored executions still buffered, awaiting a
+    /// [`Self::coalesce_error_timings`] flush
+    pub fn pending_errored_count(&self) -> usize {
+        self.errored_units.lock().len()
+    }
+
+    /// Update the compiled-module cache gauges from the compiler's live stats
+    pub fn update_cache_stats(&self, evictions: u64, resident_bytes: u64) {
+        self.cache_evictions.store(evictions, Ordering::Relaxed);
+        self.cache_resident_bytes.store(resident_bytes, Ordering::Relaxed);
+    }
+
+    /// Get the number of compiled-module cache evictions
+    pub fn cache_evictions(&self) -> u64 {
+        self.cache_evictions.load(Ordering::Relaxed)
+    }
+
+    /// Get the compiled-module cache's resident size in bytes
+    pub fn cache_resident_bytes(&self) -> u64 {
+        self.cache_resident_bytes.load(Ordering::Relaxed)
+    }
+
     /// Reset all metrics
     pub fn reset(&self) {
         self.total_executions.store(0, Ordering::Relaxed);
@@ -279,6 +687,22 @@ impl MetricsCollector {
         self.peak_memory.store(0, Ordering::Relaxed);
         self.host_calls.lock().clear();
         self.error_counts.lock().clear();
+        self.phase_durations.lock().clear();
+        self.handler_stats.lock().clear();
+        self.accumulated_compute_units.store(0, Ordering::Relaxed);
+        self.compute_unit_executions.store(0, Ordering::Relaxed);
+        self.total_errored_units.store(0, Ordering::Relaxed);
+        self.errored_units.lock().clear();
+        self.cache_evictions.store(0, Ordering::Relaxed);
+        self.cache_resident_bytes.store(0, Ordering::Relaxed);
+        for bucket in &self.latency_buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.latency_sum_us.store(0, Ordering::Relaxed);
+        self.latency_count.store(0, Ordering::Relaxed);
+        self.event_emissions.lock().clear();
+        self.events_dropped.store(0, Ordering::Relaxed);
+        self.resource_limit_violations.lock().clear();
     }
 
     /// Export Prometheus-format metrics
@@ -310,6 +734,45 @@ impl MetricsCollector {
             self.cache_hit_rate()
         ));
 
+        // Execution-latency histogram (cumulative buckets, per Prometheus convention)
+        output.push_str("\n# HELP nexus_execution_time_us Execution duration histogram\n");
+        output.push_str("# TYPE nexus_execution_time_us histogram\n");
+        let mut cumulative = 0u64;
+        for (i, &bound) in LATENCY_BUCKET_BOUNDS_US.iter().enumerate() {
+            cumulative += self.latency_buckets[i].load(Ordering::Relaxed);
+            output.push_str(&format!(
+                "nexus_execution_time_us_bucket{{le=\"{}\"}} {}\n",
+                bound, cumulative
+            ));
+        }
+        cumulative += self.latency_buckets[LATENCY_BUCKET_BOUNDS_US.len()].load(Ordering::Relaxed);
+        output.push_str(&format!(
+            "nexus_execution_time_us_bucket{{le=\"+Inf\"}} {}\n",
+            cumulative
+        ));
+        output.push_str(&format!(
+            "nexus_execution_time_us_sum {}\n",
+            self.latency_sum_us.load(Ordering::Relaxed)
+        ));
+        output.push_str(&format!(
+            "nexus_execution_time_us_count {}\n",
+            self.latency_count.load(Ordering::Relaxed)
+        ));
+
+        output.push_str("\n# HELP nexus_cache_evictions_total Compiled-module cache evictions\n");
+        output.push_str("# TYPE nexus_cache_evictions_total counter\n");
+        output.push_str(&format!(
+            "nexus_cache_evictions_total {}\n",
+            self.cache_evictions()
+        ));
+
+        output.push_str("\n# HELP nexus_cache_resident_bytes Compiled-module cache resident size\n");
+        output.push_str("# TYPE nexus_cache_resident_bytes gauge\n");
+        output.push_str(&format!(
+            "nexus_cache_resident_bytes {}\n",
+            self.cache_resident_bytes()
+        ));
+
         output.push_str("\n# HELP nexus_peak_memory_bytes Peak memory usage\n");
         output.push_str("# TYPE nexus_peak_memory_bytes gauge\n");
         output.push_str(&format!("nexus_peak_memory_bytes {}\n", self.peak_memory()));
@@ -324,6 +787,91 @@ impl MetricsCollector {
             ));
         }
 
+        // Per-phase timing breakdown
+        output.push_str("\n# HELP nexus_phase_duration_us Total time spent per execution phase\n");
+        output.push_str("# TYPE nexus_phase_duration_us counter\n");
+        for (phase, duration_us) in self.phase_durations() {
+            output.push_str(&format!(
+                "nexus_phase_duration_us{{phase=\"{}\"}} {}\n",
+                phase, duration_us
+            ));
+        }
+
+        // Per-handler execution counts
+        output.push_str("\n# HELP nexus_handler_executions_total Total executions per handler\n");
+        output.push_str("# TYPE nexus_handler_executions_total counter\n");
+        for (key, timing) in self.handler_stats() {
+            output.push_str(&format!(
+                "nexus_handler_executions_total{{panel=\"{}\",handler=\"{}\",status=\"success\"}} {}\n",
+                key.panel_id, key.handler_id, timing.successes
+            ));
+            output.push_str(&format!(
+                "nexus_handler_executions_total{{panel=\"{}\",handler=\"{}\",status=\"error\"}} {}\n",
+                key.panel_id, key.handler_id, timing.failures
+            ));
+        }
+
+        output.push_str("\n# HELP nexus_handler_execution_time_us Per-handler execution time\n");
+        output.push_str("# TYPE nexus_handler_execution_time_us gauge\n");
+        for (key, timing) in self.handler_stats() {
+            output.push_str(&format!(
+                "nexus_handler_execution_time_us{{panel=\"{}\",handler=\"{}\",stat=\"avg\"}} {:.2}\n",
+                key.panel_id,
+                key.handler_id,
+                timing.avg_duration_us()
+            ));
+            output.push_str(&format!(
+                "nexus_handler_execution_time_us{{panel=\"{}\",handler=\"{}\",stat=\"peak\"}} {}\n",
+                key.panel_id, key.handler_id, timing.peak_duration_us
+            ));
+        }
+
+        // Compute units (fuel)
+        output.push_str("\n# HELP nexus_compute_units_total Total compute units charged\n");
+        output.push_str("# TYPE nexus_compute_units_total counter\n");
+        output.push_str(&format!(
+            "nexus_compute_units_total {}\n",
+            self.accumulated_compute_units()
+        ));
+
+        output.push_str(
+            "\n# HELP nexus_compute_units_errored_total Compute units charged to coalesced errored executions\n",
+        );
+        output.push_str("# TYPE nexus_compute_units_errored_total counter\n");
+        output.push_str(&format!(
+            "nexus_compute_units_errored_total {}\n",
+            self.total_errored_units()
+        ));
+
+        // Emitted events
+        output.push_str("\n# HELP nexus_events_emitted_total Events emitted by handlers, by name\n");
+        output.push_str("# TYPE nexus_events_emitted_total counter\n");
+        for (name, count) in self.event_emissions() {
+            output.push_str(&format!(
+                "nexus_events_emitted_total{{event=\"{}\"}} {}\n",
+                name, count
+            ));
+        }
+
+        output.push_str("\n# HELP nexus_events_dropped_total Events dropped by a bounded event sink\n");
+        output.push_str("# TYPE nexus_events_dropped_total counter\n");
+        output.push_str(&format!(
+            "nexus_events_dropped_total {}\n",
+            self.events_dropped()
+        ));
+
+        // Resource-limit violations
+        output.push_str(
+            "\n# HELP nexus_resource_limit_violations_total Executions terminated by a ResourceLimits ceiling\n",
+        );
+        output.push_str("# TYPE nexus_resource_limit_violations_total counter\n");
+        for (kind, count) in self.resource_limit_violations() {
+            output.push_str(&format!(
+                "nexus_resource_limit_violations_total{{kind=\"{}\"}} {}\n",
+                kind, count
+            ));
+        }
+
         // Errors
         output.push_str("\n# HELP nexus_errors_total Error counts by code\n");
         output.push_str("# TYPE nexus_errors_total counter\n");
@@ -344,11 +892,42 @@ impl Default for MetricsCollector {
     }
 }
 
+/// An execution phase tracked for per-phase timing breakdowns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Deserializing the incoming WASM context
+    Deserialize,
+    /// Instantiating and linking the WASM module
+    Instantiate,
+    /// Running the handler itself
+    Execute,
+    /// Waiting on a suspended host function call to return
+    HostWait,
+    /// Serializing the outgoing result
+    Serialize,
+}
+
+impl Phase {
+    /// Label used as the metrics key and Prometheus label value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Deserialize => "deserialize",
+            Phase::Instantiate => "instantiate",
+            Phase::Execute => "execute",
+            Phase::HostWait => "host_wait",
+            Phase::Serialize => "serialize",
+        }
+    }
+}
+
 /// Timer for measuring execution duration
 pub struct ExecutionTimer {
     start: Instant,
     compilation_start: Option<Instant>,
     compilation_duration: Option<Duration>,
+    phase_durations: HashMap<Phase, u64>,
+    active_phase: Option<(Phase, Instant)>,
 }
 
 impl ExecutionTimer {
@@ -358,6 +937,8 @@ impl ExecutionTimer {
             start: Instant::now(),
             compilation_start: None,
             compilation_duration: None,
+            phase_durations: HashMap::new(),
+            active_phase: None,
         }
     }
 
@@ -373,6 +954,25 @@ impl ExecutionTimer {
         }
     }
 
+    /// Begin timing `phase`. Only one phase is timed at a time: if another
+    /// phase is already active, its elapsed time is discarded and a later
+    /// `exit` for it is a no-op.
+    pub fn enter(&mut self, phase: Phase) {
+        self.active_phase = Some((phase, Instant::now()));
+    }
+
+    /// Stop timing `phase` and accumulate the elapsed microseconds. A no-op
+    /// if `phase` isn't the one most recently entered.
+    pub fn exit(&mut self, phase: Phase) {
+        if let Some((active, start)) = self.active_phase.take() {
+            if active == phase {
+                *self.phase_durations.entry(phase).or_insert(0) += start.elapsed().as_micros() as u64;
+            } else {
+                self.active_phase = Some((active, start));
+            }
+        }
+    }
+
     /// Get elapsed time
     pub fn elapsed(&self) -> Duration {
         self.start.elapsed()
@@ -387,7 +987,13 @@ impl ExecutionTimer {
     pub fn into_metrics(self, cache_hit: bool) -> ExecutionMetrics {
         let mut metrics = ExecutionMetrics::new()
             .with_duration(self.elapsed())
-            .with_cache_hit(cache_hit);
+            .with_cache_hit(cache_hit)
+            .with_phase_durations(
+                self.phase_durations
+                    .into_iter()
+                    .map(|(phase, duration_us)| (phase.as_str().to_string(), duration_us))
+                    .collect(),
+            );
 
         if let Some(compilation_time) = self.compilation_duration {
             metrics = metrics.with_compilation_time(compilation_time);
@@ -430,8 +1036,8 @@ mod tests {
             .with_duration(Duration::from_millis(10))
             .with_cache_hit(true);
         
-        collector.record_execution(&metrics, true);
-        collector.record_execution(&metrics, false);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, true);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, false);
         
         assert_eq!(collector.total_executions(), 2);
         assert_eq!(collector.successful_executions(), 1);
@@ -445,9 +1051,9 @@ mod tests {
         let hit = ExecutionMetrics::new().with_cache_hit(true);
         let miss = ExecutionMetrics::new().with_cache_hit(false);
         
-        collector.record_execution(&hit, true);
-        collector.record_execution(&hit, true);
-        collector.record_execution(&miss, true);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &hit, true);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &hit, true);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &miss, true);
         
         assert!((collector.cache_hit_rate() - 0.666).abs() < 0.01);
     }
@@ -456,7 +1062,7 @@ mod tests {
     fn test_prometheus_output() {
         let collector = MetricsCollector::new();
         let metrics = ExecutionMetrics::new();
-        collector.record_execution(&metrics, true);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, true);
         
         let output = collector.to_prometheus();
         assert!(output.contains("nexus_handler_executions_total"));
@@ -470,4 +1076,335 @@ mod tests {
         let elapsed = timer.elapsed();
         assert!(elapsed >= Duration::from_millis(10));
     }
+
+    #[test]
+    fn test_phase_timing_accumulates_into_metrics() {
+        let mut timer = ExecutionTimer::start();
+
+        timer.enter(Phase::Deserialize);
+        std::thread::sleep(Duration::from_millis(5));
+        timer.exit(Phase::Deserialize);
+
+        timer.enter(Phase::Execute);
+        std::thread::sleep(Duration::from_millis(5));
+        timer.exit(Phase::Execute);
+
+        let metrics = timer.into_metrics(false);
+        assert!(metrics.phase_durations[Phase::Deserialize.as_str()] >= 5_000);
+        assert!(metrics.phase_durations[Phase::Execute.as_str()] >= 5_000);
+        assert!(!metrics.phase_durations.contains_key(Phase::HostWait.as_str()));
+    }
+
+    #[test]
+    fn test_exit_mismatched_phase_is_a_no_op() {
+        let mut timer = ExecutionTimer::start();
+
+        timer.enter(Phase::Execute);
+        timer.exit(Phase::Deserialize);
+
+        let metrics = timer.into_metrics(false);
+        assert!(metrics.phase_durations.is_empty());
+    }
+
+    #[test]
+    fn test_metrics_collector_sums_phase_durations() {
+        let collector = MetricsCollector::new();
+
+        let mut durations = HashMap::new();
+        durations.insert(Phase::Execute.as_str().to_string(), 100);
+        let metrics = ExecutionMetrics::new().with_phase_durations(durations.clone());
+
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, true);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, true);
+
+        assert_eq!(
+            collector.phase_durations()[Phase::Execute.as_str()],
+            200
+        );
+    }
+
+    #[test]
+    fn test_prometheus_output_includes_phase_durations() {
+        let collector = MetricsCollector::new();
+
+        let mut durations = HashMap::new();
+        durations.insert(Phase::Instantiate.as_str().to_string(), 42);
+        let metrics = ExecutionMetrics::new().with_phase_durations(durations);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, true);
+
+        let output = collector.to_prometheus();
+        assert!(output.contains("nexus_phase_duration_us"));
+        assert!(output.contains("phase=\"instantiate\""));
+    }
+
+    #[test]
+    fn test_handler_stats_aggregate_per_handler() {
+        let collector = MetricsCollector::new();
+
+        let fast = ExecutionMetrics::new().with_duration(Duration::from_millis(10));
+        let slow = ExecutionMetrics::new().with_duration(Duration::from_millis(50));
+
+        collector.record_execution(HandlerKey::new("panel-1", "increment"), &fast, true);
+        collector.record_execution(HandlerKey::new("panel-1", "increment"), &slow, false);
+        collector.record_execution(HandlerKey::new("panel-1", "reset"), &fast, true);
+
+        let stats = collector.handler_stats();
+        assert_eq!(stats.len(), 2);
+
+        let increment = &stats[&HandlerKey::new("panel-1", "increment")];
+        assert_eq!(increment.executions, 2);
+        assert_eq!(increment.successes, 1);
+        assert_eq!(increment.failures, 1);
+        assert_eq!(increment.peak_duration_us, 50_000);
+
+        let reset = &stats[&HandlerKey::new("panel-1", "reset")];
+        assert_eq!(reset.executions, 1);
+    }
+
+    #[test]
+    fn test_handler_timing_accumulate() {
+        let mut total = HandlerTiming::default();
+        let a = HandlerTiming {
+            executions: 2,
+            successes: 2,
+            failures: 0,
+            total_duration_us: 100,
+            peak_duration_us: 80,
+            total_memory_bytes: 1000,
+            host_calls: HashMap::new(),
+        };
+        let b = HandlerTiming {
+            executions: 1,
+            successes: 0,
+            failures: 1,
+            total_duration_us: 30,
+            peak_duration_us: 30,
+            total_memory_bytes: 500,
+            host_calls: HashMap::new(),
+        };
+
+        total.accumulate(&a);
+        total.accumulate(&b);
+
+        assert_eq!(total.executions, 3);
+        assert_eq!(total.successes, 2);
+        assert_eq!(total.failures, 1);
+        assert_eq!(total.total_duration_us, 130);
+        assert_eq!(total.peak_duration_us, 80);
+        assert_eq!(total.total_memory_bytes, 1500);
+    }
+
+    #[test]
+    fn test_handler_stats_evicts_least_recently_used_past_cap() {
+        let collector = MetricsCollector::new();
+        let metrics = ExecutionMetrics::new();
+
+        for i in 0..(MAX_HANDLER_ENTRIES + 1) {
+            collector.record_execution(
+                HandlerKey::new("panel-1", format!("handler-{i}")),
+                &metrics,
+                true,
+            );
+        }
+
+        let stats = collector.handler_stats();
+        assert_eq!(stats.len(), MAX_HANDLER_ENTRIES);
+        assert!(!stats.contains_key(&HandlerKey::new("panel-1", "handler-0")));
+        assert!(stats.contains_key(&HandlerKey::new("panel-1", format!("handler-{MAX_HANDLER_ENTRIES}"))));
+    }
+
+    #[test]
+    fn test_prometheus_output_includes_per_handler_series() {
+        let collector = MetricsCollector::new();
+        let metrics = ExecutionMetrics::new().with_duration(Duration::from_millis(5));
+        collector.record_execution(HandlerKey::new("panel-1", "increment"), &metrics, true);
+
+        let output = collector.to_prometheus();
+        assert!(output.contains("panel=\"panel-1\",handler=\"increment\""));
+        assert!(output.contains("stat=\"avg\""));
+        assert!(output.contains("stat=\"peak\""));
+    }
+
+    #[test]
+    fn test_successful_execution_charges_compute_units_immediately() {
+        let collector = MetricsCollector::new();
+        let metrics = ExecutionMetrics::new().with_compute_units(150);
+
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, true);
+
+        assert_eq!(collector.accumulated_compute_units(), 150);
+        assert_eq!(collector.pending_errored_count(), 0);
+    }
+
+    #[test]
+    fn test_errored_execution_buffers_units_until_coalesced() {
+        let collector = MetricsCollector::new();
+        let metrics = ExecutionMetrics::new().with_compute_units(10);
+
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, false);
+
+        assert_eq!(collector.accumulated_compute_units(), 0);
+        assert_eq!(collector.pending_errored_count(), 1);
+
+        collector.coalesce_error_timings(100);
+
+        // Trapped early after only 10 units, but still charged the estimated
+        // program cost of 100
+        assert_eq!(collector.accumulated_compute_units(), 100);
+        assert_eq!(collector.total_errored_units(), 100);
+        assert_eq!(collector.pending_errored_count(), 0);
+    }
+
+    #[test]
+    fn test_coalesce_error_timings_charges_actual_cost_when_higher() {
+        let collector = MetricsCollector::new();
+        let metrics = ExecutionMetrics::new().with_compute_units(500);
+
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, false);
+        collector.coalesce_error_timings(100);
+
+        // Consumed more than the estimate before trapping, so the real cost wins
+        assert_eq!(collector.accumulated_compute_units(), 500);
+        assert_eq!(collector.total_errored_units(), 500);
+    }
+
+    #[test]
+    fn test_latency_histogram_buckets_by_duration() {
+        let collector = MetricsCollector::new();
+
+        let fast = ExecutionMetrics::new().with_duration(Duration::from_micros(50));
+        let slow = ExecutionMetrics::new().with_duration(Duration::from_millis(2));
+
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &fast, true);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &slow, true);
+
+        let output = collector.to_prometheus();
+        assert!(output.contains("nexus_execution_time_us_count 2"));
+        assert!(output.contains("nexus_execution_time_us_sum 2050"));
+        assert!(output.contains("nexus_execution_time_us_bucket{le=\"100\"} 1"));
+        assert!(output.contains("nexus_execution_time_us_bucket{le=\"+Inf\"} 2"));
+    }
+
+    #[test]
+    fn test_quantile_interpolates_within_bucket() {
+        let collector = MetricsCollector::new();
+
+        // 10 executions uniformly at 10us, all in the first (<=100us) bucket
+        for _ in 0..10 {
+            let metrics = ExecutionMetrics::new().with_duration(Duration::from_micros(10));
+            collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, true);
+        }
+
+        let p50 = collector.quantile(0.5);
+        assert!(p50 > 0.0 && p50 <= 100.0);
+    }
+
+    #[test]
+    fn test_quantile_empty_collector_is_zero() {
+        let collector = MetricsCollector::new();
+        assert_eq!(collector.quantile(0.95), 0.0);
+    }
+
+    #[test]
+    fn test_update_cache_stats_and_prometheus_output() {
+        let collector = MetricsCollector::new();
+        collector.update_cache_stats(3, 4096);
+
+        assert_eq!(collector.cache_evictions(), 3);
+        assert_eq!(collector.cache_resident_bytes(), 4096);
+
+        let output = collector.to_prometheus();
+        assert!(output.contains("nexus_cache_evictions_total 3"));
+        assert!(output.contains("nexus_cache_resident_bytes 4096"));
+    }
+
+    #[test]
+    fn test_prometheus_output_includes_compute_units() {
+        let collector = MetricsCollector::new();
+        let ok = ExecutionMetrics::new().with_compute_units(20);
+        let err = ExecutionMetrics::new().with_compute_units(5);
+
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &ok, true);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &err, false);
+        collector.coalesce_error_timings(50);
+
+        let output = collector.to_prometheus();
+        assert!(output.contains("nexus_compute_units_total 70"));
+        assert!(output.contains("nexus_compute_units_errored_total 50"));
+    }
+
+    #[test]
+    fn test_record_event_emission_and_drop_counts() {
+        let collector = MetricsCollector::new();
+
+        collector.record_event_emission("toast");
+        collector.record_event_emission("toast");
+        collector.record_event_emission("custom");
+        collector.record_event_dropped();
+
+        assert_eq!(collector.event_emissions().get("toast"), Some(&2));
+        assert_eq!(collector.event_emissions().get("custom"), Some(&1));
+        assert_eq!(collector.events_dropped(), 1);
+    }
+
+    #[test]
+    fn test_prometheus_output_includes_event_counters() {
+        let collector = MetricsCollector::new();
+        collector.record_event_emission("toast");
+        collector.record_event_dropped();
+
+        let output = collector.to_prometheus();
+        assert!(output.contains("nexus_events_emitted_total{event=\"toast\"} 1"));
+        assert!(output.contains("nexus_events_dropped_total 1"));
+    }
+
+    #[test]
+    fn test_reset_clears_event_counters() {
+        let collector = MetricsCollector::new();
+        collector.record_event_emission("toast");
+        collector.record_event_dropped();
+
+        collector.reset();
+
+        assert!(collector.event_emissions().is_empty());
+        assert_eq!(collector.events_dropped(), 0);
+    }
+
+    #[test]
+    fn test_resource_limit_violations_aggregate_by_kind() {
+        let collector = MetricsCollector::new();
+        let host_calls = ExecutionMetrics::new()
+            .with_resource_limit_exceeded(ResourceLimitKind::HostCalls);
+        let events = ExecutionMetrics::new().with_resource_limit_exceeded(ResourceLimitKind::Events);
+
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &host_calls, false);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &host_calls, false);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &events, false);
+
+        let violations = collector.resource_limit_violations();
+        assert_eq!(violations.get("host_calls"), Some(&2));
+        assert_eq!(violations.get("events"), Some(&1));
+    }
+
+    #[test]
+    fn test_prometheus_output_includes_resource_limit_violations() {
+        let collector = MetricsCollector::new();
+        let metrics =
+            ExecutionMetrics::new().with_resource_limit_exceeded(ResourceLimitKind::StateMutations);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, false);
+
+        let output = collector.to_prometheus();
+        assert!(output.contains("nexus_resource_limit_violations_total{kind=\"state_mutations\"} 1"));
+    }
+
+    #[test]
+    fn test_reset_clears_resource_limit_violations() {
+        let collector = MetricsCollector::new();
+        let metrics = ExecutionMetrics::new().with_resource_limit_exceeded(ResourceLimitKind::Events);
+        collector.record_execution(HandlerKey::new("panel-1", "handler-1"), &metrics, false);
+
+        collector.reset();
+
+        assert!(collector.resource_limit_violations().is_empty());
+    }
 }
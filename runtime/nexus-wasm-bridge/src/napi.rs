@@ -3,16 +3,19 @@
 //! This module exposes the WASM runtime to Node.js via N-API,
 //! allowing the workspace-kernel to interact with the Rust runtime.
 
-use crate::capability::CapabilityChecker;
-use crate::config::RuntimeConfig;
-use crate::context::{AsyncResult, RuntimeValue, WasmContext, WasmResult};
-use crate::engine::WasmRuntime;
-use crate::error::RuntimeError;
+use crate::capability::infer_capabilities;
+use crate::config::{ResourceLimits, RuntimeConfig};
+use crate::context::{AsyncResult, EmittedEvent, RuntimeValue, StateMutation, ViewCommand, WasmContext, WasmResult};
+use crate::engine::{BatchHandler, BatchJob, WasmRuntime};
+use crate::error::{ErrorCode, RuntimeError, WasmError};
+use crate::event_sink::EventSink;
+use crate::host_functions::op_driver::HostOpFn;
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use napi_derive::napi;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, oneshot, RwLock};
 
 /// JavaScript-friendly configuration
 #[napi(object)]
@@ -110,6 +113,40 @@ impl TryFrom<JsWasmContext> for WasmContext {
     }
 }
 
+/// One job in an `execute_batch` call: a handler (as source or pre-compiled
+/// bytecode, exactly one of `handler_code`/`bytecode` must be set) plus its
+/// own execution context.
+#[napi(object)]
+pub struct JsBatchJob {
+    /// Handler source to compile and run; mutually exclusive with `bytecode`
+    pub handler_code: Option<String>,
+    /// Pre-compiled bytecode to run directly; mutually exclusive with `handler_code`
+    pub bytecode: Option<Buffer>,
+    /// This job's execution context
+    pub context: JsWasmContext,
+}
+
+impl TryFrom<JsBatchJob> for BatchJob {
+    type Error = napi::Error;
+
+    fn try_from(js: JsBatchJob) -> Result<Self> {
+        let handler = match (js.handler_code, js.bytecode) {
+            (Some(code), None) => BatchHandler::Source(code),
+            (None, Some(bytecode)) => BatchHandler::Bytecode(bytecode.to_vec()),
+            _ => {
+                return Err(napi::Error::from_reason(
+                    "Batch job must set exactly one of `handlerCode`/`bytecode`",
+                ))
+            }
+        };
+
+        Ok(BatchJob {
+            handler,
+            context: WasmContext::try_from(js.context)?,
+        })
+    }
+}
+
 /// JavaScript-friendly execution result
 #[napi(object)]
 pub struct JsWasmResult {
@@ -123,8 +160,12 @@ pub struct JsWasmResult {
     pub events: Buffer,
     /// View commands (MessagePack encoded array)
     pub view_commands: Buffer,
-    /// Suspension details (if suspended)
-    pub suspension: Option<JsSuspension>,
+    /// Pending suspensions (if suspended); more than one entry means the
+    /// handler fanned out several concurrent extension calls (`Promise.all`)
+    pub suspensions: Vec<JsSuspension>,
+    /// Join condition `suspensions` must satisfy before the handler resumes:
+    /// "all" or "any". Meaningless outside `status === "suspended"`.
+    pub join_mode: String,
     /// Error details (if error)
     pub error: Option<JsWasmError>,
     /// Execution metrics
@@ -147,12 +188,21 @@ impl From<WasmResult> for JsWasmResult {
             view_commands: Buffer::from(
                 rmp_serde::to_vec(&result.view_commands).unwrap_or_default()
             ),
-            suspension: result.suspension.map(|s| JsSuspension {
-                suspension_id: s.suspension_id,
-                extension_name: s.extension_name,
-                method: s.method,
-                args: Buffer::from(rmp_serde::to_vec(&s.args).unwrap_or_default()),
-            }),
+            suspensions: result
+                .suspensions
+                .into_iter()
+                .map(|s| JsSuspension {
+                    suspension_id: s.suspension_id,
+                    seq: s.seq,
+                    extension_name: s.extension_name,
+                    method: s.method,
+                    args: Buffer::from(rmp_serde::to_vec(&s.args).unwrap_or_default()),
+                })
+                .collect(),
+            join_mode: match result.join_mode {
+                crate::context::JoinMode::All => "all".to_string(),
+                crate::context::JoinMode::Any => "any".to_string(),
+            },
             error: result.error.map(|e| JsWasmError {
                 code: e.code.to_string(),
                 message: e.message,
@@ -178,6 +228,9 @@ impl From<WasmResult> for JsWasmResult {
 pub struct JsSuspension {
     /// Unique suspension ID
     pub suspension_id: String,
+    /// Sequence number assigned when this call suspended, for ordering
+    /// concurrent calls whose UUIDs don't sort meaningfully
+    pub seq: u64,
     /// Extension name
     pub extension_name: String,
     /// Method being called
@@ -186,6 +239,50 @@ pub struct JsSuspension {
     pub args: Buffer,
 }
 
+/// A single event, view command, or state mutation forwarded to JS as a
+/// handler executes, via `execute_handler_streaming`'s `on_emit` callback
+#[napi(object)]
+pub struct JsStreamItem {
+    /// Discriminant: `"event"`, `"view"`, or `"mutation"`
+    pub kind: String,
+    /// MessagePack-encoded payload: the corresponding `EmittedEvent`,
+    /// `ViewCommand`, or `StateMutation`
+    pub payload: Buffer,
+}
+
+impl JsStreamItem {
+    fn new(kind: &str, payload: &impl serde::Serialize) -> Self {
+        Self {
+            kind: kind.to_string(),
+            payload: Buffer::from(rmp_serde::to_vec(payload).unwrap_or_default()),
+        }
+    }
+}
+
+/// Forwards every event/view-command/mutation recorded during one
+/// `execute_handler_streaming` call into an unbounded channel, so the
+/// (synchronous, lock-held) call into [`EventSink`] methods never blocks on
+/// the JS side; a task spawned alongside the execution drains the channel
+/// and marshals each item through the caller's `ThreadsafeFunction`.
+#[derive(Debug)]
+struct StreamingSink {
+    tx: mpsc::UnboundedSender<JsStreamItem>,
+}
+
+impl EventSink for StreamingSink {
+    fn on_event(&self, _panel_id: &str, _handler_id: &str, event: &EmittedEvent) {
+        let _ = self.tx.send(JsStreamItem::new("event", event));
+    }
+
+    fn on_view_command(&self, _panel_id: &str, _handler_id: &str, command: &ViewCommand) {
+        let _ = self.tx.send(JsStreamItem::new("view", command));
+    }
+
+    fn on_mutation(&self, _panel_id: &str, _handler_id: &str, mutation: &StateMutation) {
+        let _ = self.tx.send(JsStreamItem::new("mutation", mutation));
+    }
+}
+
 /// JavaScript-friendly async result for resumption
 #[napi(object)]
 pub struct JsAsyncResult {
@@ -268,10 +365,16 @@ pub struct JsRuntimeStats {
     pub available_instances: usize,
     /// Cache hit rate (0.0 - 1.0)
     pub cache_hit_rate: f64,
+    /// Fraction of idle instance releases reused in place (0.0 - 1.0)
+    pub fast_reuse_hit_rate: f64,
     /// Average execution time in microseconds
     pub avg_execution_time_us: u64,
     /// Total memory in bytes
     pub total_memory_bytes: u64,
+    /// Idle/suspended instances currently mid low-memory episode
+    pub low_memory_pending_instances: usize,
+    /// Whether instances are using the 64-bit memory ABI
+    pub memory64_enabled: bool,
 }
 
 /// The WASM runtime wrapper exposed to Node.js
@@ -295,13 +398,19 @@ impl NexusRuntime {
         })
     }
 
-    /// Execute a handler in the WASM sandbox
+    /// Execute a handler in the WASM sandbox. `execution_id`, if given
+    /// (typically one returned by [`Self::create_abort_handle`]), lets a
+    /// later [`Self::cancel`] call abort this execution before
+    /// `timeout_ms` elapses, returning a `JsWasmResult` with `status:
+    /// "error"` and `code: "CANCELLED"` instead of the handler's normal
+    /// result.
     #[napi]
     pub async fn execute_handler(
         &self,
         handler_code: String,
         context: JsWasmContext,
         timeout_ms: Option<u32>,
+        execution_id: Option<String>,
     ) -> Result<JsWasmResult> {
         let inner = self.inner.read().await;
         let runtime = inner
@@ -309,16 +418,147 @@ impl NexusRuntime {
             .ok_or_else(|| napi::Error::from_reason("Runtime has been shut down"))?;
 
         let wasm_context = WasmContext::try_from(context)?;
-        let timeout = timeout_ms.unwrap_or(5000);
+        let limits = ResourceLimits {
+            timeout_ms: timeout_ms.unwrap_or(5000),
+            ..ResourceLimits::default()
+        };
 
         let result = runtime
-            .execute_handler(&handler_code, wasm_context, timeout)
+            .execute_handler(&handler_code, wasm_context, &limits, execution_id.as_deref())
             .await
             .map_err(|e| napi::Error::from_reason(format!("Execution failed: {}", e)))?;
 
         Ok(JsWasmResult::from(result))
     }
 
+    /// Mint a fresh execution id to pass as `execution_id` into
+    /// [`Self::execute_handler`], so that execution can later be aborted via
+    /// [`Self::cancel`] before its timeout elapses.
+    #[napi]
+    pub async fn create_abort_handle(&self) -> Result<String> {
+        let inner = self.inner.read().await;
+        let runtime = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("Runtime has been shut down"))?;
+        Ok(runtime.create_abort_handle())
+    }
+
+    /// Abort the in-flight execution running under `execution_id`. Returns
+    /// `false` if no execution is currently running under that id (already
+    /// finished, or the id was never used).
+    #[napi]
+    pub async fn cancel(&self, execution_id: String) -> Result<bool> {
+        let inner = self.inner.read().await;
+        let runtime = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("Runtime has been shut down"))?;
+        Ok(runtime.cancel(&execution_id))
+    }
+
+    /// Execute a handler, forwarding each event, view command, and state
+    /// mutation to `on_emit` as soon as it's recorded instead of only once
+    /// execution finishes. The final `JsWasmResult` is still returned at the
+    /// end for metrics and status, but its `events`/`view_commands` fields
+    /// may be empty since everything was already delivered via `on_emit`.
+    #[napi]
+    pub async fn execute_handler_streaming(
+        &self,
+        handler_code: String,
+        context: JsWasmContext,
+        timeout_ms: Option<u32>,
+        on_emit: ThreadsafeFunction<JsStreamItem, ErrorStrategy::CalleeHandled>,
+    ) -> Result<JsWasmResult> {
+        let inner = self.inner.read().await;
+        let runtime = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("Runtime has been shut down"))?;
+
+        let wasm_context = WasmContext::try_from(context)?;
+        let limits = ResourceLimits {
+            timeout_ms: timeout_ms.unwrap_or(5000),
+            ..ResourceLimits::default()
+        };
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let sink: Arc<dyn EventSink> = Arc::new(StreamingSink { tx });
+        let (done_tx, mut done_rx) = oneshot::channel::<()>();
+
+        let drain_task = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    biased;
+                    item = rx.recv() => match item {
+                        Some(item) => on_emit.call(Ok(item), ThreadsafeFunctionCallMode::NonBlocking),
+                        None => break,
+                    },
+                    _ = &mut done_rx => {
+                        while let Ok(item) = rx.try_recv() {
+                            on_emit.call(Ok(item), ThreadsafeFunctionCallMode::NonBlocking);
+                        }
+                        break;
+                    }
+                }
+            }
+        });
+
+        let result = runtime
+            .execute_handler_streaming(&handler_code, wasm_context, &limits, sink)
+            .await
+            .map_err(|e| napi::Error::from_reason(format!("Execution failed: {}", e)));
+
+        let _ = done_tx.send(());
+        let _ = drain_task.await;
+
+        Ok(JsWasmResult::from(result?))
+    }
+
+    /// Register host-side JS answerers for extension calls, each keyed as
+    /// `"extension.method"` (e.g. `"http.get"`). Every registered function
+    /// receives that call's arguments MessagePack-encoded and must resolve
+    /// with a MessagePack-encoded `RuntimeValue`, or reject to fail the call.
+    ///
+    /// Covered calls are resolved inline by `execute_handler`'s op-driver the
+    /// moment the handler makes them, without the full suspend/`resume_handler`
+    /// FFI round-trip — see [`WasmRuntime::register_host_ops`]. Calls with no
+    /// registered answerer still fall back to that round-trip as before.
+    /// Registering the same key again replaces the previous answerer.
+    #[napi]
+    pub async fn register_host_ops(
+        &self,
+        ops: HashMap<String, ThreadsafeFunction<Buffer, ErrorStrategy::CalleeHandled>>,
+    ) -> Result<()> {
+        let inner = self.inner.read().await;
+        let runtime = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("Runtime has been shut down"))?;
+
+        let wrapped: HashMap<String, Arc<HostOpFn>> = ops
+            .into_iter()
+            .map(|(key, tsfn)| {
+                let tsfn = Arc::new(tsfn);
+                let op: Arc<HostOpFn> = Arc::new(move |args: Vec<RuntimeValue>| {
+                    let tsfn = Arc::clone(&tsfn);
+                    Box::pin(async move {
+                        let payload = Buffer::from(rmp_serde::to_vec(&args).unwrap_or_default());
+                        match tsfn.call_async::<Buffer>(Ok(payload)).await {
+                            Ok(buf) => rmp_serde::from_slice(&buf).map_err(|e| {
+                                WasmError::new(
+                                    ErrorCode::InternalError,
+                                    format!("Failed to decode host op result: {}", e),
+                                )
+                            }),
+                            Err(e) => Err(WasmError::new(ErrorCode::InternalError, e.to_string())),
+                        }
+                    })
+                });
+                (key, op)
+            })
+            .collect();
+
+        runtime.register_host_ops(wrapped);
+        Ok(())
+    }
+
     /// Pre-compile handler code to bytecode
     #[napi]
     pub async fn precompile_handler(&self, handler_code: String) -> Result<Buffer> {
@@ -335,13 +575,15 @@ impl NexusRuntime {
         Ok(Buffer::from(bytecode))
     }
 
-    /// Execute pre-compiled bytecode
+    /// Execute pre-compiled bytecode. `execution_id` behaves exactly as it
+    /// does on [`Self::execute_handler`].
     #[napi]
     pub async fn execute_compiled_handler(
         &self,
         bytecode: Buffer,
         context: JsWasmContext,
         timeout_ms: Option<u32>,
+        execution_id: Option<String>,
     ) -> Result<JsWasmResult> {
         let inner = self.inner.read().await;
         let runtime = inner
@@ -349,16 +591,98 @@ impl NexusRuntime {
             .ok_or_else(|| napi::Error::from_reason("Runtime has been shut down"))?;
 
         let wasm_context = WasmContext::try_from(context)?;
-        let timeout = timeout_ms.unwrap_or(5000);
+        let limits = ResourceLimits {
+            timeout_ms: timeout_ms.unwrap_or(5000),
+            ..ResourceLimits::default()
+        };
 
         let result = runtime
-            .execute_compiled_handler(&bytecode, wasm_context, timeout)
+            .execute_compiled_handler(&bytecode, wasm_context, &limits, execution_id.as_deref())
             .await
             .map_err(|e| napi::Error::from_reason(format!("Execution failed: {}", e)))?;
 
         Ok(JsWasmResult::from(result))
     }
 
+    /// Run many independent handlers concurrently in one call instead of
+    /// the caller awaiting a separate `execute_handler`/
+    /// `execute_compiled_handler` per job — see
+    /// [`WasmRuntime::execute_batch`]. `results[i]` corresponds to
+    /// `jobs[i]`; a job erroring or timing out yields its own error
+    /// `JsWasmResult` rather than aborting the rest of the batch. The same
+    /// `timeout_ms` applies to every job in the batch.
+    #[napi]
+    pub async fn execute_batch(
+        &self,
+        jobs: Vec<JsBatchJob>,
+        timeout_ms: Option<u32>,
+    ) -> Result<Vec<JsWasmResult>> {
+        let inner = self.inner.read().await;
+        let runtime = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("Runtime has been shut down"))?;
+
+        let limits = ResourceLimits {
+            timeout_ms: timeout_ms.unwrap_or(5000),
+            ..ResourceLimits::default()
+        };
+
+        let jobs = jobs
+            .into_iter()
+            .map(BatchJob::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+        let results = runtime.execute_batch(jobs, &limits).await;
+
+        Ok(results.into_iter().map(JsWasmResult::from).collect())
+    }
+
+    /// Capture a freshly-compiled handler's post-init instance as a
+    /// persistable buffer, so it can be restored later (in this process or
+    /// another one) via `restore_from_snapshot` without repeating
+    /// compilation or linear-memory initialization
+    #[napi]
+    pub async fn snapshot_instance(&self, handler_code: String) -> Result<Buffer> {
+        let inner = self.inner.read().await;
+        let runtime = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("Runtime has been shut down"))?;
+
+        let snapshot = runtime
+            .snapshot_instance(&handler_code)
+            .await
+            .map_err(|e| napi::Error::from_reason(format!("Snapshot failed: {}", e)))?;
+
+        Ok(Buffer::from(snapshot))
+    }
+
+    /// Restore a buffer produced by `snapshot_instance` and execute it
+    #[napi]
+    pub async fn restore_from_snapshot(
+        &self,
+        snapshot: Buffer,
+        context: JsWasmContext,
+        timeout_ms: Option<u32>,
+    ) -> Result<JsWasmResult> {
+        let inner = self.inner.read().await;
+        let runtime = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("Runtime has been shut down"))?;
+
+        let wasm_context = WasmContext::try_from(context)?;
+        let limits = ResourceLimits {
+            timeout_ms: timeout_ms.unwrap_or(5000),
+            ..ResourceLimits::default()
+        };
+
+        let result = runtime
+            .restore_from_snapshot(&snapshot, wasm_context, &limits)
+            .await
+            .map_err(|e| napi::Error::from_reason(format!("Restore failed: {}", e)))?;
+
+        Ok(JsWasmResult::from(result))
+    }
+
     /// Resume a suspended handler execution
     #[napi]
     pub async fn resume_handler(
@@ -396,8 +720,11 @@ impl NexusRuntime {
             active_instances: stats.active_instances,
             available_instances: stats.available_instances,
             cache_hit_rate: stats.cache_hit_rate,
+            fast_reuse_hit_rate: stats.fast_reuse_hit_rate,
             avg_execution_time_us: stats.avg_execution_time_us,
             total_memory_bytes: stats.total_memory_bytes,
+            low_memory_pending_instances: stats.low_memory_pending_instances,
+            memory64_enabled: stats.memory_model == crate::config::MemoryModel::Memory64,
         })
     }
 
@@ -412,10 +739,45 @@ impl NexusRuntime {
         Ok(runtime.get_prometheus_metrics())
     }
 
+    /// Begin (or continue) recording per-handler coverage on every
+    /// subsequent `execute_handler` call; `execute_compiled_handler` calls
+    /// carry no source map and are not recorded. Mirrors Deno's
+    /// `CoverageCollector.start`.
+    #[napi]
+    pub async fn start_coverage(&self) -> Result<()> {
+        let inner = self.inner.read().await;
+        let runtime = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("Runtime has been shut down"))?;
+
+        runtime.start_coverage();
+        Ok(())
+    }
+
+    /// Drain coverage accumulated since the last `take_coverage` (or since
+    /// `start_coverage`, if this is the first call) as a JSON-encoded
+    /// Buffer in V8's `Profiler.takePreciseCoverage` `{result: [{scriptId,
+    /// url, functions: [{functionName, ranges, isBlockCoverage}]}]}` shape,
+    /// so the kernel's test tooling can feed it straight to a c8/istanbul v8
+    /// coverage reporter. Recording stays enabled.
+    #[napi]
+    pub async fn take_coverage(&self) -> Result<Buffer> {
+        let inner = self.inner.read().await;
+        let runtime = inner
+            .as_ref()
+            .ok_or_else(|| napi::Error::from_reason("Runtime has been shut down"))?;
+
+        let report = runtime.take_coverage();
+        let json = serde_json::to_vec(&report)
+            .map_err(|e| napi::Error::from_reason(format!("Failed to encode coverage: {}", e)))?;
+
+        Ok(Buffer::from(json))
+    }
+
     /// Infer capabilities from handler code
     #[napi]
     pub fn infer_capabilities(handler_code: String) -> Vec<String> {
-        CapabilityChecker::infer_from_code(&handler_code)
+        infer_capabilities(&handler_code)
             .into_iter()
             .map(|c| c.to_string())
             .collect()